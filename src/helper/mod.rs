@@ -18,10 +18,27 @@ pub fn query_matches<'tree>(
 }
 
 pub fn build_walker<P: AsRef<Path>>(path: P, types: &[&str]) -> (Walk, Types) {
+    build_walker_excluding(path, types, &[])
+}
+
+/// Like [`build_walker`], but skips directories directly under `path` with one
+/// of the given names, e.g. installed dependencies.
+///
+/// Types without a default definition can be given as `name:glob`.
+pub fn build_walker_excluding<P: AsRef<Path>>(
+    path: P,
+    types: &[&str],
+    excluded_dirs: &'static [&'static str],
+) -> (Walk, Types) {
     let mut types_builder = ignore::types::TypesBuilder::new();
     types_builder.add_defaults();
     for ty in types {
-        types_builder.select(ty);
+        if let Some((name, _)) = ty.split_once(':') {
+            types_builder.add_def(ty).unwrap();
+            types_builder.select(name);
+        } else {
+            types_builder.select(ty);
+        }
     }
     let matcher = types_builder.build().unwrap();
 
@@ -30,6 +47,15 @@ pub fn build_walker<P: AsRef<Path>>(path: P, types: &[&str]) -> (Walk, Types) {
             .git_global(false)
             .parents(false)
             .types(matcher.clone())
+            .filter_entry(move |entry| {
+                let is_excluded = entry.depth() == 1
+                    && entry.file_type().is_some_and(|t| t.is_dir())
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|n| excluded_dirs.contains(&n));
+                !is_excluded
+            })
             .build(),
         matcher,
     )
@@ -0,0 +1,595 @@
+//! Scanning of container images exported locally, as an OCI image layout
+//! directory or a `docker save` tarball.
+//!
+//! The layers are applied in order, honoring whiteouts, into a merged root
+//! filesystem in a temporary directory, where every file matching a manifest
+//! scanner is scanned like any other file. OS package databases are covered by
+//! the dpkg and apk scanners.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::SourcePkgError;
+use crate::{Package, Scanner};
+
+const OCI_INDEX: &str = "index.json";
+const DOCKER_MANIFEST: &str = "manifest.json";
+
+const WHITEOUT_PREFIX: &str = ".wh.";
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// A manifest found in an image.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ImageScanResult {
+    /// Absolute path of the manifest in the image, like `/var/lib/dpkg/status`.
+    pub path: PathBuf,
+    /// Name of the scanner which recognized the manifest.
+    pub scanner: String,
+    pub package: Package,
+    /// Digest of the layer which last wrote the manifest.
+    pub layer: String,
+    /// Digest of the layer which introduced each dependency, by purl.
+    ///
+    /// A dependency listed by every version of a manifest since a layer was
+    /// introduced by that layer, like the packages installed by a `RUN` step
+    /// in a package database rewritten by later steps.
+    pub dependency_layers: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Descriptor {
+    #[serde(default)]
+    media_type: String,
+    digest: String,
+    #[serde(default)]
+    platform: Option<Platform>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Platform {
+    os: String,
+}
+
+/// An OCI image index or image manifest, told apart by their fields.
+#[derive(Debug, Deserialize)]
+struct OciManifest {
+    #[serde(default)]
+    manifests: Vec<Descriptor>,
+    #[serde(default)]
+    layers: Vec<Descriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerManifest {
+    config: String,
+    layers: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DockerConfig {
+    #[serde(default)]
+    rootfs: Option<DockerRootfs>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DockerRootfs {
+    #[serde(default)]
+    diff_ids: Vec<String>,
+}
+
+/// A layer of an image, with the path of its tarball in the image layout.
+#[derive(Debug)]
+struct Layer {
+    path: PathBuf,
+    digest: String,
+}
+
+/// Path of a blob in an OCI image layout, `blobs/<algorithm>/<hex>`.
+fn blob_path(layout: &Path, digest: &str) -> Result<PathBuf, SourcePkgError> {
+    match digest.split_once(':') {
+        Some((algorithm, hex))
+            if !algorithm.is_empty()
+                && !hex.is_empty()
+                && [algorithm, hex]
+                    .iter()
+                    .all(|s| s.chars().all(|c| c.is_ascii_alphanumeric())) =>
+        {
+            Ok(layout.join("blobs").join(algorithm).join(hex))
+        }
+        _ => Err(SourcePkgError::GenericsError2(format!(
+            "Invalid digest: {}",
+            digest
+        ))),
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, SourcePkgError> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// Layers of the image manifest of an OCI image layout, following nested
+/// indexes. The first manifest for an actual platform is picked for
+/// multi-platform images, skipping attestations.
+fn oci_layers(layout: &Path) -> Result<Vec<Layer>, SourcePkgError> {
+    let mut manifest: OciManifest = read_json(&layout.join(OCI_INDEX))?;
+
+    // Bounded, an index can't contain itself
+    for _ in 0..8 {
+        if manifest.manifests.is_empty() {
+            return manifest
+                .layers
+                .into_iter()
+                .map(|l| {
+                    Ok(Layer {
+                        path: blob_path(layout, &l.digest)?,
+                        digest: l.digest,
+                    })
+                })
+                .collect();
+        }
+
+        let descriptor = manifest
+            .manifests
+            .iter()
+            .find(|m| m.platform.as_ref().is_none_or(|p| p.os != "unknown"))
+            .ok_or(SourcePkgError::GenericsError("No image manifest in index"))?;
+        log::debug!("Reading {} {}", descriptor.media_type, descriptor.digest);
+        manifest = read_json(&blob_path(layout, &descriptor.digest)?)?;
+    }
+
+    Err(SourcePkgError::GenericsError(
+        "Too deeply nested image index",
+    ))
+}
+
+/// Layers of the first image of a `docker save` tarball, named after the
+/// digests of their content if the config lists them.
+fn docker_layers(layout: &Path) -> Result<Vec<Layer>, SourcePkgError> {
+    let manifests: Vec<DockerManifest> = read_json(&layout.join(DOCKER_MANIFEST))?;
+    let manifest = manifests
+        .into_iter()
+        .next()
+        .ok_or(SourcePkgError::GenericsError("No image in manifest.json"))?;
+
+    let diff_ids = normalize(Path::new(&manifest.config))
+        .and_then(|p| read_json::<DockerConfig>(&layout.join(p)).ok())
+        .and_then(|c| c.rootfs)
+        .map(|r| r.diff_ids)
+        .filter(|ids| ids.len() == manifest.layers.len());
+
+    manifest
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let path = normalize(Path::new(l)).ok_or_else(|| {
+                SourcePkgError::GenericsError2(format!("Invalid layer path: {}", l))
+            })?;
+            Ok(Layer {
+                path: layout.join(path),
+                digest: diff_ids
+                    .as_ref()
+                    .map_or_else(|| l.clone(), |ids| ids[i].clone()),
+            })
+        })
+        .collect()
+}
+
+/// Opens a possibly compressed tarball.
+fn open_tar(path: &Path) -> Result<tar::Archive<Box<dyn Read>>, SourcePkgError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let magic = reader.fill_buf()?;
+
+    let reader: Box<dyn Read> = if magic.starts_with(GZIP_MAGIC) {
+        Box::new(flate2::read::GzDecoder::new(reader))
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        Box::new(zstd::stream::read::Decoder::with_buffer(reader)?)
+    } else {
+        Box::new(reader)
+    };
+
+    Ok(tar::Archive::new(reader))
+}
+
+/// The path of an entry relative to the root, `None` if it may escape it.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut ret = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(c) => ret.push(c),
+            Component::CurDir | Component::RootDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!ret.as_os_str().is_empty()).then_some(ret)
+}
+
+/// The merged root filesystem of the layers applied so far.
+struct Rootfs {
+    root: PathBuf,
+    /// The scanner to tell manifests apart.
+    scanner: Scanner,
+    /// Layer which last wrote each path, relative to the root.
+    origins: BTreeMap<PathBuf, usize>,
+    /// Layers which wrote each manifest, or a manifest next to it, since it
+    /// was created.
+    versions: HashMap<PathBuf, Vec<usize>>,
+}
+
+impl Rootfs {
+    fn new(root: PathBuf, scanner: Scanner) -> Self {
+        Self {
+            root,
+            scanner,
+            origins: BTreeMap::new(),
+            versions: HashMap::new(),
+        }
+    }
+
+    /// Paths under `rel`, and itself.
+    fn subtree<'a>(&'a self, rel: &'a Path) -> impl Iterator<Item = (&'a PathBuf, &'a usize)> {
+        self.origins
+            .range(rel.to_path_buf()..)
+            .take_while(move |(p, _)| p.starts_with(rel))
+    }
+
+    fn forget(&mut self, rel: &Path) {
+        self.origins.remove(rel);
+        self.versions.remove(rel);
+    }
+
+    /// Removes `rel` and everything under it.
+    fn remove(&mut self, rel: &Path) -> std::io::Result<()> {
+        let path = self.root.join(rel);
+        match std::fs::symlink_metadata(&path) {
+            Ok(m) if m.is_dir() => std::fs::remove_dir_all(&path)?,
+            Ok(_) => std::fs::remove_file(&path)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+
+        let removed = self
+            .subtree(rel)
+            .map(|(p, _)| p.clone())
+            .collect::<Vec<_>>();
+        for p in removed {
+            self.forget(&p);
+        }
+        Ok(())
+    }
+
+    /// Removes the content of the directory `rel` from the layers below
+    /// `layer`.
+    fn make_opaque(&mut self, rel: &Path, layer: usize) -> std::io::Result<()> {
+        let lower = self
+            .subtree(rel)
+            .filter(|(p, l)| p.as_path() != rel && **l < layer)
+            .map(|(p, _)| p.clone())
+            .collect::<Vec<_>>();
+
+        // Children first, directories holding files of this layer are kept
+        for p in lower.iter().rev() {
+            let path = self.root.join(p);
+            let removed = match std::fs::symlink_metadata(&path) {
+                Ok(m) if m.is_dir() => std::fs::remove_dir(&path).is_ok(),
+                Ok(_) => std::fs::remove_file(&path).is_ok(),
+                Err(_) => true,
+            };
+            if removed {
+                self.forget(p);
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes room for `rel`, replacing files in the way of its parents and
+    /// whatever it replaces.
+    fn prepare(&mut self, rel: &Path, is_dir: bool) -> std::io::Result<()> {
+        for ancestor in rel.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty() {
+                break;
+            }
+            if std::fs::symlink_metadata(self.root.join(ancestor)).is_ok_and(|m| !m.is_dir()) {
+                self.remove(ancestor)?;
+                break;
+            }
+        }
+        if let Some(parent) = rel.parent() {
+            std::fs::create_dir_all(self.root.join(parent))?;
+        }
+
+        // A file replacing a file is a new version of it
+        match std::fs::symlink_metadata(self.root.join(rel)) {
+            Ok(m) if m.is_dir() == is_dir => Ok(()),
+            Ok(_) => self.remove(rel),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn added(&mut self, rel: PathBuf, layer: usize, written: &mut Vec<PathBuf>) {
+        if self.scanner.find_scanner(&rel).is_some() {
+            self.versions.entry(rel.clone()).or_default().push(layer);
+            written.push(rel.clone());
+        }
+        self.origins.insert(rel, layer);
+    }
+
+    /// Applies the layer at `path`, returning the manifests it wrote.
+    fn apply(&mut self, path: &Path, layer: usize) -> Result<Vec<PathBuf>, SourcePkgError> {
+        let mut archive = open_tar(path)?;
+        let mut written = vec![];
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let rel = match normalize(&entry.path()?) {
+                Some(rel) => rel,
+                None => continue,
+            };
+            let name = rel
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            if name == OPAQUE_WHITEOUT {
+                if let Some(parent) = rel.parent() {
+                    self.make_opaque(parent, layer)?;
+                }
+                continue;
+            }
+            if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+                self.remove(&rel.with_file_name(hidden))?;
+                continue;
+            }
+
+            match entry.header().entry_type() {
+                tar::EntryType::Directory => {
+                    self.prepare(&rel, true)?;
+                    std::fs::create_dir_all(self.root.join(&rel))?;
+                    self.origins.insert(rel, layer);
+                }
+                tar::EntryType::Regular | tar::EntryType::Continuous => {
+                    self.prepare(&rel, false)?;
+                    let mut file = File::create(self.root.join(&rel))?;
+                    std::io::copy(&mut entry, &mut file)?;
+                    self.added(rel, layer, &mut written);
+                }
+                tar::EntryType::Link => {
+                    let target = entry.link_name()?.as_deref().and_then(normalize);
+                    self.prepare(&rel, false)?;
+                    let target = match target {
+                        Some(t) if self.root.join(&t).is_file() => t,
+                        _ => continue,
+                    };
+                    std::fs::copy(self.root.join(target), self.root.join(&rel))?;
+                    self.added(rel, layer, &mut written);
+                }
+                // Links are not followed when scanning, nor are devices read
+                _ => self.remove(&rel)?,
+            }
+        }
+
+        Ok(written)
+    }
+}
+
+impl Scanner {
+    /// Scans the OCI image layout directory or `docker save` tarball at `path`
+    /// for manifests of any supported type.
+    ///
+    /// Manifests which can't be recognized are skipped.
+    pub async fn scan_image(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<ImageScanResult>, SourcePkgError> {
+        let path = path.as_ref().to_path_buf();
+        let work = tempfile::tempdir()?;
+
+        let layout = if path.is_dir() {
+            path
+        } else {
+            let layout = work.path().join("image");
+            let dest = layout.clone();
+            tokio::task::spawn_blocking(move || -> Result<(), SourcePkgError> {
+                open_tar(&path)?.unpack(&dest)?;
+                Ok(())
+            })
+            .await
+            .unwrap()?;
+            layout
+        };
+
+        let layers = if layout.join(DOCKER_MANIFEST).is_file() {
+            docker_layers(&layout)?
+        } else if layout.join(OCI_INDEX).is_file() {
+            oci_layers(&layout)?
+        } else {
+            return Err(SourcePkgError::GenericsError(
+                "Neither an OCI image layout nor a docker save tarball",
+            ));
+        };
+
+        let root = work.path().join("rootfs");
+        std::fs::create_dir_all(&root)?;
+        let mut rootfs = Rootfs::new(root.clone(), self.clone());
+
+        // Purls of each version of the manifests, while they are on disk
+        let mut listed: HashMap<(PathBuf, usize), HashSet<String>> = HashMap::new();
+
+        for (index, layer) in layers.iter().enumerate() {
+            log::debug!("Applying layer {} {}", index, layer.digest);
+
+            let layer_path = layer.path.clone();
+            let (r, written) = tokio::task::spawn_blocking(move || {
+                let written = rootfs.apply(&layer_path, index);
+                (rootfs, written)
+            })
+            .await
+            .unwrap();
+            rootfs = r;
+
+            // Manifests may be read along with the ones next to them, like the
+            // files of a `status.d` database
+            let dirs = written?
+                .iter()
+                .filter_map(|rel| rel.parent().map(Path::to_path_buf))
+                .collect::<HashSet<_>>();
+            let mut changed = vec![];
+            for (rel, versions) in rootfs.versions.iter_mut() {
+                if rel.parent().is_some_and(|p| dirs.contains(p)) {
+                    if versions.last() != Some(&index) {
+                        versions.push(index);
+                    }
+                    changed.push(rel.clone());
+                }
+            }
+
+            for rel in changed {
+                if let Ok((_, package)) = self.scan(root.join(&rel), &root).await {
+                    let purls = package.dependencies.into_iter().map(|d| d.purl);
+                    listed.insert((rel, index), purls.collect());
+                }
+            }
+        }
+
+        let mut manifests = rootfs.versions.into_iter().collect::<Vec<_>>();
+        manifests.sort();
+
+        let mut ret = vec![];
+        for (rel, versions) in manifests {
+            let (scanner, package) = match self.scan(root.join(&rel), &root).await {
+                Ok(res) => res,
+                Err(SourcePkgError::NotSupported) | Err(SourcePkgError::Skipped) => continue,
+                Err(e) => {
+                    log::warn!("Failed to scan /{}: {}", rel.display(), e);
+                    continue;
+                }
+            };
+
+            let mut dependency_layers = HashMap::new();
+            for dep in &package.dependencies {
+                let mut introduced = versions[versions.len() - 1];
+                for &version in versions.iter().rev().skip(1) {
+                    match listed.get(&(rel.clone(), version)) {
+                        Some(purls) if purls.contains(&dep.purl) => introduced = version,
+                        _ => break,
+                    }
+                }
+                dependency_layers.insert(dep.purl.clone(), layers[introduced].digest.clone());
+            }
+
+            ret.push(ImageScanResult {
+                path: Path::new("/").join(&rel),
+                scanner,
+                package,
+                layer: layers[rootfs.origins[&rel]].digest.clone(),
+                dependency_layers,
+            });
+        }
+
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the results of the image built by the same three layers:
+    ///
+    /// 1. `etc/os-release`, a dpkg database with `base-files`, and manifests in
+    ///    `opt/tool` and `app/legacy`;
+    /// 2. `libc6` added to the database, `app/requirements.txt` and a whiteout
+    ///    of `opt/tool`;
+    /// 3. an opaque `app` with `app/package.json` and `app/requirements.txt`.
+    async fn check(image: &Path, digests: [&str; 3]) {
+        let results = Scanner::new().scan_image(image).await.unwrap();
+
+        let layers = results
+            .iter()
+            .map(|r| {
+                (
+                    r.path.to_str().unwrap(),
+                    r.scanner.as_str(),
+                    r.layer.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layers,
+            vec![
+                ("/app/package.json", "npm", digests[2]),
+                ("/app/requirements.txt", "pypi", digests[2]),
+                ("/var/lib/dpkg/status", "deb", digests[1]),
+            ]
+        );
+
+        assert_eq!(
+            results[2].dependency_layers,
+            maplit::hashmap! {
+                "pkg:deb/debian/base-files@12.4+deb12u5?arch=amd64&distro=debian-12".to_string() => digests[0].to_string(),
+                "pkg:deb/debian/libc6@2.36-9+deb12u4?arch=amd64&distro=debian-12&upstream=glibc".to_string() => digests[1].to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn docker_save() {
+        let image = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/image/docker-save.tar"
+        ));
+
+        check(
+            image,
+            [
+                "sha256:ddc05b921500e0d13e12853b7cb9355c290081f6397a14fc6072af24599dd46e",
+                "sha256:d0b944aaa33b2853138074fe862105170daf65b088eca07ac0207176a7976a02",
+                "sha256:e9af3581e3b712a2c55c195d336178fc27d53afd3c31dc8113ac6f1e5d80458f",
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn oci_layout() {
+        let image = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/image/oci"));
+
+        check(
+            image,
+            [
+                "sha256:e538b99079c5d0eebadc657fc5356fce938debf9b4dd3224ee127505ffcc143f",
+                "sha256:5a50124c34ee5c1da762d8e96a70884005050055c87afe210ce71599bacf3702",
+                "sha256:5b81ffcb7f901180cf049ee55e3b84f0a264ec799fa14c321db46b0a3ec839a7",
+            ],
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn oci_layout_zstd() {
+        let image = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/image/oci-zstd"
+        ));
+
+        check(
+            image,
+            [
+                "sha256:e538b99079c5d0eebadc657fc5356fce938debf9b4dd3224ee127505ffcc143f",
+                "sha256:7574166ac42534688badd0a0338fb90e241e1adda9a4bba647e1e630ae1eecb4",
+                "sha256:d2f6cf1d4ce677219959f70e2b438f7d77c8b0d4d0e00a770442e33a699a9eb5",
+            ],
+        )
+        .await;
+    }
+}
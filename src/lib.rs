@@ -2,10 +2,11 @@
 
 pub mod error;
 pub mod helper;
+pub mod image;
 pub mod pkgs;
 pub mod types;
 
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 pub use pkgs::common::model::{DependentPackage, Package, PackageManifest};
 use std::collections::HashMap;
 use std::path::Path;
@@ -21,6 +22,13 @@ pub struct Scanner {
 
     glob_index_to_scanner_index: HashMap<usize, usize>,
     glob_set: GlobSet,
+
+    /// Patterns with a parent directory, like `status.d/*`, matched against
+    /// the file name and the name of its parent.
+    path_glob_index_to_scanner_index: HashMap<usize, usize>,
+    path_glob_set: GlobSet,
+
+    reachability: pkgs::ReachabilityConfig,
 }
 
 impl Scanner {
@@ -31,6 +39,10 @@ impl Scanner {
         let mut glob_set = GlobSetBuilder::new();
         let mut current_glob_index = 0usize;
 
+        let mut path_glob_index_to_scanner_index = HashMap::new();
+        let mut path_glob_set = GlobSetBuilder::new();
+        let mut current_path_glob_index = 0usize;
+
         let mut supported_types = vec![];
         for (index, scanner) in scanners.iter().enumerate() {
             let supported_type = SupportedType {
@@ -45,6 +57,17 @@ impl Scanner {
             supported_types.push(supported_type);
 
             for pat in scanner.file_name_patterns() {
+                if pat.contains('/') {
+                    let glob = GlobBuilder::new(pat)
+                        .literal_separator(true)
+                        .build()
+                        .expect("Failed to compile pattern");
+                    path_glob_set.add(glob);
+                    path_glob_index_to_scanner_index.insert(current_path_glob_index, index);
+                    current_path_glob_index += 1;
+                    continue;
+                }
+
                 let glob = Glob::new(pat).expect("Failed to compile pattern");
                 glob_set.add(glob);
                 glob_index_to_scanner_index.insert(current_glob_index, index);
@@ -53,6 +76,7 @@ impl Scanner {
         }
 
         let glob_set = glob_set.build().expect("Failed to build glob set");
+        let path_glob_set = path_glob_set.build().expect("Failed to build glob set");
 
         Self {
             scanners,
@@ -60,9 +84,19 @@ impl Scanner {
 
             glob_index_to_scanner_index,
             glob_set,
+
+            path_glob_index_to_scanner_index,
+            path_glob_set,
+
+            reachability: Default::default(),
         }
     }
 
+    pub fn with_reachability_config(mut self, config: pkgs::ReachabilityConfig) -> Self {
+        self.reachability = config;
+        self
+    }
+
     pub async fn scan(
         &self,
         path: impl AsRef<Path>,
@@ -71,21 +105,17 @@ impl Scanner {
         let location = path.as_ref();
         let prefix = prefix.as_ref();
 
-        if let Some(match_idx) = self
-            .glob_set
-            .matches(
-                location
-                    .file_name()
-                    .ok_or(error::SourcePkgError::GenericsError(
-                        "Invalid file name ending in '..'",
-                    ))?,
-            )
-            .first()
-        {
-            let scanner_idx = self.glob_index_to_scanner_index[match_idx];
+        location
+            .file_name()
+            .ok_or(error::SourcePkgError::GenericsError(
+                "Invalid file name ending in '..'",
+            ))?;
+
+        if let Some(scanner_idx) = self.find_scanner(location) {
             let scanner = &self.scanners[scanner_idx];
             let ctx = pkgs::RecognizeContext {
                 prefix: prefix.to_path_buf(),
+                reachability: self.reachability.clone(),
             };
 
             return match scanner.recognize_with_config(location, &ctx).await {
@@ -97,6 +127,20 @@ impl Scanner {
         Err(error::SourcePkgError::NotSupported)
     }
 
+    /// Index of the scanner for the file at `location`, if any.
+    fn find_scanner(&self, location: &Path) -> Option<usize> {
+        let file_name = location.file_name()?;
+        if let Some(match_idx) = self.glob_set.matches(file_name).first() {
+            return Some(self.glob_index_to_scanner_index[match_idx]);
+        }
+
+        let parent_name = location.parent()?.file_name()?;
+        self.path_glob_set
+            .matches(Path::new(parent_name).join(file_name))
+            .first()
+            .map(|match_idx| self.path_glob_index_to_scanner_index[match_idx])
+    }
+
     pub fn supported_types(&self) -> &[SupportedType] {
         &self.types
     }
@@ -0,0 +1,201 @@
+//! Packages installed on Alpine systems, from `lib/apk/db/installed`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use packageurl::PackageUrl;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::os_release::{self, OsRelease};
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+/// Parses the records of an installed database, with the single letter keys
+/// of their lines.
+///
+/// Keys repeated in a record, like the `F:` and `R:` lines of the files of a
+/// package, keep their first value.
+pub fn parse_records(content: &str) -> Vec<HashMap<String, String>> {
+    let mut ret = vec![];
+    let mut fields = HashMap::new();
+
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            if !fields.is_empty() {
+                ret.push(std::mem::take(&mut fields));
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            fields
+                .entry(key.to_string())
+                .or_insert_with(|| value.to_string());
+        }
+    }
+    if !fields.is_empty() {
+        ret.push(fields);
+    }
+
+    ret
+}
+
+/// The name of a dependency or provided name like `so:libc.musl-x86_64.so.1=1`
+/// or `musl>=1.2`, `None` for conflicts like `!busybox-suid`.
+fn dependency_name(spec: &str) -> Option<&str> {
+    if spec.starts_with('!') {
+        return None;
+    }
+    spec.split(['<', '>', '=', '~'])
+        .next()
+        .filter(|n| !n.is_empty())
+}
+
+/// Installed packages of the records of an installed database, with the
+/// packages depending on them as parents.
+pub fn build_graph(
+    records: &[HashMap<String, String>],
+    release: Option<&OsRelease>,
+) -> Vec<DependentPackage> {
+    let namespace = release.map_or("alpine", |r| r.id.as_str());
+    let distro = release.and_then(|r| r.distro());
+
+    let installed = records
+        .iter()
+        .filter_map(|fields| {
+            let name = fields.get("P")?;
+            let version = fields.get("V")?;
+
+            let mut purl = PackageUrl::new("apk", name.as_str()).ok()?;
+            purl.with_namespace(namespace)
+                .with_version(version.as_str());
+            if let Some(arch) = fields.get("A") {
+                purl.add_qualifier("arch", arch.as_str()).ok()?;
+            }
+            if let Some(distro) = &distro {
+                purl.add_qualifier("distro", distro.as_str()).ok()?;
+            }
+            if let Some(origin) = fields.get("o").filter(|o| *o != name) {
+                purl.add_qualifier("upstream", origin.as_str()).ok()?;
+            }
+
+            Some((fields, purl.to_string()))
+        })
+        .collect::<Vec<_>>();
+
+    // Shared objects, commands and virtual names resolve to the packages
+    // providing them
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, (fields, _)) in installed.iter().enumerate() {
+        index.insert(fields["P"].as_str(), i);
+    }
+    for (i, (fields, _)) in installed.iter().enumerate() {
+        let provides = fields.get("p").map(String::as_str).unwrap_or_default();
+        for name in provides.split_whitespace().filter_map(dependency_name) {
+            index.entry(name).or_insert(i);
+        }
+    }
+
+    let mut dependencies = installed
+        .iter()
+        .map(|(fields, purl)| DependentPackage {
+            purl: purl.clone(),
+            requirement: fields["V"].clone(),
+            is_resolved: true,
+            declared_license: fields.get("L").cloned().unwrap_or_default(),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    for (i, (fields, purl)) in installed.iter().enumerate() {
+        let depends = fields.get("D").map(String::as_str).unwrap_or_default();
+        for name in depends.split_whitespace().filter_map(dependency_name) {
+            match index.get(name) {
+                Some(&child) if child != i => {
+                    dependencies[child].parents.insert(purl.clone());
+                    dependencies[child].relation.insert(Relation::Indirect);
+                }
+                Some(_) => {}
+                None => log::debug!("{} depends on {}, which is not installed", purl, name),
+            }
+        }
+    }
+
+    for dep in dependencies.iter_mut() {
+        if dep.parents.is_empty() {
+            dep.relation.insert(Relation::Direct);
+        }
+    }
+
+    dependencies
+}
+
+pub struct ApkInstalled {}
+
+impl ApkInstalled {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for ApkInstalled {
+    fn get_name(&self) -> String {
+        "apk".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "apk-installed".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        // `<root>/lib/apk/db/installed`
+        let root = path
+            .parent()
+            .filter(|p| p.ends_with("lib/apk/db"))
+            .and_then(|p| p.ancestors().nth(3))
+            .ok_or(SourcePkgError::NotSupported)?;
+
+        let content = tokio::fs::read_to_string(path).await?;
+        let release = os_release::read(root);
+
+        Ok(Package {
+            dependencies: build_graph(&parse_records(&content), release.as_ref()),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["installed"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn installed() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/apk/rootfs/lib/apk/db/installed"
+        ));
+
+        let package = ApkInstalled::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 3);
+
+        let musl = "pkg:apk/alpine/musl@1.2.4_git20230717-r4?arch=x86_64&distro=alpine-3.19.1";
+        let busybox = "pkg:apk/alpine/busybox@1.36.1-r15?arch=x86_64&distro=alpine-3.19.1";
+        let bundle = "pkg:apk/alpine/ca-certificates-bundle@20240226-r0?arch=x86_64&distro=alpine-3.19.1&upstream=ca-certificates";
+
+        // Through `so:libc.musl-x86_64.so.1`
+        assert_eq!(deps[musl].parents, maplit::hashset! {busybox.to_string()});
+        assert_eq!(deps[musl].relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(deps[busybox].relation, maplit::hashset! {Relation::Direct});
+
+        assert_eq!(deps[bundle].declared_license, "MPL-2.0 AND MIT");
+        assert_eq!(deps[busybox].declared_license, "GPL-2.0-only");
+    }
+}
@@ -0,0 +1,284 @@
+//! The Maven artifacts pinned by rules_jvm_external in `maven_install.json`,
+//! with their SHA-256 checksums and dependency graph.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::Value as Json;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::{maven_purl, split_coordinates};
+
+/// A pinned artifact, by the key the dependencies refer to it with.
+struct Pinned {
+    key: String,
+    coordinates: String,
+    version: String,
+    sha256: Option<String>,
+    children: Vec<String>,
+}
+
+/// The artifacts of the current format: `artifacts` keyed by
+/// `group:artifact[:packaging]` with the checksums of their classifiers,
+/// `jar` for none, and `dependencies` keyed by `group:artifact[:packaging
+/// [:classifier]]`.
+fn pinned_artifacts(lock: &Json) -> Vec<Pinned> {
+    let artifacts = lock
+        .get("artifacts")
+        .and_then(Json::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let dependencies = lock.get("dependencies").and_then(Json::as_object);
+    let children = |key: &str| -> Vec<String> {
+        dependencies
+            .and_then(|deps| deps.get(key)?.as_array())
+            .map(|deps| {
+                deps.iter()
+                    .filter_map(Json::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut ret = vec![];
+    for (key, artifact) in &artifacts {
+        let version = artifact
+            .get("version")
+            .and_then(Json::as_str)
+            .unwrap_or_default();
+        let shasums = artifact
+            .get("shasums")
+            .and_then(Json::as_object)
+            .cloned()
+            .unwrap_or_default();
+        for (classifier, sha256) in &shasums {
+            // Not fetched, or not a dependency of the build
+            let sha256 = match sha256.as_str() {
+                Some(sha256) if classifier != "sources" => sha256,
+                _ => continue,
+            };
+            let (key, coordinates) = match classifier.as_str() {
+                "jar" => (key.clone(), key.clone()),
+                classifier => {
+                    let mut parts = key.splitn(3, ':');
+                    let group = parts.next().unwrap_or_default();
+                    let artifact = parts.next().unwrap_or_default();
+                    let packaging = parts.next().unwrap_or("jar");
+                    let coordinates =
+                        format!("{}:{}:{}:{}", group, artifact, packaging, classifier);
+                    (coordinates.clone(), coordinates)
+                }
+            };
+            ret.push(Pinned {
+                children: children(&key),
+                key,
+                coordinates,
+                version: version.to_string(),
+                sha256: Some(sha256.to_string()),
+            });
+        }
+    }
+    ret
+}
+
+/// The artifacts of the `dependency_tree` of the older format, keyed by
+/// their coordinates with the version.
+fn dependency_tree(tree: &Json) -> Vec<Pinned> {
+    let artifacts = tree
+        .get("dependencies")
+        .and_then(Json::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let mut ret = vec![];
+    for artifact in &artifacts {
+        let coord = match artifact.get("coord").and_then(Json::as_str) {
+            Some(coord) => coord,
+            None => continue,
+        };
+        let (coordinates, version) = match split_coordinates(coord) {
+            Some(split) => split,
+            None => continue,
+        };
+        ret.push(Pinned {
+            key: coord.to_string(),
+            coordinates: coordinates.to_string(),
+            version: version.to_string(),
+            sha256: artifact
+                .get("sha256")
+                .and_then(Json::as_str)
+                .map(str::to_string),
+            children: artifact
+                .get("dependencies")
+                .and_then(Json::as_array)
+                .map(|deps| {
+                    deps.iter()
+                        .filter_map(Json::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        });
+    }
+    ret
+}
+
+/// Parses a `maven_install.json`, the artifacts depending on an artifact
+/// being its parents. Artifacts no other artifact depends on are direct
+/// dependencies.
+pub fn parse(content: &str) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let lock: Json = serde_json::from_str(content)?;
+    let pinned = match lock.get("dependency_tree") {
+        Some(tree) => dependency_tree(tree),
+        None => pinned_artifacts(&lock),
+    };
+
+    let mut dependencies = vec![];
+    let mut index = HashMap::new();
+    let mut children = vec![];
+    for artifact in &pinned {
+        let purl = match maven_purl(
+            &artifact.coordinates,
+            Some(&artifact.version),
+            artifact.sha256.as_deref(),
+        ) {
+            Some(purl) => purl,
+            None => {
+                log::warn!("Invalid coordinates {}", artifact.coordinates);
+                continue;
+            }
+        };
+        index.insert(artifact.key.as_str(), dependencies.len());
+        children.push(&artifact.children);
+        dependencies.push(DependentPackage {
+            purl,
+            requirement: artifact.version.clone(),
+            scope: "compile".into(),
+            is_runtime: true,
+            is_resolved: true,
+            ..Default::default()
+        });
+    }
+
+    for (parent, keys) in children.iter().enumerate() {
+        let purl = dependencies[parent].purl.clone();
+        for key in keys.iter() {
+            match index.get(key.as_str()) {
+                Some(&child) => {
+                    dependencies[child].parents.insert(purl.clone());
+                }
+                None => log::debug!("{} depends on {}, which is not pinned", purl, key),
+            }
+        }
+    }
+    for dep in dependencies.iter_mut() {
+        if dep.parents.is_empty() {
+            dep.relation.insert(Relation::Direct);
+        } else {
+            dep.relation.insert(Relation::Indirect);
+        }
+    }
+
+    Ok(dependencies)
+}
+
+pub struct MavenInstall {}
+
+impl MavenInstall {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for MavenInstall {
+    fn get_name(&self) -> String {
+        "maven".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "maven-install".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Java".into(),
+            dependencies: parse(&content)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["*maven_install.json"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn maven_install() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/bazel/lock/maven_install.json"
+        ));
+
+        let package = MavenInstall::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+
+        let guava = "pkg:maven/com.google.guava/guava@32.1.3-jre?checksum=sha256:6d4e2b5a67a6ad2c1f7e5f8c3c5e7b4b4f4a4e5d2a1c0b9e8f7d6c5b4a3f2e1d";
+        assert_eq!(deps[guava].relation, maplit::hashset! {Relation::Direct});
+        assert!(deps[guava].is_resolved);
+
+        let failureaccess = deps["pkg:maven/com.google.guava/failureaccess@1.0.1?checksum=sha256:a171ee4c734dd2da837e4b16be9df4661afab72a41adaf31eb84dfdaf936ca26"];
+        assert_eq!(
+            failureaccess.relation,
+            maplit::hashset! {Relation::Indirect}
+        );
+        assert_eq!(failureaccess.parents, maplit::hashset! {guava.to_string()});
+
+        let epoll = deps["pkg:maven/io.netty/netty-transport-native-epoll@4.1.100.Final?checksum=sha256:0f4b2a8e3d6c9b1a7e5f4d3c2b1a0f9e8d7c6b5a4f3e2d1c0b9a8f7e6d5c4b3a&classifier=linux-x86_64"];
+        assert_eq!(epoll.parents, maplit::hashset! {guava.to_string()});
+    }
+
+    #[test]
+    fn dependency_tree() {
+        let lock = r#"{
+  "dependency_tree": {
+    "__AUTOGENERATED_FILE_DO_NOT_MODIFY_THIS_FILE_MANUALLY": 1,
+    "dependencies": [
+      {
+        "coord": "junit:junit:4.13.2",
+        "dependencies": ["org.hamcrest:hamcrest-core:1.3"],
+        "sha256": "8E495B634469D64FB8ACFA3495A065CBACC8A0FFF55CE1E31007BE4C16DC57D3"
+      },
+      {
+        "coord": "org.hamcrest:hamcrest-core:1.3",
+        "dependencies": [],
+        "sha256": "66fdef91e9739348df7a096aa384a5685f4e875584cce89386a7a47251c4d8e9"
+      }
+    ],
+    "version": "0.1.0"
+  }
+}"#;
+        let deps = parse(lock).unwrap();
+        assert_eq!(deps.len(), 2);
+        assert_eq!(
+            deps[0].purl,
+            "pkg:maven/junit/junit@4.13.2?checksum=sha256:8e495b634469d64fb8acfa3495a065cbacc8a0fff55ce1e31007be4c16dc57d3"
+        );
+        assert_eq!(deps[0].relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(deps[1].parents, maplit::hashset! {deps[0].purl.clone()});
+    }
+}
@@ -0,0 +1,75 @@
+//! Bazel modules, and the Maven artifacts pinned by rules_jvm_external.
+
+use std::path::{Path, PathBuf};
+
+use packageurl::PackageUrl;
+
+pub mod maven_install;
+pub mod module;
+pub mod starlark;
+
+/// The purl of the module `name` of the Bazel Central Registry.
+pub fn bazel_purl(name: &str, version: Option<&str>) -> Option<String> {
+    let mut purl = PackageUrl::new("bazel", name).ok()?;
+    if let Some(version) = version.filter(|v| !v.is_empty()) {
+        purl.with_version(version);
+    }
+    Some(purl.to_string())
+}
+
+/// The purl of the Maven artifact `coordinates`, like `group:artifact`,
+/// `group:artifact:packaging` or `group:artifact:packaging:classifier`.
+pub fn maven_purl(
+    coordinates: &str,
+    version: Option<&str>,
+    sha256: Option<&str>,
+) -> Option<String> {
+    let mut parts = coordinates.split(':');
+    let group = parts.next()?;
+    let artifact = parts.next()?;
+    let packaging = parts.next();
+    let classifier = parts.next();
+
+    let mut purl = PackageUrl::new("maven", artifact).ok()?;
+    purl.with_namespace(group);
+    if let Some(version) = version.filter(|v| !v.is_empty()) {
+        purl.with_version(version);
+    }
+    if let Some(classifier) = classifier {
+        purl.add_qualifier("classifier", classifier).ok()?;
+    }
+    if let Some(packaging) = packaging.filter(|p| *p != "jar") {
+        purl.add_qualifier("type", packaging).ok()?;
+    }
+    if let Some(sha256) = sha256 {
+        purl.add_qualifier("checksum", format!("sha256:{}", sha256.to_lowercase()))
+            .ok()?;
+    }
+    Some(purl.to_string())
+}
+
+/// Splits Maven coordinates with a version, `group:artifact[:packaging
+/// [:classifier]]:version`, into the coordinates and the version.
+pub fn split_coordinates(coordinates: &str) -> Option<(&str, &str)> {
+    let (coordinates, version) = coordinates.rsplit_once(':')?;
+    coordinates.contains(':').then_some((coordinates, version))
+}
+
+/// The path of the file of a label of the main repository, like
+/// `//third_party:requirements_lock.txt`, in the workspace at `root`.
+pub fn label_path(root: &Path, label: &str) -> Option<PathBuf> {
+    let label = label
+        .strip_prefix("@@")
+        .or_else(|| label.strip_prefix('@'))
+        .unwrap_or(label);
+    let (package, target) = match label.strip_prefix("//") {
+        Some(label) => label.split_once(':').unwrap_or((label, "")),
+        // Labels of other repositories
+        None if label.contains("//") => return None,
+        None => ("", label.trim_start_matches(':')),
+    };
+    if target.is_empty() {
+        return None;
+    }
+    Some(root.join(package).join(target))
+}
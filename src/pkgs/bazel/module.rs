@@ -0,0 +1,481 @@
+//! Bazel modules: the `bazel_dep`s of `MODULE.bazel`, the Maven artifacts and
+//! pip requirements of its extensions, and the modules selected in
+//! `MODULE.bazel.lock`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSetBuilder};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value as Json;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::python::pyrequirements::PyRequirements;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::starlark::{calls, Call};
+use super::{bazel_purl, label_path, maven_purl, split_coordinates};
+
+/// Whether the requirements file at `path` is already read by the
+/// requirements scanner.
+fn is_scanned_requirements(path: &Path) -> bool {
+    lazy_static! {
+        static ref PATTERNS: globset::GlobSet = {
+            let mut set = GlobSetBuilder::new();
+            for pat in PyRequirements::new().file_name_patterns() {
+                if let Ok(glob) = GlobBuilder::new(pat).literal_separator(true).build() {
+                    set.add(glob);
+                }
+            }
+            set.build().unwrap()
+        };
+    }
+
+    let file_name = match path.file_name() {
+        Some(file_name) => Path::new(file_name),
+        None => return false,
+    };
+    let with_parent = path
+        .parent()
+        .and_then(Path::file_name)
+        .map(|parent| Path::new(parent).join(file_name));
+    PATTERNS.is_match(file_name) || with_parent.is_some_and(|p| PATTERNS.is_match(p))
+}
+
+/// The requirements of a lock compiled by rules_python or pip-compile,
+/// without the `--hash` options the requirements parser does not support.
+fn parse_requirements_lock(path: &Path) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    lazy_static! {
+        static ref HASH: Regex = Regex::new(r"\\\s*\n|\s--hash[=\s]\S+").unwrap();
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    let mut content = HASH.replace_all(&content, " ").into_owned();
+    content.push('\n');
+    PyRequirements::parse_requirement_content(&content)
+}
+
+/// A `bazel_dep`, in the `dev` scope for a `dev_dependency`.
+fn bazel_dep(call: &Call) -> Option<DependentPackage> {
+    let name = call.string("name")?;
+    let is_dev = call.flag("dev_dependency");
+    Some(DependentPackage {
+        purl: bazel_purl(name, None)?,
+        requirement: call.string("version").unwrap_or_default().to_string(),
+        scope: if is_dev { "dev" } else { "" }.to_string(),
+        is_runtime: !is_dev,
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
+
+/// A Maven artifact of `maven.install` or `maven.artifact`, declared by its
+/// coordinates with the version.
+fn maven_artifact(coordinates: &str, version: &str, is_test: bool) -> Option<DependentPackage> {
+    let scope = if is_test { "test" } else { "compile" };
+    Some(DependentPackage {
+        purl: maven_purl(coordinates, None, None)?,
+        requirement: version.to_string(),
+        scope: scope.to_string(),
+        is_runtime: !is_test,
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
+
+/// Reads a `MODULE.bazel` of the workspace at `root`, with its `bazel_dep`s
+/// unless `with_bazel_deps` is false. The artifacts of the `maven.install`s
+/// with a `lock_file` are left to the `maven_install.json` scanner, and the
+/// `requirements_lock` of the `pip.parse`s are read unless the requirements
+/// scanner already does, in the scope of their hub.
+pub fn parse(content: &str, root: &Path, with_bazel_deps: bool) -> Package {
+    let calls = calls(content);
+
+    let mut package = Package {
+        primary_language: "Starlark".into(),
+        ..Default::default()
+    };
+
+    // The Maven repositories, and whether they are pinned
+    let mut pinned = HashMap::new();
+    for call in &calls {
+        if let Some(("maven", "install")) = call.callee.split_once('.') {
+            let name = call.string("name").unwrap_or("maven");
+            let is_pinned = call
+                .string("lock_file")
+                .and_then(|label| label_path(root, label))
+                .is_some_and(|path| path.exists());
+            *pinned.entry(name).or_insert(false) |= is_pinned;
+        }
+    }
+    let is_pinned = |call: &Call| {
+        pinned
+            .get(call.string("name").unwrap_or("maven"))
+            .copied()
+            .unwrap_or(false)
+    };
+
+    for call in &calls {
+        let method = call.callee.rsplit('.').next().unwrap_or_default();
+        match (call.callee.as_str(), method) {
+            ("module", _) => {
+                package.name = call.string("name").unwrap_or_default().to_string();
+                package.version = call.string("version").unwrap_or_default().to_string();
+            }
+            ("bazel_dep", _) if with_bazel_deps => package.dependencies.extend(bazel_dep(call)),
+            (_, "install") if call.kwargs.contains_key("artifacts") && !is_pinned(call) => {
+                let artifacts = call.kwargs["artifacts"].strings();
+                for (coordinates, version) in artifacts.into_iter().filter_map(split_coordinates) {
+                    package
+                        .dependencies
+                        .extend(maven_artifact(coordinates, version, false));
+                }
+            }
+            (_, "artifact") if !is_pinned(call) => {
+                let (group, artifact) = match (call.string("group"), call.string("artifact")) {
+                    (Some(group), Some(artifact)) => (group, artifact),
+                    _ => continue,
+                };
+                let mut coordinates = format!("{}:{}", group, artifact);
+                if let Some(classifier) = call.string("classifier") {
+                    let packaging = call.string("packaging").unwrap_or("jar");
+                    coordinates = format!("{}:{}:{}", coordinates, packaging, classifier);
+                } else if let Some(packaging) = call.string("packaging") {
+                    coordinates = format!("{}:{}", coordinates, packaging);
+                }
+                let version = call.string("version").unwrap_or_default();
+                package.dependencies.extend(maven_artifact(
+                    &coordinates,
+                    version,
+                    call.flag("testonly"),
+                ));
+            }
+            (_, "parse") => {
+                let path = match call
+                    .string("requirements_lock")
+                    .and_then(|label| label_path(root, label))
+                {
+                    Some(path) if path.exists() && !is_scanned_requirements(&path) => path,
+                    _ => continue,
+                };
+                let hub = call.string("hub_name").unwrap_or("pip");
+                match parse_requirements_lock(&path) {
+                    Ok(deps) => package.dependencies.extend(deps.into_iter().map(|mut dep| {
+                        dep.scope = hub.to_string();
+                        dep
+                    })),
+                    Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    package
+}
+
+/// The names of the `bazel_dep`s of a `MODULE.bazel`.
+fn declared_modules(content: &str) -> HashSet<String> {
+    calls(content)
+        .iter()
+        .filter(|call| call.callee == "bazel_dep")
+        .filter_map(|call| call.string("name"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Reads the module graph of a `MODULE.bazel.lock`, its `moduleDepGraph`
+/// keyed by `name@version` with the `<root>` module, or else the modules of
+/// the `registryFileHashes` of the newer lock files. Without a graph, the
+/// modules among `declared` are the direct dependencies.
+pub fn parse_lock(
+    content: &str,
+    declared: &HashSet<String>,
+) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    lazy_static! {
+        static ref SOURCE_JSON: Regex =
+            Regex::new(r"/modules/([^/]+)/([^/]+)/source\.json$").unwrap();
+    }
+
+    let lock: Json = serde_json::from_str(content)?;
+
+    let graph = match lock.get("moduleDepGraph").and_then(Json::as_object) {
+        Some(graph) => graph,
+        None => {
+            let hashes = lock
+                .get("registryFileHashes")
+                .and_then(Json::as_object)
+                .ok_or(SourcePkgError::GenericsError("Invalid MODULE.bazel.lock"))?;
+            let mut seen = HashSet::new();
+            let mut ret = vec![];
+            for url in hashes.keys() {
+                let caps = match SOURCE_JSON.captures(url) {
+                    Some(caps) => caps,
+                    None => continue,
+                };
+                let (name, version) = (&caps[1], &caps[2]);
+                if !seen.insert((name.to_string(), version.to_string())) {
+                    continue;
+                }
+                let relation = match declared.contains(name) {
+                    true => Relation::Direct,
+                    false => Relation::Indirect,
+                };
+                ret.extend(
+                    bazel_purl(name, Some(version)).map(|purl| DependentPackage {
+                        purl,
+                        requirement: version.to_string(),
+                        is_runtime: true,
+                        is_resolved: true,
+                        relation: maplit::hashset! {relation},
+                        ..Default::default()
+                    }),
+                );
+            }
+            return Ok(ret);
+        }
+    };
+
+    let deps_of = |module: &Json| -> Vec<String> {
+        module
+            .get("deps")
+            .and_then(Json::as_object)
+            .map(|deps| {
+                deps.values()
+                    .filter_map(Json::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut dependencies = vec![];
+    let mut index = HashMap::new();
+    for (key, module) in graph {
+        if key == "<root>" {
+            continue;
+        }
+        let name = module
+            .get("name")
+            .and_then(Json::as_str)
+            .unwrap_or_default();
+        let version = module
+            .get("version")
+            .and_then(Json::as_str)
+            .unwrap_or_default();
+        let purl = match bazel_purl(name, Some(version)) {
+            Some(purl) => purl,
+            None => continue,
+        };
+        index.insert(key.as_str(), dependencies.len());
+        dependencies.push(DependentPackage {
+            purl,
+            requirement: version.to_string(),
+            is_runtime: true,
+            is_resolved: !version.is_empty(),
+            ..Default::default()
+        });
+    }
+
+    for (key, module) in graph {
+        let parent = index
+            .get(key.as_str())
+            .map(|&i| dependencies[i].purl.clone());
+        for child in deps_of(module) {
+            let child = match index.get(child.as_str()) {
+                Some(&child) => child,
+                None => continue,
+            };
+            match &parent {
+                Some(parent) => {
+                    dependencies[child].parents.insert(parent.clone());
+                }
+                None => {
+                    dependencies[child].relation.insert(Relation::Direct);
+                }
+            }
+        }
+    }
+    for dep in dependencies.iter_mut() {
+        if dep.relation.is_empty() {
+            dep.relation.insert(Relation::Indirect);
+        }
+    }
+
+    Ok(dependencies)
+}
+
+pub struct ModuleBazel {}
+
+impl ModuleBazel {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for ModuleBazel {
+    fn get_name(&self) -> String {
+        "bazel".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let root = path.parent().unwrap_or(Path::new("."));
+        // The modules are covered by the lock file
+        let with_bazel_deps = !path.with_file_name("MODULE.bazel.lock").exists();
+        Ok(parse(&content, root, with_bazel_deps))
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["MODULE.bazel"]
+    }
+}
+
+pub struct ModuleBazelLock {}
+
+impl ModuleBazelLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for ModuleBazelLock {
+    fn get_name(&self) -> String {
+        "bazel".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "bazel-lock".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let declared = std::fs::read_to_string(path.with_file_name("MODULE.bazel"))
+            .map(|module| declared_modules(&module))
+            .unwrap_or_default();
+        Ok(Package {
+            primary_language: "Starlark".into(),
+            dependencies: parse_lock(&content, &declared)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["MODULE.bazel.lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn module_bazel() {
+        let dir = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/bazel/module"
+        ));
+
+        let package = ModuleBazel::new()
+            .recognize(&dir.join("MODULE.bazel"))
+            .await
+            .unwrap();
+        assert_eq!(package.name, "app");
+        assert_eq!(package.version, "1.0.0");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 8);
+
+        let rules_go = deps["pkg:bazel/rules_go"];
+        assert_eq!(rules_go.requirement, "0.46.0");
+        assert!(rules_go.is_runtime);
+        assert!(!rules_go.is_resolved);
+        assert_eq!(deps["pkg:bazel/buildifier_prebuilt"].scope, "dev");
+        assert!(!deps["pkg:bazel/buildifier_prebuilt"].is_runtime);
+
+        let guava = deps["pkg:maven/com.google.guava/guava"];
+        assert_eq!(guava.requirement, "32.1.3-jre");
+        assert_eq!(guava.scope, "compile");
+        assert_eq!(
+            deps["pkg:maven/io.netty/netty-transport-native-epoll?classifier=linux-x86_64"]
+                .requirement,
+            "4.1.100.Final"
+        );
+        assert_eq!(deps["pkg:maven/junit/junit"].scope, "test");
+        assert!(!deps["pkg:maven/junit/junit"].is_runtime);
+
+        let requests = deps["pkg:pypi/requests@2.31.0"];
+        assert_eq!(requests.requirement, "2.31.0");
+        assert_eq!(requests.scope, "pypi");
+    }
+
+    #[tokio::test]
+    async fn module_bazel_lock() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/bazel/lock"));
+
+        // The modules are read from the lock file, the pinned artifacts from
+        // `maven_install.json`, and `requirements_lock.txt` by itself
+        let package = ModuleBazel::new()
+            .recognize(&dir.join("MODULE.bazel"))
+            .await
+            .unwrap();
+        assert!(package.dependencies.is_empty());
+
+        let package = ModuleBazelLock::new()
+            .recognize(&dir.join("MODULE.bazel.lock"))
+            .await
+            .unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+
+        let rules_jvm = deps["pkg:bazel/rules_jvm_external@6.0"];
+        assert!(rules_jvm.is_resolved);
+        assert_eq!(rules_jvm.relation, maplit::hashset! {Relation::Direct});
+        let bazel_skylib = deps["pkg:bazel/bazel_skylib@1.5.0"];
+        assert_eq!(bazel_skylib.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(
+            bazel_skylib.parents,
+            maplit::hashset! {
+                "pkg:bazel/rules_jvm_external@6.0".to_string(),
+                "pkg:bazel/platforms@0.0.8".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn registry_file_hashes() {
+        let lock = r#"{
+  "lockFileVersion": 13,
+  "registryFileHashes": {
+    "https://bcr.bazel.build/bazel_registry.json": "8a28e4af",
+    "https://bcr.bazel.build/modules/rules_go/0.46.0/MODULE.bazel": "b9b1f8a0",
+    "https://bcr.bazel.build/modules/rules_go/0.46.0/source.json": "0e8b2f4c",
+    "https://bcr.bazel.build/modules/platforms/0.0.8/source.json": "c1f4e2a7"
+  }
+}"#;
+        let deps = parse_lock(lock, &maplit::hashset! {"rules_go".to_string()}).unwrap();
+        assert_eq!(deps.len(), 2);
+        let rules_go = deps
+            .iter()
+            .find(|d| d.purl == "pkg:bazel/rules_go@0.46.0")
+            .unwrap();
+        assert_eq!(rules_go.relation, maplit::hashset! {Relation::Direct});
+        let platforms = deps
+            .iter()
+            .find(|d| d.purl == "pkg:bazel/platforms@0.0.8")
+            .unwrap();
+        assert_eq!(platforms.relation, maplit::hashset! {Relation::Indirect});
+    }
+}
@@ -0,0 +1,296 @@
+//! The calls of Starlark files like `MODULE.bazel`, with their literal
+//! arguments. Other expressions are not evaluated.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    List(Vec<Value>),
+    Bool(bool),
+    /// A name, like a variable.
+    Ident(String),
+    /// Any other expression.
+    Other,
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The strings of a list.
+    pub fn strings(&self) -> Vec<&str> {
+        match self {
+            Value::List(items) => items.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// A call like `bazel_dep(name = "rules_go", version = "0.46.0")` or
+/// `maven.install(...)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub callee: String,
+    pub args: Vec<Value>,
+    pub kwargs: HashMap<String, Value>,
+}
+
+impl Call {
+    pub fn string(&self, name: &str) -> Option<&str> {
+        self.kwargs.get(name).and_then(Value::as_str)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.kwargs.get(name) == Some(&Value::Bool(true))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Punct(char),
+}
+
+fn tokenize(content: &str) -> Vec<Token> {
+    let chars = content.chars().collect::<Vec<_>>();
+    let mut ret = vec![];
+    let mut pos = 0;
+    while pos < chars.len() {
+        let c = chars[pos];
+        if c.is_whitespace() {
+            pos += 1;
+        } else if c == '#' {
+            while pos < chars.len() && chars[pos] != '\n' {
+                pos += 1;
+            }
+        } else if c == '"' || c == '\'' {
+            let triple = chars.get(pos..pos + 3) == Some(&[c, c, c][..]);
+            let quote_len = if triple { 3 } else { 1 };
+            pos += quote_len;
+            let mut s = String::new();
+            while pos < chars.len() {
+                if chars[pos] == '\\' && pos + 1 < chars.len() {
+                    s.push(chars[pos + 1]);
+                    pos += 2;
+                } else if chars[pos] == c
+                    && (!triple || chars.get(pos..pos + 3) == Some(&[c, c, c][..]))
+                {
+                    pos += quote_len;
+                    break;
+                } else {
+                    s.push(chars[pos]);
+                    pos += 1;
+                }
+            }
+            ret.push(Token::Str(s));
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = pos;
+            while pos < chars.len() && (chars[pos].is_alphanumeric() || chars[pos] == '_') {
+                pos += 1;
+            }
+            ret.push(Token::Ident(chars[start..pos].iter().collect()));
+        } else {
+            ret.push(Token::Punct(c));
+            pos += 1;
+        }
+    }
+    ret
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    /// Skips the rest of an expression, up to a delimiter at its level.
+    fn skip_expression(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct('(' | '[' | '{') => depth += 1,
+                Token::Punct(')' | ']' | '}') if depth == 0 => return,
+                Token::Punct(')' | ']' | '}') => depth -= 1,
+                Token::Punct(',') if depth == 0 => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// The dotted name at the position, like `maven.install`.
+    fn dotted_name(&mut self) -> Option<String> {
+        let mut name = match self.peek()? {
+            Token::Ident(ident) => ident.clone(),
+            _ => return None,
+        };
+        self.pos += 1;
+        while self.is_punct('.') {
+            match self.tokens.get(self.pos + 1) {
+                Some(Token::Ident(ident)) => {
+                    name.push('.');
+                    name.push_str(ident);
+                    self.pos += 2;
+                }
+                _ => break,
+            }
+        }
+        Some(name)
+    }
+
+    fn value(&mut self) -> Value {
+        let value = match self.peek() {
+            Some(Token::Str(s)) => {
+                let s = s.clone();
+                self.pos += 1;
+                Value::Str(s)
+            }
+            Some(Token::Punct('[')) => {
+                self.pos += 1;
+                let mut items = vec![];
+                while self.peek().is_some() && !self.is_punct(']') {
+                    let start = self.pos;
+                    items.push(self.value());
+                    if self.is_punct(',') || self.pos == start {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+                Value::List(items)
+            }
+            Some(Token::Ident(_)) => {
+                let name = self.dotted_name().unwrap_or_default();
+                if self.is_punct('(') {
+                    self.call(name);
+                    Value::Other
+                } else {
+                    match name.as_str() {
+                        "True" => Value::Bool(true),
+                        "False" => Value::Bool(false),
+                        _ => Value::Ident(name),
+                    }
+                }
+            }
+            _ => Value::Other,
+        };
+
+        // Like `"a" + VERSION`
+        match self.peek() {
+            Some(Token::Punct(',' | ')' | ']' | '}')) | None => value,
+            _ => {
+                self.skip_expression();
+                Value::Other
+            }
+        }
+    }
+
+    /// The arguments of the call of `callee`, at its opening parenthesis.
+    fn call(&mut self, callee: String) -> Call {
+        self.pos += 1;
+        let mut call = Call {
+            callee,
+            args: vec![],
+            kwargs: HashMap::new(),
+        };
+        while self.peek().is_some() && !self.is_punct(')') {
+            let keyword = match (self.peek(), self.tokens.get(self.pos + 1)) {
+                (Some(Token::Ident(name)), Some(Token::Punct('=')))
+                    if self.tokens.get(self.pos + 2) != Some(&Token::Punct('=')) =>
+                {
+                    Some(name.clone())
+                }
+                _ => None,
+            };
+            match keyword {
+                Some(name) => {
+                    self.pos += 2;
+                    let value = self.value();
+                    call.kwargs.insert(name, value);
+                }
+                None => {
+                    let value = self.value();
+                    call.args.push(value);
+                }
+            }
+            if self.is_punct(',') {
+                self.pos += 1;
+            } else if !self.is_punct(')') {
+                self.skip_expression();
+                // A comma, or a stray closing bracket
+                if !self.is_punct(')') {
+                    self.pos += 1;
+                }
+            }
+        }
+        self.pos += 1;
+        call
+    }
+}
+
+/// The top-level calls of a Starlark file, in order.
+pub fn calls(content: &str) -> Vec<Call> {
+    let mut parser = Parser {
+        tokens: tokenize(content),
+        pos: 0,
+    };
+
+    let mut ret = vec![];
+    while parser.peek().is_some() {
+        match parser.dotted_name() {
+            Some(name) if parser.is_punct('(') => ret.push(parser.call(name)),
+            Some(_) => {}
+            None => parser.pos += 1,
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_calls() {
+        let calls = calls(
+            r#"
+# The module
+module(name = "app", version = "1.0")
+bazel_dep(name = "rules_go", version = "0.46.0", dev_dependency = True)
+maven = use_extension("@rules_jvm_external//:extensions.bzl", "maven")
+maven.install(
+    artifacts = ["junit:junit:4.13.2", "a:b:" + VERSION],
+    repositories = [],
+)
+"#,
+        );
+
+        assert_eq!(
+            calls.iter().map(|c| c.callee.as_str()).collect::<Vec<_>>(),
+            vec!["module", "bazel_dep", "use_extension", "maven.install"]
+        );
+        assert_eq!(calls[1].string("version"), Some("0.46.0"));
+        assert!(calls[1].flag("dev_dependency"));
+        assert_eq!(
+            calls[2].args[0],
+            Value::Str("@rules_jvm_external//:extensions.bzl".into())
+        );
+        assert_eq!(
+            calls[3].kwargs["artifacts"],
+            Value::List(vec![Value::Str("junit:junit:4.13.2".into()), Value::Other])
+        );
+    }
+}
@@ -4,7 +4,7 @@ use cargo_manifest::Error as CargoManifestError;
 use cargo_manifest::Manifest as CargoManifest;
 use maplit::hashset;
 use packageurl::PackageUrl;
-use crate::types::Relation;
+use crate::types::{Reachability, Relation};
 use std::{path::Path, str::FromStr};
 use toml::value::Value;
 
@@ -13,6 +13,8 @@ use crate::pkgs::common::model::{Package, PackageManifest};
 
 use super::common::model::DependentPackage;
 
+mod reachability;
+
 pub struct CargoLock {}
 
 impl CargoLock {
@@ -143,6 +145,7 @@ impl CargoToml {
     }
 
     fn parse_cargo_toml(path: impl AsRef<Path>) -> Result<Package, SourcePkgError> {
+        let path = path.as_ref();
         let metadata = match CargoManifest::from_path(path) {
             Ok(metadata) => metadata,
             Err(err) => match err {
@@ -154,48 +157,80 @@ impl CargoToml {
             },
         };
 
-        let convert = |scope: &str, is_dev: bool| {
-            let scope = scope.to_string();
+        let mut dependencies = vec![];
+        // Name of each dependency in code, `package` renames it
+        let mut crate_names = vec![];
+
+        let mut sections = vec![
+            ("dependencies", metadata.dependencies.unwrap_or_default()),
+            (
+                "dev-dependencies",
+                metadata.dev_dependencies.unwrap_or_default(),
+            ),
+            (
+                "build-dependencies",
+                metadata.build_dependencies.unwrap_or_default(),
+            ),
+        ];
+        // `[target.'cfg(windows)'.dependencies]` and the like
+        for (_, target) in metadata.target.unwrap_or_default() {
+            sections.push(("dependencies", target.dependencies));
+            sections.push(("dev-dependencies", target.dev_dependencies));
+            sections.push(("build-dependencies", target.build_dependencies));
+        }
 
-            move |(name, dep): (String, Dependency)| DependentPackage {
-                purl: PackageUrl::new("cargo", name)
-                    .expect("purl arguments are invalid")
-                    .to_string(),
-                scope: scope.clone(),
-                is_runtime: !is_dev,
-                is_optional: match &dep {
-                    cargo_manifest::Dependency::Simple(_) => false,
-                    cargo_manifest::Dependency::Detailed(detail) => {
-                        detail.optional.unwrap_or(false)
-                    }
-                },
-                is_resolved: false,
-                requirement: match dep {
-                    cargo_manifest::Dependency::Simple(version) => version,
-                    cargo_manifest::Dependency::Detailed(detail) => {
-                        // arbitrary version for None case
-                        detail.version.unwrap_or_else(|| "*".into())
-                    }
-                },
-                parents: Default::default(),
-                relation: hashset! {Relation::Direct},
-                reachable: Default::default(),
+        for (scope, deps) in sections {
+            for (name, dep) in deps {
+                let package_name = match &dep {
+                    Dependency::Detailed(detail) => detail.package.as_deref().unwrap_or(&name),
+                    Dependency::Simple(_) => &name,
+                };
+
+                dependencies.push(DependentPackage {
+                    purl: PackageUrl::new("cargo", package_name)
+                        .expect("purl arguments are invalid")
+                        .to_string(),
+                    scope: scope.to_string(),
+                    is_runtime: scope == "dependencies",
+                    is_optional: match &dep {
+                        cargo_manifest::Dependency::Simple(_) => false,
+                        cargo_manifest::Dependency::Detailed(detail) => {
+                            detail.optional.unwrap_or(false)
+                        }
+                    },
+                    is_resolved: false,
+                    requirement: match dep {
+                        cargo_manifest::Dependency::Simple(version) => version,
+                        cargo_manifest::Dependency::Detailed(detail) => {
+                            // arbitrary version for None case
+                            detail.version.unwrap_or_else(|| "*".into())
+                        }
+                    },
+                    parents: Default::default(),
+                    relation: hashset! {Relation::Direct},
+                    reachable: Default::default(),
+                    declared_license: Default::default(),
+                });
+                crate_names.push(name.replace('-', "_"));
             }
-        };
-
-        let dependencies = metadata
-            .dependencies
-            .unwrap_or_default()
-            .into_iter()
-            .map(convert("dependencies", false));
-
-        let dev_dependencies = metadata
-            .dev_dependencies
-            .unwrap_or_default()
-            .into_iter()
-            .map(convert("dev-dependencies", true));
+        }
 
-        let dependencies = dependencies.chain(dev_dependencies).collect();
+        if let Some(usage) = path.parent().and_then(reachability::walk_reachability) {
+            for (dep, name) in dependencies.iter_mut().zip(&crate_names) {
+                let used = match dep.scope.as_str() {
+                    // Dev-dependencies are only available to tests, benches and examples
+                    "dev-dependencies" => {
+                        usage.dev.contains(name) || usage.production.contains(name)
+                    }
+                    // Build-dependencies are only available to the build script
+                    "build-dependencies" => usage.build.contains(name),
+                    _ => usage.production.contains(name),
+                };
+                if !used {
+                    dep.reachable = Reachability::No;
+                }
+            }
+        }
 
         // TODO: add authors
         let package = Package {
@@ -214,7 +249,11 @@ impl PackageManifest for CargoToml {
     }
 
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        Self::parse_cargo_toml(path)
+        let path = path.to_path_buf();
+        // Parsing the sources is blocking
+        tokio::task::spawn_blocking(move || Self::parse_cargo_toml(path))
+            .await
+            .unwrap()
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
@@ -256,4 +295,47 @@ mod test {
 
         CargoLock::parse_lockfile(filepath).unwrap();
     }
+
+    #[test]
+    fn test_cargo_toml_reachability() {
+        let filepath = PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/cargo/reachability/Cargo.toml"
+        ));
+        let package = CargoToml::parse_cargo_toml(filepath).unwrap();
+        let reachable = |name: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl == format!("pkg:cargo/{}", name))
+                .unwrap()
+                .reachable
+        };
+
+        assert_eq!(reachable("serde_json"), Reachability::Possible);
+        assert_eq!(reachable("anyhow"), Reachability::Possible);
+        assert_eq!(reachable("fancy-regex"), Reachability::Possible);
+        assert_eq!(reachable("unused-crate"), Reachability::No);
+        // Only used by `#[cfg(test)]` code
+        assert_eq!(reachable("test-only"), Reachability::No);
+        assert_eq!(reachable("tempfile"), Reachability::Possible);
+        assert_eq!(reachable("pretty_assertions"), Reachability::No);
+        // Used by `build.rs` only
+        assert_eq!(reachable("cc"), Reachability::Possible);
+        assert_eq!(reachable("bindgen"), Reachability::No);
+        // `[target.'cfg(windows)'.dependencies]`
+        assert_eq!(reachable("winapi"), Reachability::No);
+
+        let find = |name: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl == format!("pkg:cargo/{}", name))
+                .unwrap()
+        };
+        assert_eq!(find("cc").scope, "build-dependencies");
+        assert!(!find("cc").is_runtime);
+        assert_eq!(find("winapi").scope, "dependencies");
+        assert!(find("winapi").is_runtime);
+    }
 }
@@ -0,0 +1,253 @@
+//! Crates referenced by Rust sources, through `use`, `extern crate` and paths.
+
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use tree_sitter::{Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp, query_matches};
+
+static RS_CRATE_QUERY: &str = "
+[
+    (scoped_identifier path: (identifier) @crate)
+    (scoped_type_identifier path: (identifier) @crate)
+    (scoped_use_list path: (identifier) @crate)
+    (use_wildcard (identifier) @crate)
+    (use_declaration argument: (identifier) @crate)
+    (use_as_clause path: (identifier) @crate)
+    (extern_crate_declaration name: (identifier) @crate)
+]
+";
+/// Macro arguments are not parsed, paths in them are matched textually.
+static RS_MACRO_QUERY: &str = "(macro_invocation (token_tree) @tokens)";
+static RS_ATTRIBUTE_QUERY: &str = "(attribute_item (attribute) @attribute)";
+
+/// Directories with code that is only built for tests, benchmarks and examples.
+const DEV_DIRS: &[&str] = &["tests", "benches", "examples"];
+
+lazy_static! {
+    static ref MACRO_PATH: Regex = Regex::new(r"\b([A-Za-z_]\w*)\s*::").unwrap();
+    static ref CFG_TEST: Regex = Regex::new(r"^cfg\s*\(.*\btest\b").unwrap();
+}
+
+/// Crate names referenced by production code, by test-only code and by the
+/// build script.
+#[derive(Debug, Default)]
+pub struct CrateUsage {
+    pub production: HashSet<String>,
+    pub dev: HashSet<String>,
+    pub build: HashSet<String>,
+}
+
+impl CrateUsage {
+    fn extend(&mut self, other: CrateUsage) {
+        self.production.extend(other.production);
+        self.dev.extend(other.dev);
+        self.build.extend(other.build);
+    }
+
+    fn into_dev(self) -> CrateUsage {
+        CrateUsage {
+            dev: self.production.into_iter().chain(self.dev).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn into_build(self) -> CrateUsage {
+        CrateUsage {
+            build: self.production.into_iter().chain(self.build).collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Byte ranges of the items behind `#[cfg(test)]`.
+fn cfg_test_ranges(root: tree_sitter::Node, code_raw: &[u8]) -> Vec<Range<usize>> {
+    lazy_static! {
+        static ref ATTRIBUTE_QUERY: Query =
+            Query::new(&tree_sitter_rust::language(), RS_ATTRIBUTE_QUERY).unwrap();
+    }
+
+    let mut ranges = vec![];
+
+    for captures in query_matches(root, &ATTRIBUTE_QUERY, code_raw) {
+        for capture in captures {
+            let text = capture.node.utf8_text(code_raw).unwrap_or_default();
+            if !CFG_TEST.is_match(text) || text.contains("not(test)") {
+                continue;
+            }
+
+            // The attributed item follows, possibly after other attributes
+            let mut item = capture.node.parent().and_then(|n| n.next_named_sibling());
+            while let Some(node) = item {
+                if node.kind() == "attribute_item" || node.kind().ends_with("comment") {
+                    item = node.next_named_sibling();
+                } else {
+                    break;
+                }
+            }
+
+            if let Some(item) = item {
+                ranges.push(item.byte_range());
+            }
+        }
+    }
+
+    ranges
+}
+
+/// Returns the crates referenced by `code`.
+pub fn process_rs(code: &str) -> Result<CrateUsage, SourcePkgError> {
+    lazy_static! {
+        static ref CRATE_QUERY: Query =
+            Query::new(&tree_sitter_rust::language(), RS_CRATE_QUERY).unwrap();
+        static ref MACRO_QUERY: Query =
+            Query::new(&tree_sitter_rust::language(), RS_MACRO_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut usage = CrateUsage::default();
+
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_rust::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    let test_ranges = cfg_test_ranges(root, code_raw);
+    let mut insert = |name: &str, offset: usize| {
+        if test_ranges.iter().any(|r| r.contains(&offset)) {
+            usage.dev.insert(name.to_string());
+        } else {
+            usage.production.insert(name.to_string());
+        }
+    };
+
+    for captures in query_matches(root, &CRATE_QUERY, code_raw) {
+        for capture in captures {
+            insert(capture.node.utf8_text(code_raw)?, capture.node.start_byte());
+        }
+    }
+
+    for captures in query_matches(root, &MACRO_QUERY, code_raw) {
+        for capture in captures {
+            let text = capture.node.utf8_text(code_raw)?;
+            for caps in MACRO_PATH.captures_iter(text) {
+                insert(&caps[1], capture.node.start_byte());
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+fn walk_dir(path: &Path) -> CrateUsage {
+    let (walk, matcher) = build_walker_excluding(path, &["rust"], &["target"]);
+
+    let mut ret = CrateUsage::default();
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        if match_ftyp(&entry, &matcher).is_none() {
+            continue;
+        }
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        match process_rs(&content) {
+            Ok(usage) => ret.extend(usage),
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+
+    ret
+}
+
+/// Crates referenced by the package in `root`, `None` if it has no sources
+/// in the conventional locations.
+///
+/// `src` is production code, `build.rs` only uses build-dependencies and
+/// `tests`, `benches` and `examples` only use dev-dependencies.
+pub fn walk_reachability(root: &Path) -> Option<CrateUsage> {
+    let mut usage = CrateUsage::default();
+    let mut found = false;
+
+    let src = root.join("src");
+    if src.is_dir() {
+        found = true;
+        usage.extend(walk_dir(&src));
+    }
+
+    let build_script = root.join("build.rs");
+    if build_script.is_file() {
+        found = true;
+        usage.extend(walk_dir(&build_script).into_build());
+    }
+
+    for dir in DEV_DIRS {
+        let path = root.join(dir);
+        if path.is_dir() {
+            found = true;
+            usage.extend(walk_dir(&path).into_dev());
+        }
+    }
+
+    found.then_some(usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rs_crates() {
+        let code = r#"
+extern crate lazy_static;
+use serde::{Deserialize, Serialize};
+use anyhow as error;
+
+fn main() {
+    let value: serde_json::Value = serde_json::from_str("{}").unwrap();
+    let map = indexmap::IndexMap::<String, u32>::new();
+    log::info!("{:?}", regex::Regex::new("a"));
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+}
+"#;
+
+        let usage = process_rs(code).unwrap();
+        for name in [
+            "lazy_static",
+            "serde",
+            "anyhow",
+            "serde_json",
+            "indexmap",
+            "log",
+            "regex",
+        ] {
+            assert!(usage.production.contains(name), "{}", name);
+        }
+        assert!(usage.dev.contains("tempfile"));
+        assert!(!usage.production.contains("tempfile"));
+    }
+}
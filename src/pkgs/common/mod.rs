@@ -1,6 +1,7 @@
 use anyhow::{anyhow, Result};
 
 pub mod model;
+pub mod os_release;
 
 pub fn decode_string(buffer: &[u8]) -> Result<String> {
     let mut failed_encodings = vec![];
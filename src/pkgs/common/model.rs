@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
@@ -61,12 +62,33 @@ pub fn is_manifest_default(path: &Path, patterns: &Vec<String>, extensions: &Vec
     false
 }
 
+/// Options for the reachability analysis of the sources next to a manifest.
+#[derive(Debug, Clone, Default)]
+pub struct ReachabilityConfig {
+    /// Directories with dependency JARs, searched in addition to the local
+    /// Maven repository.
+    pub jar_dirs: Vec<PathBuf>,
+    /// Whether constants resolve to gems the way Rails autoloads them, detected
+    /// from `config/application.rb` if unset.
+    pub rails_autoload: Option<bool>,
+    /// Header paths to the purl of the package providing them, like
+    /// `zlib.h` or `openssl/` for a whole directory, on top of the headers
+    /// found in the Conan cache and vcpkg installed trees.
+    pub header_index: HashMap<String, String>,
+    /// Conan home with the package cache, `$CONAN_HOME` or `~/.conan2` and
+    /// `~/.conan` if unset.
+    pub conan_home: Option<PathBuf>,
+    /// vcpkg root whose `installed` tree is searched, `$VCPKG_ROOT` if unset.
+    pub vcpkg_root: Option<PathBuf>,
+}
+
 #[derive(Debug, Default)]
 pub struct RecognizeContext {
     /// Prefix of the current file.
     ///
     /// A file should not reference any other file outside of its prefix.
     pub prefix: PathBuf,
+    pub reachability: ReachabilityConfig,
 }
 
 #[async_trait::async_trait]
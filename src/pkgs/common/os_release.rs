@@ -0,0 +1,55 @@
+//! Distribution of a root filesystem, from `etc/os-release`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OsRelease {
+    /// Lowercase distribution id, like `debian` or `alpine`.
+    pub id: String,
+    /// Version of the distribution, like `12` or `3.19.1`, empty for rolling
+    /// releases.
+    pub version_id: String,
+}
+
+impl OsRelease {
+    /// The `distro` qualifier of the purls of the packages of the
+    /// distribution, like `debian-12`.
+    pub fn distro(&self) -> Option<String> {
+        (!self.version_id.is_empty()).then(|| format!("{}-{}", self.id, self.version_id))
+    }
+}
+
+/// Parses the `KEY=value` lines of an os-release file.
+pub fn parse(content: &str) -> OsRelease {
+    let mut fields = HashMap::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+                .unwrap_or(value);
+            fields.insert(key.trim(), value.to_string());
+        }
+    }
+
+    OsRelease {
+        id: fields.remove("ID").unwrap_or_default().to_lowercase(),
+        version_id: fields.remove("VERSION_ID").unwrap_or_default(),
+    }
+}
+
+/// Reads the os-release file of the root filesystem at `root`.
+pub fn read(root: &Path) -> Option<OsRelease> {
+    ["etc/os-release", "usr/lib/os-release"]
+        .iter()
+        .find_map(|p| std::fs::read_to_string(root.join(p)).ok())
+        .map(|content| parse(&content))
+        .filter(|release| !release.id.is_empty())
+}
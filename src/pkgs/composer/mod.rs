@@ -0,0 +1,634 @@
+use packageurl::PackageUrl;
+use crate::types::{Reachability, Relation};
+use serde::Deserialize;
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp};
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest, RecognizeContext};
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+
+mod reachability;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ComposerJson {
+    #[serde(default)]
+    require: HashMap<String, String>,
+    #[serde(default)]
+    require_dev: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ComposerLockJson {
+    #[serde(default)]
+    packages: Vec<ComposerLockPackage>,
+    #[serde(default)]
+    packages_dev: Vec<ComposerLockPackage>,
+}
+
+/// `vendor/composer/installed.json`, a bare list before Composer 2.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum InstalledJson {
+    V2 {
+        packages: Vec<ComposerLockPackage>,
+        #[serde(default, rename = "dev-package-names")]
+        dev_package_names: Vec<String>,
+    },
+    V1(Vec<ComposerLockPackage>),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ComposerLockPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    require: HashMap<String, String>,
+    #[serde(default)]
+    replace: HashMap<String, String>,
+    #[serde(default)]
+    provide: HashMap<String, String>,
+    #[serde(default)]
+    autoload: ComposerAutoload,
+}
+
+/// Namespace prefixes of a package, the mapped directories are not needed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ComposerAutoload {
+    #[serde(default)]
+    psr_4: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    psr_0: HashMap<String, serde_json::Value>,
+}
+
+/// Platform packages are provided by the PHP runtime, not installed by Composer.
+fn is_platform_package(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(
+        name.as_str(),
+        "php" | "php-64bit" | "php-ipv6" | "php-zts" | "php-debug" | "hhvm" | "composer"
+    ) || name.starts_with("ext-")
+        || name.starts_with("lib-")
+        || name.starts_with("composer-")
+        || !name.contains('/')
+}
+
+fn make_purl(name: &str) -> String {
+    let (namespace, name) = if let Some((ns, n)) = name.split_once('/') {
+        (Some(ns), n)
+    } else {
+        (None, name)
+    };
+
+    let mut purl = PackageUrl::new("composer", name).unwrap();
+    if let Some(namespace) = namespace {
+        purl.with_namespace(namespace);
+    }
+
+    purl.to_string()
+}
+
+fn make_dep_unresolved(name: &str, constraint: &str, is_runtime: bool) -> DependentPackage {
+    DependentPackage {
+        purl: make_purl(name),
+        requirement: constraint.to_string(),
+        scope: (if is_runtime { "runtime" } else { "dev" }).into(),
+        is_runtime,
+        is_resolved: false,
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    }
+}
+
+fn make_purl_versioned(name: &str, version: &str) -> String {
+    let (namespace, name) = if let Some((ns, n)) = name.split_once('/') {
+        (Some(ns), n)
+    } else {
+        (None, name)
+    };
+
+    let mut purl = PackageUrl::new("composer", name).unwrap();
+    purl.with_version(version);
+    if let Some(namespace) = namespace {
+        purl.with_namespace(namespace);
+    }
+
+    purl.to_string()
+}
+
+/// Builds the dependency graph of installed packages.
+///
+/// Without a manifest, packages that nothing else requires are considered direct.
+fn build_graph(
+    packages: &[ComposerLockPackage],
+    dev_names: &HashSet<String>,
+    manifest: Option<&ComposerJson>,
+) -> Vec<DependentPackage> {
+    // Package names are case-insensitive. Names that are replaced or provided
+    // by an installed package resolve to that package.
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (i, pkg) in packages.iter().enumerate() {
+        index.insert(pkg.name.to_lowercase(), i);
+    }
+    for (i, pkg) in packages.iter().enumerate() {
+        for name in pkg.replace.keys().chain(pkg.provide.keys()) {
+            index.entry(name.to_lowercase()).or_insert(i);
+        }
+    }
+
+    let mut dependencies = packages
+        .iter()
+        .map(|pkg| {
+            let is_dev = dev_names.contains(&pkg.name.to_lowercase());
+            DependentPackage {
+                purl: make_purl_versioned(&pkg.name, &pkg.version),
+                requirement: pkg.version.clone(),
+                scope: (if is_dev { "dev" } else { "runtime" }).into(),
+                is_optional: false,
+                is_runtime: !is_dev,
+                is_resolved: true,
+                ..Default::default()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for (i, pkg) in packages.iter().enumerate() {
+        // `require-dev` is root-only, development deps for other deps are
+        // not installed
+        for name in pkg.require.keys() {
+            if is_platform_package(name) {
+                continue;
+            }
+            match index.get(&name.to_lowercase()) {
+                Some(&child) if child != i => {
+                    let parent = dependencies[i].purl.clone();
+                    dependencies[child].parents.insert(parent);
+                    dependencies[child].relation.insert(Relation::Indirect);
+                }
+                Some(_) => {}
+                None => log::warn!("{} requires {}, which is not installed", pkg.name, name),
+            }
+        }
+    }
+
+    match manifest {
+        Some(manifest) => {
+            let direct = manifest
+                .require
+                .keys()
+                .chain(manifest.require_dev.keys())
+                .filter(|name| !is_platform_package(name))
+                .filter_map(|name| index.get(&name.to_lowercase()));
+            for &i in direct {
+                dependencies[i].relation.insert(Relation::Direct);
+            }
+        }
+        None => {
+            for dep in dependencies.iter_mut() {
+                if dep.parents.is_empty() {
+                    dep.relation.insert(Relation::Direct);
+                }
+            }
+        }
+    }
+
+    dependencies
+}
+
+fn process_installed_json(
+    manifest: Option<&ComposerJson>,
+    path: impl AsRef<Path>,
+) -> Result<Package, SourcePkgError> {
+    let installed: InstalledJson = {
+        let mut file = File::open(path)?;
+        serde_json::from_reader(&mut file)?
+    };
+
+    let (packages, dev_names) = match installed {
+        InstalledJson::V2 {
+            packages,
+            dev_package_names,
+        } => (packages, dev_package_names),
+        InstalledJson::V1(packages) => (packages, vec![]),
+    };
+    let dev_names = dev_names.iter().map(|n| n.to_lowercase()).collect();
+
+    Ok(Package {
+        dependencies: build_graph(&packages, &dev_names, manifest),
+        ..Default::default()
+    })
+}
+
+/// Autoload namespace prefixes of the installed packages, with the purl
+/// (without version) of the package providing them.
+fn autoload_prefixes(root: &Path) -> Vec<(String, String)> {
+    let lock_path = root.join("composer.lock");
+    let installed_path = root.join("vendor/composer/installed.json");
+
+    let packages = if lock_path.exists() {
+        File::open(&lock_path)
+            .map_err(SourcePkgError::from)
+            .and_then(|f| Ok(serde_json::from_reader::<_, ComposerLockJson>(f)?))
+            .map(|lock| lock.packages.into_iter().chain(lock.packages_dev).collect())
+    } else if installed_path.exists() {
+        File::open(&installed_path)
+            .map_err(SourcePkgError::from)
+            .and_then(|f| Ok(serde_json::from_reader::<_, InstalledJson>(f)?))
+            .map(|installed| match installed {
+                InstalledJson::V2 { packages, .. } => packages,
+                InstalledJson::V1(packages) => packages,
+            })
+    } else {
+        Ok(vec![])
+    };
+
+    let packages: Vec<ComposerLockPackage> = match packages {
+        Ok(p) => p,
+        Err(e) => {
+            log::warn!("Failed to read autoload configuration: {}", e);
+            return vec![];
+        }
+    };
+
+    let mut ret = vec![];
+    for pkg in packages {
+        let purl = make_purl(&pkg.name);
+        for prefix in pkg
+            .autoload
+            .psr_4
+            .into_keys()
+            .chain(pkg.autoload.psr_0.into_keys())
+        {
+            ret.push((prefix, purl.clone()));
+        }
+    }
+    ret
+}
+
+fn walk_reachability(path: &Path) -> HashSet<String> {
+    let (walk, matcher) = build_walker_excluding(path, &["php"], &["vendor"]);
+
+    let mut ret = HashSet::new();
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        if match_ftyp(&entry, &matcher).is_none() {
+            continue;
+        }
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        match reachability::process_php(&content) {
+            Ok(names) => ret.extend(names),
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+
+    ret
+}
+
+pub struct PhpComposer {}
+
+impl PhpComposer {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn parse(path: impl AsRef<Path>) -> Result<Package, SourcePkgError> {
+        let path = path.as_ref();
+
+        let manifest: ComposerJson = {
+            let mut file = File::open(path)?;
+            serde_json::from_reader(&mut file)?
+        };
+
+        let lock_path = path.with_file_name("composer.lock");
+        if lock_path.exists() {
+            match process_composer_lock(&manifest, lock_path) {
+                Ok(package) => return Ok(package),
+                Err(err) => {
+                    log::error!("Failed to process composer.lock: {}", err);
+                }
+            }
+        }
+
+        let installed_path = path.with_file_name("vendor/composer/installed.json");
+        if installed_path.exists() {
+            match process_installed_json(Some(&manifest), installed_path) {
+                Ok(package) => return Ok(package),
+                Err(err) => {
+                    log::error!("Failed to process installed.json: {}", err);
+                }
+            }
+        }
+
+        let mut dependencies = vec![];
+
+        for dep in manifest.require.into_iter() {
+            if !is_platform_package(&dep.0) {
+                dependencies.push(make_dep_unresolved(&dep.0, &dep.1, true));
+            }
+        }
+
+        for dep in manifest.require_dev.into_iter() {
+            if !is_platform_package(&dep.0) {
+                dependencies.push(make_dep_unresolved(&dep.0, &dep.1, false));
+            }
+        }
+
+        let package = Package {
+            dependencies,
+            ..Default::default()
+        };
+
+        Ok(package)
+    }
+
+    async fn recognize_with_reachability(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let mut res = Self::parse(path)?;
+
+        if res.dependencies.is_empty() {
+            return Ok(res);
+        }
+
+        let parent = if let Some(parent) = path.parent() {
+            parent.to_path_buf()
+        } else {
+            return Ok(res);
+        };
+
+        let (names, prefixes) = tokio::task::spawn_blocking(move || {
+            (walk_reachability(&parent), autoload_prefixes(&parent))
+        })
+        .await
+        .unwrap();
+
+        log::debug!("Referenced names: {:?}", names);
+
+        // Packages without namespaces (`classmap`, `files`) can't be checked
+        let mapped = prefixes
+            .iter()
+            .map(|(_, purl)| purl.as_str())
+            .collect::<HashSet<_>>();
+        let reachable = prefixes
+            .iter()
+            .filter(|(prefix, _)| {
+                names
+                    .iter()
+                    .any(|name| reachability::matches_prefix(name, prefix))
+            })
+            .map(|(_, purl)| purl.as_str())
+            .collect::<HashSet<_>>();
+
+        for dep in res.dependencies.iter_mut() {
+            if dep.is_runtime && dep.relation.contains(&Relation::Direct) {
+                let purl_without_version = dep.purl.split('@').next().unwrap();
+
+                if mapped.contains(purl_without_version)
+                    && !reachable.contains(purl_without_version)
+                {
+                    dep.reachable = Reachability::No;
+                }
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for PhpComposer {
+    fn get_name(&self) -> String {
+        "composer".into()
+    }
+
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        _context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        self.recognize_with_reachability(path).await
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["composer.json"]
+    }
+}
+
+/// Scanner for `vendor/composer/installed.json`, for trees where only the
+/// installed packages are available.
+pub struct PhpComposerInstalled {}
+
+impl PhpComposerInstalled {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for PhpComposerInstalled {
+    fn get_name(&self) -> String {
+        "composer".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "composer-installed".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let vendor_dir = path
+            .parent()
+            .filter(|p| p.ends_with("vendor/composer"))
+            .and_then(|p| p.parent())
+            .ok_or(SourcePkgError::NotSupported)?;
+
+        if let Some(root) = vendor_dir.parent() {
+            if root.join("composer.json").exists() {
+                // Already covered by the manifest
+                return Err(SourcePkgError::Skipped);
+            }
+        }
+
+        process_installed_json(None, path)
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["installed.json"]
+    }
+}
+
+fn process_composer_lock(
+    manifest: &ComposerJson,
+    path: impl AsRef<Path>,
+) -> Result<Package, SourcePkgError> {
+    let lock: ComposerLockJson = {
+        let mut file = File::open(path)?;
+        serde_json::from_reader(&mut file)?
+    };
+
+    let dev_names = lock
+        .packages_dev
+        .iter()
+        .map(|pkg| pkg.name.to_lowercase())
+        .collect();
+    let packages = lock
+        .packages
+        .into_iter()
+        .chain(lock.packages_dev)
+        .collect::<Vec<_>>();
+
+    let package = Package {
+        dependencies: build_graph(&packages, &dev_names, Some(manifest)),
+        ..Default::default()
+    };
+
+    Ok(package)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[tokio::test]
+    async fn test_parse_composer() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/phpcomposer/AdminPanel/composer.json"
+        ));
+
+        let parser = PhpComposer::new();
+        let p = parser.recognize(filepath).await.unwrap();
+        println!("{:#?}", p);
+    }
+
+    #[tokio::test]
+    async fn test_composer_lock_graph() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/phpcomposer/graph/composer.json"
+        ));
+
+        let p = PhpComposer::new().recognize(filepath).await.unwrap();
+        let find = |name: &str| {
+            p.dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(&format!("pkg:composer/{}@", name)))
+        };
+
+        assert_eq!(p.dependencies.len(), 4);
+        assert!(p.dependencies.iter().all(|d| !d.purl.contains("ext-")));
+
+        let monolog = find("monolog/monolog").unwrap();
+        assert_eq!(monolog.relation, maplit::hashset! {Relation::Direct});
+
+        let psr_log = find("psr/log").unwrap();
+        assert_eq!(psr_log.relation, maplit::hashset! {Relation::Indirect});
+        assert!(psr_log.parents.contains(&monolog.purl));
+
+        // `symfony/polyfill-mbstring` is replaced by `symfony/polyfill`
+        let polyfill = find("symfony/polyfill").unwrap();
+        assert!(polyfill.parents.contains(&monolog.purl));
+
+        let phpunit = find("phpunit/phpunit").unwrap();
+        assert!(phpunit.relation.contains(&Relation::Direct));
+        assert!(!phpunit.is_runtime);
+    }
+
+    #[tokio::test]
+    async fn test_composer_unresolved() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/phpcomposer/unresolved/composer.json"
+        ));
+
+        let p = PhpComposer::new().recognize(filepath).await.unwrap();
+        let mut purls = p
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect::<Vec<_>>();
+        purls.sort();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:composer/monolog/monolog",
+                "pkg:composer/phpunit/phpunit"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_installed_json() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/phpcomposer/installed/vendor/composer/installed.json"
+        ));
+
+        let p = PhpComposerInstalled::new()
+            .recognize(filepath)
+            .await
+            .unwrap();
+        assert_eq!(p.dependencies.len(), 2);
+
+        let monolog = p
+            .dependencies
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:composer/monolog/monolog@"))
+            .unwrap();
+        assert_eq!(monolog.relation, maplit::hashset! {Relation::Direct});
+    }
+
+    #[tokio::test]
+    async fn test_composer_reachability() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/phpcomposer/reachability/composer.json"
+        ));
+
+        let p = PhpComposer::new().recognize(filepath).await.unwrap();
+        let reachable = |name: &str| {
+            p.dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(&format!("pkg:composer/{}@", name)))
+                .unwrap()
+                .reachable
+        };
+
+        assert_eq!(reachable("monolog/monolog"), Reachability::Possible);
+        assert_eq!(reachable("guzzlehttp/guzzle"), Reachability::Possible);
+        assert_eq!(reachable("twig/twig"), Reachability::Possible);
+        // Only `files` autoloading, nothing to match against
+        assert_eq!(
+            reachable("symfony/polyfill-mbstring"),
+            Reachability::Possible
+        );
+        // Only used from `vendor/`
+        assert_eq!(reachable("nesbot/carbon"), Reachability::No);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use tree_sitter::{Parser, Query};
+
+use crate::{error::SourcePkgError, helper::query_matches};
+
+/// Qualified names, both in `use` declarations and in code (`\Foo\Bar::class`).
+static PHP_QUALIFIED_NAME_QUERY: &str = "(qualified_name) @name";
+/// `use Foo;`
+static PHP_USE_NAME_QUERY: &str = "(namespace_use_clause (name) @name)";
+/// `use Foo\{Bar, Baz\Qux};`
+static PHP_USE_GROUP_QUERY: &str = "
+    (namespace_use_declaration
+        (namespace_name) @prefix
+        (namespace_use_group (namespace_use_group_clause (namespace_name) @name)))
+";
+
+fn normalize_name(name: &str) -> String {
+    name.trim_start_matches('\\').to_string()
+}
+
+/// Returns the fully qualified class and namespace names referenced by `code`.
+pub fn process_php(code: &str) -> Result<HashSet<String>, SourcePkgError> {
+    lazy_static::lazy_static! {
+        static ref QUALIFIED_NAME_QUERY: Query =
+            Query::new(&tree_sitter_php::language_php(), PHP_QUALIFIED_NAME_QUERY).unwrap();
+        static ref USE_NAME_QUERY: Query =
+            Query::new(&tree_sitter_php::language_php(), PHP_USE_NAME_QUERY).unwrap();
+        static ref USE_GROUP_QUERY: Query =
+            Query::new(&tree_sitter_php::language_php(), PHP_USE_GROUP_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut names = HashSet::new();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_php::language_php())
+        .unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    for query in [&*QUALIFIED_NAME_QUERY, &*USE_NAME_QUERY] {
+        for captures in query_matches(root, query, code_raw) {
+            for capture in captures {
+                names.insert(normalize_name(capture.node.utf8_text(code_raw)?));
+            }
+        }
+    }
+
+    for captures in query_matches(root, &USE_GROUP_QUERY, code_raw) {
+        let prefix = captures.first().expect("Expected prefix capture");
+        let name = captures.get(1).expect("Expected name capture");
+        names.insert(format!(
+            "{}\\{}",
+            normalize_name(prefix.node.utf8_text(code_raw)?),
+            name.node.utf8_text(code_raw)?
+        ));
+    }
+
+    Ok(names)
+}
+
+/// Whether `name` falls under a PSR-4 or PSR-0 autoload prefix.
+///
+/// PSR-4 prefixes end with `\`, PSR-0 ones may also be pseudo-namespaces like `Twig_`.
+pub fn matches_prefix(name: &str, prefix: &str) -> bool {
+    if prefix.is_empty() {
+        // A fallback directory, any class may come from there
+        return false;
+    }
+
+    name.starts_with(prefix) || format!("{}\\", name).starts_with(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn php_names() {
+        let code = r#"<?php
+namespace App;
+
+use Monolog\Logger;
+use Symfony\Component\{Console\Application, Yaml};
+use Twig_Environment;
+
+$client = new \GuzzleHttp\Client();
+"#;
+
+        let names = process_php(code).unwrap();
+        assert!(names.contains("Monolog\\Logger"));
+        assert!(names.contains("Symfony\\Component\\Console\\Application"));
+        assert!(names.contains("Symfony\\Component\\Yaml"));
+        assert!(names.contains("Twig_Environment"));
+        assert!(names.contains("GuzzleHttp\\Client"));
+
+        assert!(matches_prefix("Monolog\\Logger", "Monolog\\"));
+        assert!(matches_prefix("Twig_Environment", "Twig_"));
+        assert!(!matches_prefix("MonologBundle\\Foo", "Monolog\\"));
+    }
+}
@@ -4,8 +4,11 @@ use packageurl::PackageUrl;
 use crate::types::{DependentPackage, Package};
 use serde::Deserialize;
 
+use crate::pkgs::RecognizeContext;
 use crate::{error::SourcePkgError, PackageManifest};
 
+mod reachability;
+
 /// Dependency graph node for version 0.4.
 #[derive(Debug, Deserialize)]
 struct GraphNodeV04 {
@@ -194,7 +197,24 @@ impl PackageManifest for ConanLock {
     }
 
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        Self::parse(path)
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
+    }
+
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let package = Self::parse(path)?;
+
+        let root = if let Some(parent) = path.parent() {
+            parent
+        } else {
+            return Ok(package);
+        };
+
+        Ok(reachability::mark_reachability(root, &context.reachability, package).await)
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
@@ -205,6 +225,7 @@ impl PackageManifest for ConanLock {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Reachability;
 
     #[test]
     fn v05() {
@@ -227,4 +248,105 @@ mod tests {
         let package = ConanLock::parse(filepath).unwrap();
         println!("{:?}", package);
     }
+
+    #[tokio::test]
+    async fn reachability() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/conan/reachability/conan.lock"
+        ));
+
+        let reachable = |package: &Package, purl: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl.split('@').next() == Some(purl))
+                .unwrap()
+                .reachable
+        };
+
+        // Keep the Conan homes and vcpkg root of this machine out of the index
+        let empty_home = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/conan/empty-home"
+        ))
+        .to_path_buf();
+        let config = crate::pkgs::ReachabilityConfig {
+            conan_home: Some(empty_home.clone()),
+            vcpkg_root: Some(empty_home),
+            ..Default::default()
+        };
+
+        let context = RecognizeContext {
+            reachability: config.clone(),
+            ..Default::default()
+        };
+        let package = ConanLock::new()
+            .recognize_with_config(filepath, &context)
+            .await
+            .unwrap();
+        assert_eq!(
+            reachable(&package, "pkg:conan/zlib"),
+            Reachability::Possible
+        );
+        assert_eq!(
+            reachable(&package, "pkg:conan/openssl"),
+            Reachability::Possible
+        );
+        assert_eq!(reachable(&package, "pkg:conan/fmt"), Reachability::No);
+        // No known headers
+        assert_eq!(
+            reachable(&package, "pkg:conan/nlohmann_json"),
+            Reachability::Possible
+        );
+
+        let spdlog = package
+            .dependencies
+            .iter()
+            .find(|d| d.purl == "pkg:vcpkg/spdlog")
+            .unwrap();
+        assert_eq!(spdlog.scope, "undeclared");
+        assert_eq!(package.dependencies.len(), 5);
+
+        let context = RecognizeContext {
+            reachability: crate::pkgs::ReachabilityConfig {
+                header_index: maplit::hashmap! {
+                    "nlohmann/".to_string() => "pkg:conan/nlohmann_json".to_string(),
+                },
+                ..config.clone()
+            },
+            ..Default::default()
+        };
+        let package = ConanLock::new()
+            .recognize_with_config(filepath, &context)
+            .await
+            .unwrap();
+        assert_eq!(
+            reachable(&package, "pkg:conan/nlohmann_json"),
+            Reachability::No
+        );
+
+        // Headers of the Conan 2 cache
+        let context = RecognizeContext {
+            reachability: crate::pkgs::ReachabilityConfig {
+                conan_home: Some(
+                    Path::new(concat!(
+                        env!("CARGO_MANIFEST_DIR"),
+                        "/testdata/conan/conan2-home"
+                    ))
+                    .to_path_buf(),
+                ),
+                ..config
+            },
+            ..Default::default()
+        };
+        let package = ConanLock::new()
+            .recognize_with_config(filepath, &context)
+            .await
+            .unwrap();
+        assert_eq!(
+            reachable(&package, "pkg:conan/nlohmann_json"),
+            Reachability::No
+        );
+    }
 }
@@ -0,0 +1,469 @@
+//! Reachability of C and C++ packages from the headers included by sources.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use packageurl::PackageUrl;
+use tree_sitter::{Language, Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp, query_matches};
+use crate::pkgs::common::model::ReachabilityConfig;
+use crate::types::{DependentPackage, Package, Reachability, Relation};
+
+static C_INCLUDE_QUERY: &str = "
+(preproc_include path: [(system_lib_string) (string_literal)] @path)
+";
+
+/// Build trees and installed dependencies, which include their own headers.
+const BUILD_DIRS: &[&str] = &["build", "full_deploy", "vcpkg_installed"];
+
+/// Scope of the packages whose headers are included without being declared.
+pub const UNDECLARED_SCOPE: &str = "undeclared";
+
+/// An `#include` directive.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Include {
+    pub path: String,
+    /// `#include <...>` rather than `#include "..."`.
+    pub is_system: bool,
+}
+
+/// Returns the includes of C (`is_c`) or C++ `code`.
+pub fn process_c(code: &str, is_c: bool) -> Result<Vec<Include>, SourcePkgError> {
+    lazy_static::lazy_static! {
+        static ref C_QUERY: Query =
+            Query::new(&tree_sitter_c::language(), C_INCLUDE_QUERY).unwrap();
+        static ref CPP_QUERY: Query =
+            Query::new(&tree_sitter_cpp::language(), C_INCLUDE_QUERY).unwrap();
+    }
+
+    let (language, query): (Language, &Query) = if is_c {
+        (tree_sitter_c::language(), &C_QUERY)
+    } else {
+        (tree_sitter_cpp::language(), &CPP_QUERY)
+    };
+
+    let code_raw = code.as_bytes();
+    let mut includes = vec![];
+
+    let mut parser = Parser::new();
+    parser.set_language(&language).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+
+    for captures in query_matches(tree.root_node(), query, code_raw) {
+        for capture in captures {
+            let text = capture.node.utf8_text(code_raw)?;
+            let is_system = capture.node.kind() == "system_lib_string";
+            let path = text.trim_matches(|c| c == '<' || c == '>' || c == '"');
+            if !path.is_empty() {
+                includes.push(Include {
+                    path: path.to_string(),
+                    is_system,
+                });
+            }
+        }
+    }
+
+    Ok(includes)
+}
+
+/// Maps headers to the packages providing them.
+///
+/// Keys are header paths relative to an include directory (`zlib.h`) or
+/// top-level directories with a trailing slash (`openssl/`), values are purls
+/// without version.
+#[derive(Debug, Default)]
+pub struct HeaderIndex {
+    entries: HashMap<String, HashSet<String>>,
+}
+
+impl HeaderIndex {
+    /// Builds the index from the configured headers, the Conan cache, Conan
+    /// deployments and vcpkg installed trees of the project in `root`.
+    pub fn from_project(root: &Path, config: &ReachabilityConfig) -> Self {
+        let mut index = Self::default();
+
+        for (header, purl) in &config.header_index {
+            let purl = purl.split('@').next().unwrap_or(purl);
+            index.insert(header, purl);
+        }
+
+        let homes = match &config.conan_home {
+            Some(home) => vec![home.clone()],
+            None => default_conan_homes(),
+        };
+        for home in homes {
+            index.add_conan_cache(&home);
+            index.add_conan2_cache(&home);
+        }
+        index.add_conan_deploy(&root.join("full_deploy"));
+
+        index.add_vcpkg_installed(&root.join("vcpkg_installed"));
+        let vcpkg_root = config
+            .vcpkg_root
+            .clone()
+            .or_else(|| std::env::var_os("VCPKG_ROOT").map(PathBuf::from));
+        if let Some(vcpkg_root) = vcpkg_root {
+            index.add_vcpkg_installed(&vcpkg_root.join("installed"));
+        }
+
+        index
+    }
+
+    fn insert(&mut self, header: &str, purl: &str) {
+        self.entries
+            .entry(header.to_string())
+            .or_default()
+            .insert(purl.to_string());
+    }
+
+    /// Adds the top-level entries of an include directory.
+    fn add_include_dir(&mut self, dir: &Path, purl: &str) {
+        let entries = if let Ok(e) = std::fs::read_dir(dir) {
+            e
+        } else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if entry.path().is_dir() {
+                self.insert(&format!("{}/", name), purl);
+            } else {
+                self.insert(&name, purl);
+            }
+        }
+    }
+
+    /// `data/<name>/<version>/<user>/<channel>/package/<id>/include` of a
+    /// Conan 1 cache.
+    fn add_conan_cache(&mut self, home: &Path) {
+        for (name, package_dir) in subdirs(&home.join("data")) {
+            let purl = conan_purl(&name);
+            for (_, version_dir) in subdirs(&package_dir) {
+                for (_, user_dir) in subdirs(&version_dir) {
+                    for (_, channel_dir) in subdirs(&user_dir) {
+                        for (_, id_dir) in subdirs(&channel_dir.join("package")) {
+                            self.add_include_dir(&id_dir.join("include"), &purl);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// `p/<path>/p/include` of a Conan 2 cache, the `packages` table of
+    /// `p/cache.sqlite3` giving the reference of every `<path>`.
+    fn add_conan2_cache(&mut self, home: &Path) {
+        use rusqlite::{Connection, OpenFlags};
+
+        let storage = home.join("p");
+        let db = storage.join("cache.sqlite3");
+        if !db.is_file() {
+            return;
+        }
+
+        let packages =
+            Connection::open_with_flags(&db, OpenFlags::SQLITE_OPEN_READ_ONLY).and_then(|conn| {
+                let mut stmt = conn.prepare("SELECT reference, path FROM packages")?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>();
+                rows
+            });
+        let packages = match packages {
+            Ok(p) => p,
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", db.display(), e);
+                return;
+            }
+        };
+
+        for (reference, path) in packages {
+            // `zlib/1.2.13`, or `zlib/1.2.13@user/channel`
+            let name = reference.split('/').next().unwrap_or(&reference);
+            let purl = conan_purl(name);
+            self.add_include_dir(&storage.join(path).join("p").join("include"), &purl);
+        }
+    }
+
+    /// `host/<name>/<version>/<build type>/<arch>/include` of a Conan
+    /// `full_deploy` deployment.
+    fn add_conan_deploy(&mut self, deploy: &Path) {
+        for (name, package_dir) in subdirs(&deploy.join("host")) {
+            let purl = conan_purl(&name);
+            for (_, version_dir) in subdirs(&package_dir) {
+                for (_, build_type_dir) in subdirs(&version_dir) {
+                    for (_, arch_dir) in subdirs(&build_type_dir) {
+                        self.add_include_dir(&arch_dir.join("include"), &purl);
+                    }
+                }
+            }
+        }
+    }
+
+    /// `vcpkg/info/<port>_<version>_<triplet>.list` files of a vcpkg installed
+    /// tree, listing the files of every port as `<triplet>/include/<header>`.
+    fn add_vcpkg_installed(&mut self, installed: &Path) {
+        let entries = if let Ok(e) = std::fs::read_dir(installed.join("vcpkg").join("info")) {
+            e
+        } else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let port = if let Some(p) = file_name
+                .strip_suffix(".list")
+                .and_then(|stem| stem.split('_').next())
+            {
+                p.to_string()
+            } else {
+                continue;
+            };
+
+            let purl = if let Ok(p) = PackageUrl::new("vcpkg", port.as_str()) {
+                p.to_string()
+            } else {
+                continue;
+            };
+
+            let content = if let Ok(c) = std::fs::read_to_string(entry.path()) {
+                c
+            } else {
+                continue;
+            };
+            for line in content.lines() {
+                let header = if let Some((_, h)) = line.split_once("/include/") {
+                    h
+                } else {
+                    continue;
+                };
+                if header.is_empty() || header.ends_with('/') {
+                    continue;
+                }
+                match header.split_once('/') {
+                    Some((dir, _)) => self.insert(&format!("{}/", dir), &purl),
+                    None => self.insert(header, &purl),
+                }
+            }
+        }
+    }
+
+    /// Packages that may provide the included `path`.
+    pub fn resolve(&self, path: &str) -> HashSet<String> {
+        let mut ret = self.entries.get(path).cloned().unwrap_or_default();
+
+        if let Some((dir, _)) = path.split_once('/') {
+            if let Some(purls) = self.entries.get(&format!("{}/", dir)) {
+                ret.extend(purls.iter().cloned());
+            }
+        }
+
+        ret
+    }
+
+    /// Whether any header of the package is known.
+    pub fn provides(&self, purl: &str) -> bool {
+        self.entries.values().any(|purls| purls.contains(purl))
+    }
+}
+
+fn conan_purl(name: &str) -> String {
+    PackageUrl::new("conan", name).unwrap().to_string()
+}
+
+/// Default Conan homes: `$CONAN_HOME` or `~/.conan2` for Conan 2, and
+/// `$CONAN_USER_HOME/.conan` or `~/.conan` for Conan 1.
+fn default_conan_homes() -> Vec<PathBuf> {
+    let user_home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+
+    let conan2 = std::env::var_os("CONAN_HOME")
+        .map(PathBuf::from)
+        .or_else(|| user_home.as_ref().map(|h| PathBuf::from(h).join(".conan2")));
+    let conan1 = std::env::var_os("CONAN_USER_HOME")
+        .or(user_home)
+        .map(|h| PathBuf::from(h).join(".conan"));
+
+    conan2.into_iter().chain(conan1).collect()
+}
+
+fn subdirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .map(|e| (e.file_name().to_string_lossy().into_owned(), e.path()))
+        .collect()
+}
+
+/// Returns the includes of the sources in `path` that are not headers of the
+/// project itself, `None` if there are no C or C++ sources.
+fn walk_reachability(path: &Path) -> Option<HashSet<String>> {
+    let (walk, matcher) = build_walker_excluding(path, &["c", "cpp"], BUILD_DIRS);
+
+    let mut found = false;
+    let mut includes = HashSet::new();
+    let mut project_headers = vec![];
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        if match_ftyp(&entry, &matcher).is_none() {
+            continue;
+        }
+        found = true;
+
+        let relative = entry.path().strip_prefix(path).unwrap_or(entry.path());
+        project_headers.push(relative.to_string_lossy().replace('\\', "/"));
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        let is_c = entry.path().extension().is_some_and(|e| e == "c");
+        match process_c(&content, is_c) {
+            Ok(file_includes) => {
+                let dir = entry.path().parent().unwrap_or(path);
+                for include in file_includes {
+                    if !include.is_system && dir.join(&include.path).is_file() {
+                        continue;
+                    }
+                    includes.insert(include.path);
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+
+    // Headers found through the include directories of the project
+    includes.retain(|include| {
+        !project_headers
+            .iter()
+            .any(|h| h == include || h.ends_with(&format!("/{}", include)))
+    });
+
+    found.then_some(includes)
+}
+
+/// Marks the direct dependencies of `res` as unreachable if no source in
+/// `root` includes one of their headers.
+///
+/// Packages whose headers are included without being declared are added with
+/// the [`UNDECLARED_SCOPE`] scope. Dependencies without known headers are
+/// left undecided.
+pub async fn mark_reachability(
+    root: &Path,
+    config: &ReachabilityConfig,
+    mut res: Package,
+) -> Package {
+    let root = root.to_path_buf();
+    let config = config.clone();
+    let (includes, index) = tokio::task::spawn_blocking(move || {
+        (
+            walk_reachability(&root),
+            HeaderIndex::from_project(&root, &config),
+        )
+    })
+    .await
+    .unwrap();
+
+    let includes = if let Some(i) = includes {
+        i
+    } else {
+        return res;
+    };
+
+    let mut used: HashMap<String, Vec<&String>> = HashMap::new();
+    for include in &includes {
+        for purl in index.resolve(include) {
+            used.entry(purl).or_default().push(include);
+        }
+    }
+    log::debug!("Included packages: {:?}", used);
+
+    let mut declared = HashSet::new();
+    for dep in res.dependencies.iter_mut() {
+        let purl_without_version = dep.purl.split('@').next().unwrap().to_string();
+
+        if !used.contains_key(&purl_without_version)
+            && dep.is_runtime
+            && dep.relation.contains(&Relation::Direct)
+            && index.provides(&purl_without_version)
+        {
+            dep.reachable = Reachability::No;
+        }
+
+        declared.insert(purl_without_version);
+    }
+
+    let mut undeclared = used
+        .into_iter()
+        .filter(|(purl, _)| !declared.contains(purl))
+        .collect::<Vec<_>>();
+    undeclared.sort();
+
+    for (purl, headers) in undeclared {
+        log::info!("{} is included but not declared: {:?}", purl, headers);
+        res.dependencies.push(DependentPackage {
+            purl,
+            scope: UNDECLARED_SCOPE.to_string(),
+            ..Default::default()
+        });
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn c_includes() {
+        let code = r#"
+#include <stdio.h>
+#include <openssl/ssl.h>
+#include "config.h"
+#ifdef HAVE_ZLIB
+#  include <zlib.h>
+#endif
+"#;
+
+        let includes = process_c(code, true).unwrap();
+        assert!(includes.contains(&Include {
+            path: "openssl/ssl.h".to_string(),
+            is_system: true
+        }));
+        assert!(includes.contains(&Include {
+            path: "config.h".to_string(),
+            is_system: false
+        }));
+        assert!(includes.iter().any(|i| i.path == "zlib.h"));
+
+        let includes = process_c("#include <boost/asio.hpp>\nnamespace a {}", false).unwrap();
+        assert_eq!(includes[0].path, "boost/asio.hpp");
+
+        let mut index = HeaderIndex::default();
+        index.insert("openssl/", "pkg:conan/openssl");
+        index.insert("zlib.h", "pkg:conan/zlib");
+        assert!(index.resolve("openssl/ssl.h").contains("pkg:conan/openssl"));
+        assert!(index.resolve("zlib.h").contains("pkg:conan/zlib"));
+        assert!(index.resolve("stdio.h").is_empty());
+    }
+}
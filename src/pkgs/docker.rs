@@ -0,0 +1,543 @@
+//! `Dockerfile` and `Containerfile` scanner.
+//!
+//! Base images of `FROM` and images of `COPY --from` become `pkg:docker`
+//! purls, or `pkg:oci` ones when pinned by digest. `RUN` instructions go
+//! through the same analysis as shell scripts.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use packageurl::PackageUrl;
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::shell;
+
+/// Scope of the packages installed by `RUN` instructions.
+pub const DOCKER_BUILD_SCOPE: &str = "docker-build";
+/// Scope of the images stages are built from.
+pub const BASE_IMAGE_SCOPE: &str = "base-image";
+
+const DEFAULT_REGISTRY: &str = "docker.io";
+
+lazy_static! {
+    static ref ESCAPE_DIRECTIVE: Regex = Regex::new(r"(?i)^#\s*escape\s*=\s*(\S)\s*$").unwrap();
+    static ref HEREDOC: Regex = Regex::new(r#"<<(-?)["']?([A-Za-z_][A-Za-z0-9_]*)["']?"#).unwrap();
+    static ref VARIABLE: Regex = Regex::new(
+        r"\$(?:\{([A-Za-z_][A-Za-z0-9_]*)(?::([-+])([^}]*))?\}|([A-Za-z_][A-Za-z0-9_]*))"
+    )
+    .unwrap();
+}
+
+#[derive(Debug)]
+struct Instruction {
+    keyword: String,
+    args: String,
+    /// Bodies of the heredocs of the instruction.
+    heredocs: Vec<String>,
+}
+
+/// Splits a Dockerfile into instructions, joining continuation lines.
+fn instructions(content: &str) -> Vec<Instruction> {
+    let mut escape = '\\';
+    let mut lines = content.lines().peekable();
+
+    // Parser directives are only recognized at the very top
+    while let Some(line) = lines.peek() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with('#') {
+            break;
+        }
+        if let Some(caps) = ESCAPE_DIRECTIVE.captures(trimmed) {
+            escape = caps[1].chars().next().unwrap_or('\\');
+        } else if !trimmed.contains('=') {
+            break;
+        }
+        lines.next();
+    }
+
+    let mut ret = vec![];
+    let mut current = String::new();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        if current.is_empty() && (trimmed.is_empty() || trimmed.starts_with('#')) {
+            continue;
+        }
+        if !current.is_empty() && trimmed.starts_with('#') {
+            // Comments may be interleaved with continuation lines
+            continue;
+        }
+
+        let trimmed_end = line.trim_end();
+        if let Some(stripped) = trimmed_end.strip_suffix(escape) {
+            current.push_str(stripped);
+            current.push(' ');
+            continue;
+        }
+        current.push_str(trimmed_end);
+
+        let full = std::mem::take(&mut current);
+        let full = full.trim();
+        let (keyword, args) = full.split_once(char::is_whitespace).unwrap_or((full, ""));
+
+        let mut heredocs = vec![];
+        for caps in HEREDOC.captures_iter(args) {
+            let strip_tabs = &caps[1] == "-";
+            let terminator = &caps[2];
+            let mut body = String::new();
+            for line in lines.by_ref() {
+                let line = if strip_tabs {
+                    line.trim_start_matches('\t')
+                } else {
+                    line
+                };
+                if line.trim_end() == terminator {
+                    break;
+                }
+                body.push_str(line);
+                body.push('\n');
+            }
+            heredocs.push(body);
+        }
+
+        ret.push(Instruction {
+            keyword: keyword.to_ascii_uppercase(),
+            args: args.trim().to_string(),
+            heredocs,
+        });
+    }
+
+    ret
+}
+
+/// Expands `$VAR`, `${VAR}`, `${VAR:-default}` and `${VAR:+alternative}`.
+/// Unknown variables are kept as is.
+fn substitute(value: &str, vars: &HashMap<String, String>) -> String {
+    VARIABLE
+        .replace_all(value, |caps: &regex::Captures| {
+            let name = caps.get(1).or_else(|| caps.get(4)).unwrap().as_str();
+            let var = vars.get(name).filter(|v| !v.is_empty());
+            match (caps.get(2).map(|m| m.as_str()), var) {
+                (Some("-"), Some(v)) => v.clone(),
+                (Some("-"), None) => caps[3].to_string(),
+                (Some("+"), Some(_)) => caps[3].to_string(),
+                (Some("+"), None) => String::new(),
+                (_, Some(v)) => v.clone(),
+                (_, None) => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Splits on whitespace, keeping quoted strings together and removing quotes.
+fn split_words(value: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut quote = None;
+    let mut in_word = false;
+
+    for c in value.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+
+    words
+}
+
+/// `ARG NAME=value` and `ENV NAME=value` definitions, `ENV NAME value` too.
+fn definitions(args: &str) -> Vec<(String, Option<String>)> {
+    let words = split_words(args);
+    if words.len() >= 2 && !words[0].contains('=') {
+        // Legacy `ENV NAME value` form
+        return vec![(words[0].clone(), Some(words[1..].join(" ")))];
+    }
+
+    words
+        .into_iter()
+        .map(|w| match w.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (w, None),
+        })
+        .collect()
+}
+
+/// Removes the leading `--flag=value` options of an instruction.
+fn strip_flags(args: &str) -> (Vec<&str>, &str) {
+    let mut flags = vec![];
+    let mut rest = args.trim_start();
+    while rest.starts_with("--") {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        flags.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    (flags, rest)
+}
+
+/// Returns the purl of an image reference like `ghcr.io/org/app:1.0@sha256:...`.
+fn image_dependency(image: &str, scope: &str, is_runtime: bool) -> Option<DependentPackage> {
+    let (reference, digest) = match image.split_once('@') {
+        Some((r, d)) => (r, Some(d)),
+        None => (image, None),
+    };
+
+    let slash = reference.rfind('/').unwrap_or(0);
+    let (repository, tag) = match reference.rfind(':') {
+        Some(i) if i > slash => (&reference[..i], Some(&reference[i + 1..])),
+        _ => (reference, None),
+    };
+
+    let (registry, path) = match repository.split_once('/') {
+        Some((first, rest))
+            if first.contains('.') || first.contains(':') || first == "localhost" =>
+        {
+            (Some(first), rest)
+        }
+        _ => (None, repository),
+    };
+    if path.is_empty() {
+        return None;
+    }
+    let (namespace, name) = match path.rsplit_once('/') {
+        Some((ns, n)) => (Some(ns), n),
+        None => (None, path),
+    };
+
+    let (purl, requirement) = if let Some(digest) = digest {
+        let mut purl = PackageUrl::new("oci", name).ok()?;
+        purl.with_version(digest);
+
+        let registry = registry.unwrap_or(DEFAULT_REGISTRY);
+        let repository_url = if registry == DEFAULT_REGISTRY && namespace.is_none() {
+            format!("{}/library/{}", registry, path)
+        } else {
+            format!("{}/{}", registry, path)
+        };
+        purl.add_qualifier("repository_url", repository_url).ok()?;
+        if let Some(tag) = tag {
+            purl.add_qualifier("tag", tag).ok()?;
+        }
+        (purl, digest)
+    } else {
+        let mut purl = PackageUrl::new("docker", name).ok()?;
+        if let Some(ns) = namespace {
+            purl.with_namespace(ns);
+        }
+        let tag = tag.unwrap_or("latest");
+        purl.with_version(tag);
+        if let Some(registry) = registry {
+            purl.add_qualifier("repository_url", registry).ok()?;
+        }
+        (purl, tag)
+    };
+
+    Some(DependentPackage {
+        purl: purl.to_string(),
+        requirement: requirement.to_string(),
+        scope: scope.to_string(),
+        is_runtime,
+        is_resolved: digest.is_some(),
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
+
+/// The shell script of a `RUN` instruction, exec form included.
+fn run_script(instruction: &Instruction) -> String {
+    let (_, command) = strip_flags(&instruction.args);
+
+    let mut script = if command.starts_with('[') {
+        match serde_json::from_str::<Vec<String>>(command) {
+            Ok(argv) => argv
+                .iter()
+                .map(|a| format!("'{}'", a.replace('\'', "'\\''")))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Err(_) => command.to_string(),
+        }
+    } else {
+        command.to_string()
+    };
+
+    for body in &instruction.heredocs {
+        script.push('\n');
+        script.push_str(body);
+    }
+
+    script
+}
+
+#[derive(Debug, Default)]
+struct Stage {
+    name: Option<String>,
+    /// Index of the stage this one is built from.
+    parent: Option<usize>,
+    vars: HashMap<String, String>,
+    /// Dependencies, `is_runtime` is decided once all stages are known.
+    dependencies: Vec<DependentPackage>,
+}
+
+fn parse(content: &str) -> Result<Package, SourcePkgError> {
+    let mut global_args: HashMap<String, String> = HashMap::new();
+    let mut stages: Vec<Stage> = vec![];
+
+    let find_stage = |stages: &[Stage], name: &str| {
+        stages.iter().position(|s| {
+            s.name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })
+    };
+
+    for instruction in instructions(content) {
+        match instruction.keyword.as_str() {
+            "ARG" => {
+                for (name, value) in definitions(&instruction.args) {
+                    match stages.last_mut() {
+                        None => {
+                            let value = substitute(&value.unwrap_or_default(), &global_args);
+                            global_args.insert(name, value);
+                        }
+                        Some(stage) => {
+                            let value = match value {
+                                Some(v) => Some(substitute(&v, &stage.vars)),
+                                None => global_args.get(&name).cloned(),
+                            };
+                            if let Some(v) = value {
+                                stage.vars.insert(name, v);
+                            }
+                        }
+                    }
+                }
+            }
+            "ENV" => {
+                if let Some(stage) = stages.last_mut() {
+                    for (name, value) in definitions(&instruction.args) {
+                        let value = substitute(&value.unwrap_or_default(), &stage.vars);
+                        stage.vars.insert(name, value);
+                    }
+                }
+            }
+            "FROM" => {
+                let (_, rest) = strip_flags(&instruction.args);
+                let words = split_words(rest);
+                let image = if let Some(i) = words.first() {
+                    substitute(i, &global_args)
+                } else {
+                    log::warn!("FROM without image");
+                    continue;
+                };
+                let name = match words.get(1) {
+                    Some(w) if w.eq_ignore_ascii_case("as") => words.get(2).cloned(),
+                    _ => None,
+                };
+
+                let mut stage = Stage {
+                    name,
+                    ..Default::default()
+                };
+
+                if let Some(parent) = find_stage(&stages, &image) {
+                    stage.parent = Some(parent);
+                    stage.vars = stages[parent].vars.clone();
+                } else if image.contains('$') {
+                    log::warn!("Unresolved base image {}", image);
+                } else if !image.eq_ignore_ascii_case("scratch") {
+                    stage
+                        .dependencies
+                        .extend(image_dependency(&image, BASE_IMAGE_SCOPE, false));
+                }
+
+                stages.push(stage);
+            }
+            "COPY" | "ADD" => {
+                let (flags, _) = strip_flags(&instruction.args);
+                let from = flags.iter().find_map(|f| f.strip_prefix("--from="));
+                let from = match (from, stages.last()) {
+                    (Some(f), Some(s)) => substitute(f, &s.vars),
+                    _ => continue,
+                };
+                if from.parse::<usize>().is_ok() || from.contains('$') {
+                    continue;
+                }
+                if find_stage(&stages, &from).is_none() {
+                    let dep = image_dependency(&from, DOCKER_BUILD_SCOPE, false);
+                    stages.last_mut().unwrap().dependencies.extend(dep);
+                }
+            }
+            "RUN" => {
+                let stage = if let Some(s) = stages.last_mut() {
+                    s
+                } else {
+                    continue;
+                };
+                let script = substitute(&run_script(&instruction), &stage.vars);
+                stage
+                    .dependencies
+                    .extend(shell::parse_script(&script, DOCKER_BUILD_SCOPE)?);
+            }
+            _ => {}
+        }
+    }
+
+    // The final stage, and the stages it's built from, end up in the image
+    let mut runtime = vec![false; stages.len()];
+    let mut current = stages.len().checked_sub(1);
+    while let Some(i) = current {
+        if runtime[i] {
+            break;
+        }
+        runtime[i] = true;
+        current = stages[i].parent;
+    }
+
+    let mut dependencies: Vec<DependentPackage> = vec![];
+    for (stage, is_runtime) in stages.into_iter().zip(runtime) {
+        for mut dep in stage.dependencies {
+            dep.is_runtime = is_runtime;
+            if let Some(existing) = dependencies.iter_mut().find(|d| {
+                d.purl == dep.purl && d.requirement == dep.requirement && d.scope == dep.scope
+            }) {
+                existing.is_runtime |= dep.is_runtime;
+                continue;
+            }
+            dependencies.push(dep);
+        }
+    }
+
+    Ok(Package {
+        dependencies,
+        ..Default::default()
+    })
+}
+
+pub struct Dockerfile {}
+
+impl Dockerfile {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Dockerfile {
+    fn get_name(&self) -> String {
+        "docker".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        parse(&content)
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &[
+            "Dockerfile",
+            "*.Dockerfile",
+            "*.dockerfile",
+            "Containerfile",
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn dockerfile() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/docker/Dockerfile"
+        ));
+
+        let package = Dockerfile::new().recognize(filepath).await.unwrap();
+        let find = |purl: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl == purl)
+                .unwrap_or_else(|| panic!("{} not found in {:?}", purl, package.dependencies))
+        };
+
+        let golang = find("pkg:docker/golang@1.21-alpine");
+        assert_eq!(golang.scope, BASE_IMAGE_SCOPE);
+        assert!(!golang.is_runtime);
+        assert!(!find("pkg:apk/git@2.43.0-r0").is_runtime);
+        find("pkg:apk/make");
+        find("pkg:golang/github.com/go-delve/delve/cmd/dlv@v1.22.0");
+        assert!(!find("pkg:npm/yarn@1.22.19").is_runtime);
+
+        let python = package
+            .dependencies
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:oci/python@sha256:"))
+            .unwrap();
+        assert!(python.purl.contains("tag=3.12-slim"));
+        assert!(python
+            .purl
+            .contains("repository_url=docker.io/library/python"));
+        assert!(python.is_resolved);
+        assert!(python.is_runtime);
+
+        let uv = find("pkg:docker/astral-sh/uv@0.1.24?repository_url=ghcr.io");
+        assert_eq!(uv.scope, DOCKER_BUILD_SCOPE);
+        assert!(uv.is_runtime);
+
+        let poetry = find("pkg:pypi/poetry@1.7.1");
+        assert_eq!(poetry.scope, DOCKER_BUILD_SCOPE);
+        assert!(poetry.is_runtime);
+        find("pkg:pypi/gunicorn@21.2.0");
+        find("pkg:rpm/httpd");
+        assert!(package
+            .dependencies
+            .iter()
+            .any(|d| d.purl.starts_with("pkg:deb/libpq5@")));
+
+        // Stages are not images
+        assert!(!package
+            .dependencies
+            .iter()
+            .any(|d| d.purl.contains("builder")));
+    }
+
+    #[test]
+    fn variables() {
+        let vars = maplit::hashmap! {"TAG".to_string() => "1.0".to_string()};
+        assert_eq!(substitute("app:${TAG}", &vars), "app:1.0");
+        assert_eq!(substitute("app:$TAG", &vars), "app:1.0");
+        assert_eq!(substitute("app:${OTHER:-2.0}", &vars), "app:2.0");
+        assert_eq!(substitute("app:$OTHER", &vars), "app:$OTHER");
+
+        assert_eq!(
+            definitions("A=1 B=\"two words\""),
+            vec![
+                ("A".to_string(), Some("1".to_string())),
+                ("B".to_string(), Some("two words".to_string()))
+            ]
+        );
+    }
+}
@@ -4,32 +4,17 @@ use serde::Deserialize;
 use serde_xml_rs::Deserializer;
 
 use crate::error::SourcePkgError;
-use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest, RecognizeContext};
+use crate::types::Relation;
 
 use std::path::Path;
 
-const INVALID_CHAR: &str = "\u{feff}";
-
-#[derive(Debug, Deserialize, PartialEq, Eq)]
-pub struct PackageReference {
-    #[serde(alias = "Include", default)]
-    pub name: String,
-
-    #[serde(alias = "Version", default)]
-    pub version: String,
-}
+use super::msbuild;
+use super::packages_lock::NuGetPackagesLock;
+use super::project_assets::NuGetProjectAssets;
+use super::reachability;
 
-#[derive(Debug, Deserialize, PartialEq)]
-struct ItemGroup {
-    #[serde(alias = "PackageReference", default)]
-    pub dependencies: Vec<PackageReference>,
-}
-
-#[derive(Debug, Deserialize, PartialEq)]
-struct Project {
-    #[serde(rename = "ItemGroup", default)]
-    pub item_groups: Vec<ItemGroup>,
-}
+const INVALID_CHAR: &str = "\u{feff}";
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -45,7 +30,7 @@ struct PackagesConfig {
     package: Vec<PackagesConfigItem>,
 }
 
-fn parse(path: impl AsRef<Path>) -> Result<Package, SourcePkgError> {
+fn parse(path: impl AsRef<Path>, prefix: &Path) -> Result<Package, SourcePkgError> {
     let path = path.as_ref();
 
     let mut deps = vec![];
@@ -87,36 +72,46 @@ fn parse(path: impl AsRef<Path>) -> Result<Package, SourcePkgError> {
         }
     }
 
-    let content = std::fs::read_to_string(path)?
-        .trim_start_matches(INVALID_CHAR)
-        .to_string();
-
-    let mut de =
-        Deserializer::new_from_reader(content.as_bytes()).non_contiguous_seq_elements(true);
-    let parsed =
-        Project::deserialize(&mut de).map_err(|e| SourcePkgError::GenericsError2(e.to_string()))?;
-
-    for item_group in parsed.item_groups {
-        deps.extend(item_group.dependencies.into_iter().map(|dependency| {
-            // version come from the csharp.csproj file must met the semver principle
-            // otherwise it can be dynamic upon the runtime
-            let locked_version = match semver::Version::parse(&dependency.version) {
-                Ok(version) => version.to_string(),
-                Err(_) => "".into(),
-            };
-
-            DependentPackage {
-                purl: PackageUrl::new("nuget", dependency.name)
-                    .expect("purl arguments are invalid")
-                    .to_string(),
-                requirement: locked_version.clone(),
-                is_resolved: !locked_version.is_empty(),
-                ..Default::default()
-            }
-        }));
+    let evaluation = msbuild::evaluate(path, prefix)?;
+
+    for reference in &evaluation.package_references {
+        let requirement = reference.version.clone().unwrap_or_default();
+        // Ranges and floating versions can only be resolved at restore time
+        let exact_version = msbuild::exact_version(&requirement);
+        let scope = if reference.is_private {
+            "development"
+        } else {
+            ""
+        };
+
+        let mut purl =
+            PackageUrl::new("nuget", reference.name.as_str()).expect("purl arguments are invalid");
+        if let Some(v) = exact_version {
+            purl.with_version(v);
+        }
+
+        deps.push(DependentPackage {
+            purl: purl.to_string(),
+            requirement: exact_version.unwrap_or(&requirement).to_string(),
+            scope: scope.into(),
+            is_runtime: !reference.is_private,
+            is_resolved: exact_version.is_some(),
+            relation: maplit::hashset! {Relation::Direct},
+            ..Default::default()
+        });
     }
 
     let package = Package {
+        name: evaluation
+            .property("PackageId")
+            .or_else(|| evaluation.property("AssemblyName"))
+            .or_else(|| evaluation.property("MSBuildProjectName"))
+            .unwrap_or_default()
+            .to_string(),
+        version: evaluation
+            .property("Version")
+            .unwrap_or_default()
+            .to_string(),
         dependencies: deps,
         ..Default::default()
     };
@@ -124,11 +119,78 @@ fn parse(path: impl AsRef<Path>) -> Result<Package, SourcePkgError> {
     Ok(package)
 }
 
-pub struct CSharpCsproj {}
+pub struct CSharpCsproj {
+    lock_scanner: NuGetPackagesLock,
+    assets_scanner: NuGetProjectAssets,
+}
 
 impl CSharpCsproj {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            lock_scanner: NuGetPackagesLock::new(),
+            assets_scanner: NuGetProjectAssets::new(),
+        }
+    }
+
+    async fn recognize_internal(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let path_dir = path.parent().unwrap();
+
+        {
+            let lock_path = path_dir.join("packages.lock.json");
+            if tokio::fs::metadata(&lock_path).await.is_ok() {
+                log::info!("Found packages.lock.json file, using NuGet lock scanner");
+                match self.lock_scanner.recognize(&lock_path).await {
+                    Ok(x) => return Ok(x),
+                    Err(e) => {
+                        log::warn!("Failed to recognize packages.lock.json: {}", e);
+                    }
+                }
+            }
+        }
+
+        {
+            let assets_path = path_dir.join("obj").join("project.assets.json");
+            if tokio::fs::metadata(&assets_path).await.is_ok() {
+                log::info!("Found project.assets.json file, using NuGet assets scanner");
+                match self.assets_scanner.recognize(&assets_path).await {
+                    Ok(x) => return Ok(x),
+                    Err(e) => {
+                        log::warn!("Failed to recognize project.assets.json: {}", e);
+                    }
+                }
+            }
+        }
+
+        parse(path, &context.prefix)
+    }
+
+    /// Marks the `PackageReference`s that no C# source of the project uses as
+    /// unreachable.
+    async fn recognize_with_reachability(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let res = self.recognize_internal(path, context).await?;
+
+        let is_csharp = path.extension().is_some_and(|e| e == "csproj");
+        if !is_csharp {
+            return Ok(res);
+        }
+
+        let usings = match msbuild::evaluate(path, &context.prefix) {
+            Ok(evaluation) => evaluation.usings,
+            Err(e) => {
+                log::warn!("Failed to evaluate {}: {}", path.display(), e);
+                vec![]
+            }
+        };
+
+        Ok(reachability::mark_reachability(path.parent().unwrap(), usings, res).await)
     }
 }
 
@@ -139,17 +201,27 @@ impl PackageManifest for CSharpCsproj {
     }
 
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        parse(path)
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
+    }
+
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        self.recognize_with_reachability(path, context).await
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
-        &["*.csproj"]
+        &["*.csproj", "*.fsproj", "*.vbproj"]
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::Reachability;
 
     #[test]
     fn test_csproj() {
@@ -158,7 +230,7 @@ mod tests {
             "/testdata/csharp/ICSharpCode.Decompiler.csproj"
         ));
 
-        let p = parse(filepath).unwrap();
+        let p = parse(filepath, Path::new("")).unwrap();
         println!("{:?}", p);
     }
 
@@ -169,7 +241,92 @@ mod tests {
             "/testdata/csharp_with_package/Snyk.Common.csproj"
         ));
 
-        let p = parse(filepath).unwrap();
+        let p = parse(filepath, Path::new("")).unwrap();
         println!("{:?}", p);
     }
+
+    #[tokio::test]
+    async fn test_csproj_prefers_assets() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nuget/assets/App.csproj"
+        ));
+
+        let p = CSharpCsproj::new().recognize(filepath).await.unwrap();
+        assert_eq!(p.dependencies.len(), 2);
+        assert!(p.dependencies.iter().all(|d| d.is_resolved));
+    }
+
+    #[tokio::test]
+    async fn test_csproj_msbuild_evaluation() {
+        let prefix = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nuget/msbuild"
+        ));
+        let filepath = prefix.join("src/App/App.csproj");
+        let context = RecognizeContext {
+            prefix: prefix.to_path_buf(),
+            ..Default::default()
+        };
+
+        let p = CSharpCsproj::new()
+            .recognize_with_config(&filepath, &context)
+            .await
+            .unwrap();
+        assert_eq!(p.name, "Contoso.App");
+        assert_eq!(p.version, "1.4.0");
+
+        let find = |name: &str| {
+            p.dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(&format!("pkg:nuget/{}", name)))
+        };
+        assert_eq!(find("Newtonsoft.Json").unwrap().requirement, "13.0.3");
+        assert_eq!(find("Polly").unwrap().requirement, "8.0.0");
+        assert_eq!(find("Serilog").unwrap().purl, "pkg:nuget/Serilog@2.12.0");
+        assert!(!find("StyleCop.Analyzers").unwrap().is_runtime);
+        assert!(find("System.ValueTuple").is_some());
+        assert!(find("Ignored").is_none());
+
+        let dapper = find("Dapper").unwrap();
+        assert_eq!(dapper.requirement, "[2.0,3.0)");
+        assert!(!dapper.is_resolved);
+    }
+
+    #[tokio::test]
+    async fn test_csproj_reachability() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nuget/reachability/App.csproj"
+        ));
+
+        let p = CSharpCsproj::new().recognize(filepath).await.unwrap();
+        let reachable = |name: &str| {
+            p.dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(&format!("pkg:nuget/{}@", name)))
+                .unwrap()
+                .reachable
+        };
+
+        assert_eq!(reachable("Newtonsoft.Json"), Reachability::Possible);
+        assert_eq!(
+            reachable("Microsoft.Extensions.Logging.Abstractions"),
+            Reachability::Possible
+        );
+        assert_eq!(reachable("Dapper"), Reachability::Possible);
+        // Imported through a `Using` item
+        assert_eq!(reachable("Serilog"), Reachability::Possible);
+        // Without assemblies
+        assert_eq!(reachable("StyleCop.Analyzers"), Reachability::Possible);
+        // Only imported by generated sources in `obj`
+        assert_eq!(reachable("Polly"), Reachability::No);
+        // Not a direct dependency
+        assert_eq!(reachable("Polly.Core"), Reachability::Possible);
+
+        let evaluation = msbuild::evaluate(filepath, Path::new("")).unwrap();
+        assert!(evaluation.usings.contains(&"System.Linq".to_string()));
+        assert!(evaluation.usings.contains(&"Serilog".to_string()));
+        assert!(!evaluation.usings.contains(&"System.Net.Http".to_string()));
+    }
 }
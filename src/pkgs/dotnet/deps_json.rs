@@ -0,0 +1,199 @@
+//! Parser for the `<app>.deps.json` files shipped with published .NET applications.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::types::Relation;
+
+use super::make_purl;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DepsJson {
+    /// Runtime libraries, keyed by target (`.NETCoreApp,Version=v6.0/linux-x64`)
+    /// and then by `Name/Version`.
+    #[serde(default)]
+    targets: IndexMap<String, IndexMap<String, TargetLibrary>>,
+    #[serde(default)]
+    libraries: HashMap<String, Library>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TargetLibrary {
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Library {
+    #[serde(rename = "type")]
+    typ: String,
+    sha512: Option<String>,
+}
+
+/// Returns the package id for a library, or `None` for project references.
+fn package_name<'a>(name: &'a str, library: Option<&Library>) -> Option<&'a str> {
+    match library.map(|l| l.typ.as_str()) {
+        Some("package") => Some(name),
+        // Self-contained applications carry the runtime as a pack
+        Some("runtimepack") => Some(name.strip_prefix("runtimepack.").unwrap_or(name)),
+        _ => None,
+    }
+}
+
+pub fn parse_str(content: &str) -> Result<Package, SourcePkgError> {
+    let deps: DepsJson = serde_json::from_str(content)?;
+
+    let mut packages: IndexMap<String, DependentPackage> = IndexMap::new();
+
+    for (target, libraries) in &deps.targets {
+        let framework = target.split('/').next().unwrap_or(target);
+
+        let mut purls = HashMap::new();
+        let mut projects = HashSet::new();
+
+        for key in libraries.keys() {
+            let (name, version) = if let Some(x) = key.split_once('/') {
+                x
+            } else {
+                log::warn!("Invalid library key: {}", key);
+                continue;
+            };
+
+            let library = deps.libraries.get(key);
+            let package_name = if let Some(n) = package_name(name, library) {
+                n
+            } else {
+                projects.insert(name.to_lowercase());
+                continue;
+            };
+
+            let sha512 = library
+                .and_then(|l| l.sha512.as_deref())
+                .filter(|s| !s.is_empty());
+            let purl = make_purl(package_name, version, sha512);
+            purls.insert(name.to_lowercase(), purl.clone());
+
+            packages
+                .entry(purl.clone())
+                .or_insert_with(|| DependentPackage {
+                    purl,
+                    requirement: version.to_string(),
+                    scope: framework.to_string(),
+                    is_resolved: true,
+                    ..Default::default()
+                });
+        }
+
+        for (key, library) in libraries {
+            let name = key.split('/').next().unwrap_or(key).to_lowercase();
+            // Dependencies of the application and its project references are direct
+            let parent = if projects.contains(&name) {
+                None
+            } else if let Some(p) = purls.get(&name) {
+                Some(p)
+            } else {
+                continue;
+            };
+
+            for dep_name in library.dependencies.keys() {
+                let child = if let Some(c) = purls.get(&dep_name.to_lowercase()) {
+                    c
+                } else {
+                    if !projects.contains(&dep_name.to_lowercase()) {
+                        log::warn!("Could not find dependency {} in {}", dep_name, target);
+                    }
+                    continue;
+                };
+
+                if let Some(dep) = packages.get_mut(child) {
+                    if let Some(parent) = parent {
+                        dep.parents.insert(parent.clone());
+                        dep.relation.insert(Relation::Indirect);
+                    } else {
+                        dep.relation.insert(Relation::Direct);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Package {
+        dependencies: packages.into_values().collect(),
+        ..Default::default()
+    })
+}
+
+pub struct DotnetDepsJson {}
+
+impl DotnetDepsJson {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for DotnetDepsJson {
+    fn get_name(&self) -> String {
+        "nuget".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "nuget-deps-json".into()
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["*.deps.json"]
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        parse_str(&content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn deps_json() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nuget/publish/App.deps.json"
+        ));
+
+        let package = DotnetDepsJson::new().recognize(path).await.unwrap();
+        assert_eq!(package.dependencies.len(), 3);
+        assert!(package
+            .dependencies
+            .iter()
+            .all(|d| !d.purl.contains("/App@") && !d.purl.contains("/MyLib@")));
+
+        let find = |name: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(&format!("pkg:nuget/{}@", name)))
+                .unwrap()
+        };
+
+        let serilog = find("Serilog");
+        assert!(serilog.relation.contains(&Relation::Direct));
+        assert!(serilog.purl.contains("checksum=sha512:"));
+
+        // Referenced by the `MyLib` project
+        assert!(find("Polly").relation.contains(&Relation::Direct));
+
+        let sink = find("Serilog.Sinks.Console");
+        assert_eq!(sink.relation, maplit::hashset! {Relation::Direct});
+
+        assert!(serilog.relation.contains(&Relation::Indirect));
+        assert_eq!(serilog.parents.len(), 1);
+    }
+}
@@ -1,3 +1,37 @@
+use base64::Engine;
+use packageurl::PackageUrl;
+
 pub mod csproj;
+pub mod deps_json;
+pub mod msbuild;
 pub mod nuget_central;
 pub mod nuspec;
+pub mod packages_lock;
+pub mod paket;
+pub mod project_assets;
+pub mod reachability;
+
+fn make_purl(name: &str, version: &str, sha512: Option<&str>) -> String {
+    let mut purl = PackageUrl::new("nuget", name).unwrap();
+    purl.with_version(version);
+    if let Some(checksum) = sha512.and_then(sha512_to_checksum) {
+        purl.add_qualifier("checksum", checksum).ok();
+    }
+    purl.to_string()
+}
+
+/// Converts a base64 encoded SHA-512 digest, as found in NuGet lock and asset
+/// files, to a purl `checksum` qualifier value.
+fn sha512_to_checksum(hash: &str) -> Option<String> {
+    let hash = hash.strip_prefix("sha512-").unwrap_or(hash);
+    let digest = base64::engine::general_purpose::STANDARD
+        .decode(hash)
+        .map_err(|e| log::warn!("Invalid sha512 hash {}: {}", hash, e))
+        .ok()?;
+
+    let hex = digest
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    Some(format!("sha512:{}", hex))
+}
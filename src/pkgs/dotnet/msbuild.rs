@@ -0,0 +1,880 @@
+//! A lightweight MSBuild evaluator.
+//!
+//! Only the subset needed to find package references is supported: property
+//! definitions and `$(Property)` expansion, `Import` elements, the implicit
+//! `Directory.Build.props`/`Directory.Packages.props` imports, and simple
+//! `Condition` expressions.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use quick_xml::events::{BytesStart, Event};
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+
+const INVALID_CHAR: &str = "\u{feff}";
+
+/// Global usings added by `ImplicitUsings`, for every SDK.
+const IMPLICIT_USINGS: &[&str] = &[
+    "System",
+    "System.Collections.Generic",
+    "System.IO",
+    "System.Linq",
+    "System.Net.Http",
+    "System.Threading",
+    "System.Threading.Tasks",
+];
+
+/// Global usings added by `ImplicitUsings` on top of [`IMPLICIT_USINGS`], by SDK.
+const SDK_IMPLICIT_USINGS: &[(&str, &[&str])] = &[
+    (
+        "Microsoft.NET.Sdk.Web",
+        &[
+            "System.Net.Http.Json",
+            "Microsoft.AspNetCore.Builder",
+            "Microsoft.AspNetCore.Hosting",
+            "Microsoft.AspNetCore.Http",
+            "Microsoft.AspNetCore.Routing",
+            "Microsoft.Extensions.Configuration",
+            "Microsoft.Extensions.DependencyInjection",
+            "Microsoft.Extensions.Hosting",
+            "Microsoft.Extensions.Logging",
+        ],
+    ),
+    (
+        "Microsoft.NET.Sdk.Worker",
+        &[
+            "Microsoft.Extensions.Configuration",
+            "Microsoft.Extensions.DependencyInjection",
+            "Microsoft.Extensions.Hosting",
+            "Microsoft.Extensions.Logging",
+        ],
+    ),
+    (
+        "Microsoft.NET.Sdk.WindowsDesktop",
+        &["System.Drawing", "System.Windows.Forms"],
+    ),
+];
+
+/// Properties that only get a value at build time. Conditions comparing them
+/// can't be decided statically, so they are assumed to hold.
+const BUILD_TIME_PROPERTIES: &[&str] = &[
+    "configuration",
+    "platform",
+    "targetframework",
+    "runtimeidentifier",
+    "os",
+];
+
+lazy_static! {
+    static ref PROPERTY_REF: Regex = Regex::new(r"\$\(([A-Za-z_][A-Za-z0-9_.-]*)\)").unwrap();
+    static ref PATH_OF_FILE_ABOVE: Regex =
+        Regex::new(r"GetPathOfFileAbove\(\s*'?([^',)]+?)'?\s*(?:,\s*'?([^')]*?)'?\s*)?\)").unwrap();
+}
+
+/// A minimal XML element tree.
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn from_start(start: &BytesStart) -> Result<Self, SourcePkgError> {
+        let mut attributes = vec![];
+        for attr in start.attributes() {
+            let attr = attr.map_err(|e| SourcePkgError::GenericsError2(e.to_string()))?;
+            let key = String::from_utf8(attr.key.as_ref().to_vec())?;
+            attributes.push((key, attr.unescape_value()?.into_owned()));
+        }
+
+        Ok(Self {
+            name: String::from_utf8(start.name().as_ref().to_vec())?,
+            attributes,
+            ..Default::default()
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn child_text(&self, name: &str) -> Option<&str> {
+        self.children
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .map(|c| c.text.trim())
+    }
+
+    /// Returns the attribute, or the text of a child element with the same name.
+    fn metadata(&self, name: &str) -> Option<&str> {
+        self.attribute(name).or_else(|| self.child_text(name))
+    }
+
+    fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+fn parse_xml(content: &str) -> Result<Element, SourcePkgError> {
+    let mut reader = quick_xml::Reader::from_str(content);
+    let mut buffer = Vec::new();
+
+    let mut stack: Vec<Element> = vec![Element::default()];
+
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(e) => stack.push(Element::from_start(&e)?),
+            Event::Empty(e) => {
+                let element = Element::from_start(&e)?;
+                stack.last_mut().unwrap().children.push(element);
+            }
+            Event::Text(t) => stack.last_mut().unwrap().text.push_str(&t.unescape()?),
+            Event::CData(t) => stack
+                .last_mut()
+                .unwrap()
+                .text
+                .push_str(&String::from_utf8_lossy(&t)),
+            Event::End(_) => {
+                if stack.len() < 2 {
+                    return Err(SourcePkgError::GenericsError("Unbalanced MSBuild XML"));
+                }
+                let element = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(element);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        // Clear the buffer to keep memory usage low
+        buffer.clear();
+    }
+
+    stack
+        .pop()
+        .and_then(|root| root.children.into_iter().find(|c| c.is("Project")))
+        .ok_or(SourcePkgError::GenericsError("No Project element found"))
+}
+
+/// An item as it appears in a project file, evaluated once all properties are known.
+#[derive(Debug, Clone)]
+struct RawItem {
+    kind: String,
+    include: Option<String>,
+    update: Option<String>,
+    remove: Option<String>,
+    version: Option<String>,
+    version_override: Option<String>,
+    private_assets: Option<String>,
+    conditions: Vec<String>,
+    directory: PathBuf,
+}
+
+/// A `PackageReference` after evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageReference {
+    pub name: String,
+    /// The version requirement, after property expansion and central package
+    /// management have been applied.
+    pub version: Option<String>,
+    /// `PrivateAssets="all"`, typically analyzers and build tooling.
+    pub is_private: bool,
+    /// Added to every project through a `GlobalPackageReference`.
+    pub is_global: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Evaluation {
+    pub properties: HashMap<String, String>,
+    pub package_references: Vec<PackageReference>,
+    /// Namespaces imported in every source file, through `ImplicitUsings` and
+    /// `Using` items.
+    pub usings: Vec<String>,
+}
+
+impl Evaluation {
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .get(&name.to_ascii_lowercase())
+            .map(|s| s.as_str())
+    }
+}
+
+struct Evaluator {
+    prefix: PathBuf,
+    /// The `Sdk` attribute of the project.
+    sdk: Option<String>,
+    /// Property values, keyed by lower-cased name.
+    properties: HashMap<String, String>,
+    items: Vec<RawItem>,
+    visited: HashSet<PathBuf>,
+}
+
+/// Tri-state condition result, `None` when it depends on build-time state.
+type Tristate = Option<bool>;
+
+impl Evaluator {
+    fn new(prefix: PathBuf) -> Self {
+        Self {
+            prefix,
+            sdk: None,
+            properties: HashMap::new(),
+            items: vec![],
+            visited: HashSet::new(),
+        }
+    }
+
+    fn set_property(&mut self, name: &str, value: String) {
+        self.properties.insert(name.to_ascii_lowercase(), value);
+    }
+
+    fn in_prefix(&self, path: &Path) -> bool {
+        path.starts_with(&self.prefix)
+    }
+
+    /// Expands `$(Property)` references. The flag is set when a build-time
+    /// property without a static value was referenced.
+    fn expand(&self, value: &str) -> (String, bool) {
+        let mut unknown = false;
+        let expanded = PROPERTY_REF.replace_all(value, |caps: &regex::Captures| {
+            let name = caps[1].to_ascii_lowercase();
+            match self.properties.get(&name) {
+                Some(v) => v.clone(),
+                None => {
+                    if BUILD_TIME_PROPERTIES.contains(&name.as_str()) {
+                        unknown = true;
+                    }
+                    String::new()
+                }
+            }
+        });
+        if expanded.contains("$([") {
+            // Property functions are not supported
+            unknown = true;
+        }
+        (expanded.into_owned(), unknown)
+    }
+
+    fn condition_holds(&self, condition: Option<&str>, directory: &Path) -> bool {
+        match condition {
+            Some(c) if !c.trim().is_empty() => {
+                let tokens = tokenize(c);
+                let mut parser = ConditionParser {
+                    evaluator: self,
+                    directory,
+                    tokens: &tokens,
+                    pos: 0,
+                };
+                parser.or().unwrap_or(true)
+            }
+            _ => true,
+        }
+    }
+
+    fn all_conditions_hold(&self, conditions: &[String], directory: &Path) -> bool {
+        conditions
+            .iter()
+            .all(|c| self.condition_holds(Some(c), directory))
+    }
+
+    /// Evaluates a project or props file, following its imports.
+    fn evaluate_file(&mut self, path: &Path) -> Result<(), SourcePkgError> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !self.visited.insert(path.clone()) {
+            log::debug!("{} was already imported", path.display());
+            return Ok(());
+        }
+
+        let content = std::fs::read(&path)?;
+        let content = crate::pkgs::common::decode_string(&content)?;
+        let project = parse_xml(content.trim_start_matches(INVALID_CHAR))?;
+        if self.sdk.is_none() {
+            self.sdk = project.attribute("Sdk").map(str::to_string);
+        }
+
+        let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let mut this_dir = directory.to_string_lossy().into_owned();
+        this_dir.push(std::path::MAIN_SEPARATOR);
+        self.set_property("MSBuildThisFileDirectory", this_dir);
+
+        self.evaluate_children(&project, &directory, &[])
+    }
+
+    fn evaluate_children(
+        &mut self,
+        parent: &Element,
+        directory: &Path,
+        conditions: &[String],
+    ) -> Result<(), SourcePkgError> {
+        for element in &parent.children {
+            let mut conditions = conditions.to_vec();
+            if let Some(c) = element.attribute("Condition") {
+                conditions.push(c.to_string());
+            }
+
+            if element.is("PropertyGroup") {
+                if !self.all_conditions_hold(&conditions, directory) {
+                    continue;
+                }
+                for property in &element.children {
+                    if !self.condition_holds(property.attribute("Condition"), directory) {
+                        continue;
+                    }
+                    let (value, _) = self.expand(property.text.trim());
+                    self.set_property(&property.name, value);
+                }
+            } else if element.is("ItemGroup") {
+                for item in &element.children {
+                    let mut conditions = conditions.clone();
+                    if let Some(c) = item.attribute("Condition") {
+                        conditions.push(c.to_string());
+                    }
+                    self.items.push(RawItem {
+                        kind: item.name.clone(),
+                        include: item.attribute("Include").map(str::to_string),
+                        update: item.attribute("Update").map(str::to_string),
+                        remove: item.attribute("Remove").map(str::to_string),
+                        version: item.metadata("Version").map(str::to_string),
+                        version_override: item.metadata("VersionOverride").map(str::to_string),
+                        private_assets: item.metadata("PrivateAssets").map(str::to_string),
+                        conditions,
+                        directory: directory.to_path_buf(),
+                    });
+                }
+            } else if element.is("ImportGroup") {
+                self.evaluate_children(element, directory, &conditions)?;
+            } else if element.is("Import") {
+                if !self.all_conditions_hold(&conditions, directory) {
+                    continue;
+                }
+                if let Some(project) = element.attribute("Project") {
+                    self.import(project, directory)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn import(&mut self, project: &str, directory: &Path) -> Result<(), SourcePkgError> {
+        let path = if let Some(caps) = PATH_OF_FILE_ABOVE.captures(project) {
+            // `$([MSBuild]::GetPathOfFileAbove('Directory.Build.props', '$(MSBuildThisFileDirectory)../'))`
+            let (start, _) = caps
+                .get(2)
+                .map(|s| self.expand(s.as_str()))
+                .unwrap_or_else(|| (directory.to_string_lossy().into_owned(), false));
+            let start = directory.join(start);
+            let start = start.canonicalize().unwrap_or(start);
+            match self.find_file_above(&start, &caps[1]) {
+                Some(p) => p,
+                None => return Ok(()),
+            }
+        } else {
+            let (expanded, unknown) = self.expand(project);
+            if unknown || expanded.contains('*') {
+                log::debug!("Skipping import of {}", project);
+                return Ok(());
+            }
+            directory.join(expanded.replace('\\', "/"))
+        };
+
+        let path = path.canonicalize().unwrap_or(path);
+        if !self.in_prefix(&path) {
+            log::warn!("Refusing to import {} outside of prefix", path.display());
+            return Ok(());
+        }
+        if !path.is_file() {
+            // Most likely an SDK import
+            log::debug!("Import {} does not exist", path.display());
+            return Ok(());
+        }
+
+        let this_dir = self.properties.get("msbuildthisfiledirectory").cloned();
+        let res = self.evaluate_file(&path);
+        if let Some(d) = this_dir {
+            self.set_property("MSBuildThisFileDirectory", d);
+        }
+        res
+    }
+
+    /// Finds `name` in `start` or its ancestors, without leaving the prefix.
+    fn find_file_above(&self, start: &Path, name: &str) -> Option<PathBuf> {
+        let mut dir = Some(start);
+        while let Some(d) = dir {
+            if !self.in_prefix(d) {
+                break;
+            }
+            let candidate = d.join(name);
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+            dir = d.parent();
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Str(String),
+    Word(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(s: &str) -> Vec<Token> {
+    let chars = s.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '\'')
+                    .map(|p| i + 1 + p)
+                    .unwrap_or(chars.len());
+                tokens.push(Token::Str(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '=' | '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let (op, len) = match (c, next) {
+                    ('=', Some('=')) => ("==", 2),
+                    ('!', Some('=')) => ("!=", 2),
+                    ('<', Some('=')) => ("<=", 2),
+                    ('>', Some('=')) => (">=", 2),
+                    ('!', _) => ("!", 1),
+                    ('<', _) => ("<", 1),
+                    ('>', _) => (">", 1),
+                    // A lone `=`, taken as `==`
+                    _ => ("==", 1),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            _ => {
+                let start = i;
+                let mut depth = 0;
+                while i < chars.len() {
+                    let c = chars[i];
+                    // Keep `$(Prop)` together
+                    if c == '(' && i > start && chars[i - 1] == '$' {
+                        depth += 1;
+                    } else if c == ')' && depth > 0 {
+                        depth -= 1;
+                    } else if depth == 0 && (c.is_whitespace() || "'(),=!<>".contains(c)) {
+                        break;
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::Word(chars[start..i].iter().collect()));
+            }
+        }
+    }
+
+    tokens
+}
+
+struct ConditionParser<'a> {
+    evaluator: &'a Evaluator,
+    directory: &'a Path,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> ConditionParser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let t = self.tokens.get(self.pos);
+        self.pos += 1;
+        t
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w.eq_ignore_ascii_case(word))
+    }
+
+    fn or(&mut self) -> Tristate {
+        let mut value = self.and();
+        while self.peek_word("or") {
+            self.pos += 1;
+            let rhs = self.and();
+            value = match (value, rhs) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            };
+        }
+        value
+    }
+
+    fn and(&mut self) -> Tristate {
+        let mut value = self.unary();
+        while self.peek_word("and") {
+            self.pos += 1;
+            let rhs = self.unary();
+            value = match (value, rhs) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            };
+        }
+        value
+    }
+
+    fn unary(&mut self) -> Tristate {
+        match self.peek() {
+            Some(Token::Op("!")) => {
+                self.pos += 1;
+                self.unary().map(|v| !v)
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let value = self.or();
+                if self.peek() == Some(&Token::RParen) {
+                    self.pos += 1;
+                }
+                value
+            }
+            _ => self.comparison(),
+        }
+    }
+
+    fn function_call(&mut self, name: &str) -> Tristate {
+        // Consume `(`
+        self.pos += 1;
+        let mut args = vec![];
+        while let Some(t) = self.next() {
+            match t {
+                Token::RParen => break,
+                Token::Str(s) | Token::Word(s) => args.push(self.evaluator.expand(s)),
+                _ => {}
+            }
+        }
+
+        match name.to_ascii_lowercase().as_str() {
+            "exists" => {
+                let (path, unknown) = args.first().cloned().unwrap_or_default();
+                if unknown {
+                    return None;
+                }
+                let path = path.trim();
+                Some(!path.is_empty() && self.directory.join(path.replace('\\', "/")).exists())
+            }
+            "hastrailingslash" => {
+                let (path, unknown) = args.first().cloned().unwrap_or_default();
+                if unknown {
+                    return None;
+                }
+                Some(path.ends_with('/') || path.ends_with('\\'))
+            }
+            _ => None,
+        }
+    }
+
+    fn operand(&mut self) -> Option<(String, bool)> {
+        match self.next()?.clone() {
+            Token::Str(s) | Token::Word(s) => Some(self.evaluator.expand(&s)),
+            _ => None,
+        }
+    }
+
+    fn comparison(&mut self) -> Tristate {
+        if let Some(Token::Word(w)) = self.peek() {
+            let w = w.clone();
+            if self.tokens.get(self.pos + 1) == Some(&Token::LParen) {
+                self.pos += 1;
+                return self.function_call(&w);
+            }
+        }
+
+        let (lhs, lhs_unknown) = self.operand()?;
+        let op = match self.peek() {
+            Some(Token::Op(op)) if *op != "!" => *op,
+            _ => {
+                // A bare operand, `true` or `false`
+                if lhs_unknown {
+                    return None;
+                }
+                return match lhs.trim().to_ascii_lowercase().as_str() {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    _ => None,
+                };
+            }
+        };
+        self.pos += 1;
+        let (rhs, rhs_unknown) = self.operand()?;
+
+        if lhs_unknown || rhs_unknown {
+            return None;
+        }
+
+        match op {
+            "==" => Some(lhs.eq_ignore_ascii_case(&rhs)),
+            "!=" => Some(!lhs.eq_ignore_ascii_case(&rhs)),
+            _ => {
+                let (l, r) = (lhs.parse::<f64>().ok()?, rhs.parse::<f64>().ok()?);
+                Some(match op {
+                    "<" => l < r,
+                    "<=" => l <= r,
+                    ">" => l > r,
+                    ">=" => l >= r,
+                    _ => return None,
+                })
+            }
+        }
+    }
+}
+
+/// Evaluates a project file.
+///
+/// `Directory.Build.props` and `Directory.Packages.props` are looked up in the
+/// project directory and its ancestors, without leaving `prefix`. An empty
+/// prefix restricts the lookup to the project directory.
+pub fn evaluate(path: &Path, prefix: &Path) -> Result<Evaluation, SourcePkgError> {
+    let path = path.canonicalize()?;
+    let directory = path.parent().unwrap_or(Path::new("")).to_path_buf();
+
+    let prefix = if prefix.as_os_str().is_empty() {
+        directory.clone()
+    } else {
+        prefix
+            .canonicalize()
+            .unwrap_or_else(|_| prefix.to_path_buf())
+    };
+
+    let mut evaluator = Evaluator::new(prefix);
+
+    evaluator.set_property(
+        "MSBuildProjectDirectory",
+        directory.to_string_lossy().into_owned(),
+    );
+    if let Some(stem) = path.file_stem() {
+        evaluator.set_property("MSBuildProjectName", stem.to_string_lossy().into_owned());
+    }
+    if let Some(name) = path.file_name() {
+        evaluator.set_property("MSBuildProjectFile", name.to_string_lossy().into_owned());
+    }
+
+    // Implicit imports of the .NET SDK, in the order of `Microsoft.Common.props`.
+    for props in ["Directory.Build.props", "Directory.Packages.props"] {
+        if let Some(p) = evaluator.find_file_above(&directory, props) {
+            let res = evaluator.evaluate_file(&p);
+            if let Err(e) = res {
+                log::warn!("Failed to evaluate {}: {}", p.display(), e);
+            }
+        }
+    }
+
+    evaluator.evaluate_file(&path)?;
+
+    Ok(evaluator.finish())
+}
+
+impl Evaluator {
+    fn finish(self) -> Evaluation {
+        let central = self
+            .properties
+            .get("managepackageversionscentrally")
+            .map(|v| v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+
+        // Keyed by lower-cased package id.
+        let mut references: IndexMap<String, PackageReference> = IndexMap::new();
+        let mut versions: HashMap<String, String> = HashMap::new();
+
+        let implicit_usings = self
+            .properties
+            .get("implicitusings")
+            .map(|v| v.eq_ignore_ascii_case("enable") || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false);
+        let mut usings: IndexMap<String, String> = IndexMap::new();
+        if implicit_usings {
+            let sdk = self.sdk.as_deref().unwrap_or_default();
+            let sdk_usings = SDK_IMPLICIT_USINGS
+                .iter()
+                .filter(|(name, _)| name.eq_ignore_ascii_case(sdk))
+                .flat_map(|(_, u)| u.iter());
+            for using in IMPLICIT_USINGS.iter().chain(sdk_usings) {
+                usings.insert(using.to_lowercase(), using.to_string());
+            }
+        }
+
+        for item in &self.items {
+            if !self.all_conditions_hold(&item.conditions, &item.directory) {
+                continue;
+            }
+
+            let expand = |v: &Option<String>| {
+                v.as_deref()
+                    .map(|v| self.expand(v).0.trim().to_string())
+                    .filter(|v| !v.is_empty())
+            };
+            let version = expand(&item.version);
+            let is_private = expand(&item.private_assets)
+                .map(|v| v.split(';').any(|a| a.trim().eq_ignore_ascii_case("all")))
+                .unwrap_or(false);
+
+            let kind = item.kind.to_ascii_lowercase();
+            match kind.as_str() {
+                "packagereference" | "globalpackagereference" => {
+                    let is_global = kind == "globalpackagereference";
+
+                    if let Some(update) = expand(&item.update) {
+                        if let Some(r) = references.get_mut(&update.to_lowercase()) {
+                            if version.is_some() {
+                                r.version = version;
+                            }
+                            r.is_private |= is_private;
+                        }
+                        continue;
+                    }
+
+                    let name = if let Some(n) = expand(&item.include) {
+                        n
+                    } else {
+                        continue;
+                    };
+
+                    // Central package management does not allow `Version`,
+                    // only `VersionOverride`.
+                    let version = expand(&item.version_override).or(version);
+
+                    for name in name.split(';').map(str::trim).filter(|n| !n.is_empty()) {
+                        references.insert(
+                            name.to_lowercase(),
+                            PackageReference {
+                                name: name.to_string(),
+                                version: version.clone(),
+                                is_private: is_private || is_global,
+                                is_global,
+                            },
+                        );
+                    }
+                }
+                "using" => {
+                    if let Some(remove) = expand(&item.remove) {
+                        usings.shift_remove(&remove.to_lowercase());
+                    } else if let Some(include) = expand(&item.include) {
+                        usings.insert(include.to_lowercase(), include);
+                    }
+                }
+                "packageversion" => {
+                    let name = expand(&item.include).or_else(|| expand(&item.update));
+                    if let (Some(name), Some(version)) = (name, version) {
+                        versions.insert(name.to_lowercase(), version);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut package_references = references.into_values().collect::<Vec<_>>();
+        if central {
+            for r in package_references.iter_mut() {
+                if r.version.is_none() {
+                    r.version = versions.get(&r.name.to_lowercase()).cloned();
+                }
+            }
+        }
+
+        Evaluation {
+            properties: self.properties,
+            package_references,
+            usings: usings.into_values().collect(),
+        }
+    }
+}
+
+lazy_static! {
+    static ref NUGET_VERSION: Regex =
+        Regex::new(r"^\d+(\.\d+){0,3}(-[0-9A-Za-z.-]+)?(\+[0-9A-Za-z.-]+)?$").unwrap();
+}
+
+/// Returns the exact version for a NuGet version requirement, if there is one.
+///
+/// A bare version (`1.2.3`) is a minimum, but NuGet resolves it to the lowest
+/// applicable version, i.e. itself. `[1.2.3]` pins the version. Ranges like
+/// `[1.2,2.0)` and floating versions like `1.*` can't be resolved statically.
+pub fn exact_version(requirement: &str) -> Option<&str> {
+    let requirement = requirement.trim();
+    let v = requirement
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .unwrap_or(requirement)
+        .trim();
+
+    if NUGET_VERSION.is_match(v) {
+        Some(v)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nuget_versions() {
+        assert_eq!(exact_version("1.2.3"), Some("1.2.3"));
+        assert_eq!(exact_version("[1.2.3.4]"), Some("1.2.3.4"));
+        assert_eq!(exact_version("2.0.0-preview.1"), Some("2.0.0-preview.1"));
+        assert_eq!(exact_version("[1.2,2.0)"), None);
+        assert_eq!(exact_version("1.*"), None);
+        assert_eq!(exact_version("$(Missing)"), None);
+    }
+
+    #[test]
+    fn conditions() {
+        let mut evaluator = Evaluator::new(PathBuf::new());
+        evaluator.set_property("Foo", "bar".into());
+        let dir = Path::new(".");
+
+        assert!(evaluator.condition_holds(Some("'$(Foo)' == 'bar'"), dir));
+        assert!(evaluator.condition_holds(Some("'$(Foo)' == 'BAR'"), dir));
+        assert!(!evaluator.condition_holds(Some("'$(Foo)' != 'bar'"), dir));
+        assert!(evaluator.condition_holds(Some("'$(Foo)'='bar'"), dir));
+        assert!(!evaluator.condition_holds(Some("'$(Foo)'='baz'"), dir));
+        assert!(!evaluator.condition_holds(Some("'$(Missing)' != ''"), dir));
+        assert!(evaluator.condition_holds(Some("'$(Missing)' == '' and $(Foo) == bar"), dir));
+        assert!(!evaluator.condition_holds(Some("!('$(Foo)' == 'bar')"), dir));
+        assert!(!evaluator.condition_holds(Some("Exists('does/not/exist')"), dir));
+        // Build-time properties can't be decided
+        assert!(evaluator.condition_holds(Some("'$(Configuration)' == 'Debug'"), dir));
+        assert!(evaluator.condition_holds(Some("'$(TargetFramework)' == 'net48'"), dir));
+        assert!(!evaluator.condition_holds(
+            Some("'$(TargetFramework)' == 'net48' and '$(Foo)' == 'baz'"),
+            dir
+        ));
+    }
+}
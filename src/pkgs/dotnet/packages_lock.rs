@@ -0,0 +1,180 @@
+//! Parser for NuGet `packages.lock.json` files.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::types::Relation;
+
+use super::make_purl;
+
+#[derive(Debug, Deserialize)]
+struct PackagesLock {
+    /// Locked packages, keyed by target framework and then by package id.
+    #[serde(default)]
+    dependencies: IndexMap<String, IndexMap<String, LockedPackage>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockedPackage {
+    #[serde(rename = "type")]
+    typ: LockedPackageType,
+    resolved: Option<String>,
+    content_hash: Option<String>,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+enum LockedPackageType {
+    Direct,
+    Transitive,
+    CentralTransitive,
+    Project,
+    #[serde(other)]
+    Unknown,
+}
+
+pub fn parse_str(content: &str) -> Result<Package, SourcePkgError> {
+    let lock: PackagesLock = serde_json::from_str(content)?;
+
+    let mut packages: IndexMap<String, DependentPackage> = IndexMap::new();
+
+    for (framework, entries) in &lock.dependencies {
+        // Package ids are case-insensitive, `dependencies` may not use the
+        // same casing as the entry itself.
+        let mut purls = HashMap::new();
+
+        for (name, entry) in entries {
+            if entry.typ == LockedPackageType::Project {
+                // Project references are not packages
+                continue;
+            }
+
+            let version = if let Some(v) = &entry.resolved {
+                v
+            } else {
+                log::warn!("Package {} has no resolved version", name);
+                continue;
+            };
+
+            let purl = make_purl(name, version, entry.content_hash.as_deref());
+            purls.insert(name.to_lowercase(), purl.clone());
+
+            let dep = packages
+                .entry(purl.clone())
+                .or_insert_with(|| DependentPackage {
+                    purl,
+                    requirement: version.clone(),
+                    scope: framework.clone(),
+                    is_resolved: true,
+                    ..Default::default()
+                });
+
+            match entry.typ {
+                LockedPackageType::Direct => {
+                    dep.relation.insert(Relation::Direct);
+                }
+                LockedPackageType::Transitive | LockedPackageType::CentralTransitive => {
+                    dep.relation.insert(Relation::Indirect);
+                }
+                _ => {}
+            }
+        }
+
+        for (name, entry) in entries {
+            let parent = if let Some(p) = purls.get(&name.to_lowercase()) {
+                p
+            } else {
+                continue;
+            };
+
+            for dep_name in entry.dependencies.keys() {
+                let child = if let Some(c) = purls.get(&dep_name.to_lowercase()) {
+                    c
+                } else {
+                    log::warn!("Could not find dependency {} in lockfile", dep_name);
+                    continue;
+                };
+
+                if let Some(dep) = packages.get_mut(child) {
+                    dep.parents.insert(parent.clone());
+                    dep.relation.insert(Relation::Indirect);
+                }
+            }
+        }
+    }
+
+    Ok(Package {
+        dependencies: packages.into_values().collect(),
+        ..Default::default()
+    })
+}
+
+pub struct NuGetPackagesLock {}
+
+impl NuGetPackagesLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for NuGetPackagesLock {
+    fn get_name(&self) -> String {
+        "nuget".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "nuget-lock".into()
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["packages.lock.json"]
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        parse_str(&content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn packages_lock() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nuget/lock/packages.lock.json"
+        ));
+
+        let package = NuGetPackagesLock::new().recognize(path).await.unwrap();
+        assert_eq!(package.dependencies.len(), 3);
+
+        let serilog = package
+            .dependencies
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:nuget/Serilog@2.12.0"))
+            .unwrap();
+        assert!(serilog.relation.contains(&Relation::Direct));
+        assert!(serilog.purl.contains("checksum=sha512:"));
+
+        let abstractions = package
+            .dependencies
+            .iter()
+            .find(|d| {
+                d.purl
+                    .starts_with("pkg:nuget/Microsoft.Extensions.Logging.Abstractions")
+            })
+            .unwrap();
+        assert_eq!(abstractions.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(abstractions.parents.len(), 1);
+    }
+}
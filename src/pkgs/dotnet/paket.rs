@@ -0,0 +1,446 @@
+//! Parsers for Paket's `paket.dependencies` and `paket.lock` files.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use packageurl::PackageUrl;
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::types::Relation;
+
+const MAIN_GROUP: &str = "Main";
+
+lazy_static! {
+    /// `Name (version) - restriction: ...`, the name is absent for `GIT` entries.
+    static ref LOCK_ENTRY: Regex =
+        Regex::new(r"^(?:(\S+)\s*)?\(([^)]*)\)(?:\s+-\s+(.*))?$").unwrap();
+}
+
+/// Groups commonly used for tooling rather than shipped code.
+fn is_runtime_group(group: &str) -> bool {
+    !matches!(
+        group.to_ascii_lowercase().as_str(),
+        "build" | "test" | "tests" | "docs" | "analyzers"
+    )
+}
+
+/// NuGet packages declared in `paket.dependencies`, per group.
+#[derive(Debug, Default)]
+struct PaketDependencies {
+    groups: IndexMap<String, Vec<(String, String)>>,
+}
+
+impl PaketDependencies {
+    fn parse(content: &str) -> Self {
+        let mut ret = Self::default();
+        let mut group = MAIN_GROUP.to_string();
+
+        for line in content.lines() {
+            let line = line.split("//").next().unwrap_or_default().trim();
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("group") => {
+                    group = words.next().unwrap_or(MAIN_GROUP).to_string();
+                }
+                Some("nuget") => {
+                    let name = if let Some(n) = words.next() {
+                        n
+                    } else {
+                        continue;
+                    };
+                    // Options like `framework: net6.0` end the version constraint
+                    let constraint = words
+                        .take_while(|w| !w.ends_with(':'))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    ret.groups
+                        .entry(group.clone())
+                        .or_default()
+                        .push((name.to_string(), constraint));
+                }
+                _ => {}
+            }
+        }
+
+        ret
+    }
+
+    fn contains(&self, group: &str, name: &str) -> bool {
+        self.groups
+            .iter()
+            .filter(|(g, _)| g.eq_ignore_ascii_case(group))
+            .flat_map(|(_, deps)| deps)
+            .any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    NuGet,
+    GitHub,
+    Git,
+    Http,
+}
+
+fn is_default_nuget_feed(remote: &str) -> bool {
+    remote.contains("api.nuget.org") || remote.contains("www.nuget.org")
+}
+
+/// The purl of a `paket.lock` entry, with its framework `restriction` if any.
+fn make_lock_purl(
+    source: Source,
+    remote: &str,
+    name: &str,
+    version: &str,
+    restriction: Option<&str>,
+) -> Option<String> {
+    let mut purl = match source {
+        Source::NuGet => {
+            let mut purl = PackageUrl::new("nuget", name).ok()?;
+            purl.with_version(version);
+            if !remote.is_empty() && !is_default_nuget_feed(remote) {
+                purl.add_qualifier("repository_url", remote).ok()?;
+            }
+            purl
+        }
+        Source::GitHub => {
+            // `remote: owner/repo`, the entry is a file at a commit
+            let (owner, repo) = remote.split_once('/')?;
+            let repo = repo.split(':').next().unwrap_or(repo);
+            let mut purl = PackageUrl::new("github", repo).ok()?;
+            purl.with_namespace(owner).with_version(version);
+            if !name.is_empty() {
+                purl.with_subpath(name).ok()?;
+            }
+            purl
+        }
+        Source::Git => {
+            let repo = remote
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or(remote)
+                .trim_end_matches(".git");
+            let mut purl = PackageUrl::new("generic", repo).ok()?;
+            purl.with_version(version);
+            purl.add_qualifier("vcs_url", format!("git+{}", remote))
+                .ok()?;
+            purl
+        }
+        Source::Http => {
+            // `file (/url/path)`
+            let file = name.rsplit('/').next().unwrap_or(name);
+            let mut purl = PackageUrl::new("generic", file).ok()?;
+            purl.add_qualifier(
+                "download_url",
+                format!("{}{}", remote.trim_end_matches('/'), version),
+            )
+            .ok()?;
+            purl
+        }
+    };
+
+    if let Some(restriction) = restriction {
+        purl.add_qualifier("restriction", restriction).ok()?;
+    }
+
+    Some(purl.to_string())
+}
+
+/// A resolved entry of `paket.lock`, with the names of its dependencies.
+struct LockEntry {
+    group: String,
+    name: String,
+    dependency: DependentPackage,
+    dependencies: Vec<String>,
+}
+
+fn parse_lock(content: &str, manifest: &PaketDependencies) -> Vec<DependentPackage> {
+    let mut entries: Vec<LockEntry> = vec![];
+
+    let mut group = MAIN_GROUP.to_string();
+    let mut source = None;
+    let mut remote = String::new();
+
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        let line = line.trim();
+
+        match indent {
+            0 => {
+                if let Some(g) = line.strip_prefix("GROUP ") {
+                    group = g.trim().to_string();
+                    source = None;
+                } else {
+                    // Group level options like `RESTRICTION:` don't change the source
+                    source = match line {
+                        "NUGET" => Some(Source::NuGet),
+                        "GITHUB" => Some(Source::GitHub),
+                        "GIT" => Some(Source::Git),
+                        "HTTP" => Some(Source::Http),
+                        _ if line.contains(':') => source,
+                        _ => None,
+                    };
+                }
+            }
+            2 => {
+                if let Some(r) = line.strip_prefix("remote:") {
+                    remote = r.trim().to_string();
+                }
+            }
+            4 => {
+                let source = if let Some(s) = source {
+                    s
+                } else {
+                    continue;
+                };
+                let caps = if let Some(c) = LOCK_ENTRY.captures(line) {
+                    c
+                } else {
+                    log::warn!("Unrecognized paket.lock entry: {}", line);
+                    continue;
+                };
+
+                let name = caps.get(1).map(|m| m.as_str()).unwrap_or_default();
+                let version = &caps[2];
+                // Like `restriction: || (== net461) (== net6.0)`
+                let restriction = caps
+                    .get(3)
+                    .and_then(|m| m.as_str().strip_prefix("restriction:"))
+                    .map(str::trim);
+
+                let purl =
+                    if let Some(p) = make_lock_purl(source, &remote, name, version, restriction) {
+                        p
+                    } else {
+                        log::warn!("Invalid paket.lock entry: {}", line);
+                        continue;
+                    };
+
+                entries.push(LockEntry {
+                    group: group.clone(),
+                    name: name.to_string(),
+                    dependency: DependentPackage {
+                        purl,
+                        requirement: version.to_string(),
+                        scope: group.clone(),
+                        is_runtime: is_runtime_group(&group),
+                        is_resolved: true,
+                        ..Default::default()
+                    },
+                    dependencies: vec![],
+                });
+            }
+            _ => {
+                // Transitive dependency of the previous entry, `Name (>= 1.0)`.
+                let name = line.split_whitespace().next().unwrap_or_default();
+                if let Some(entry) = entries.last_mut() {
+                    entry.dependencies.push(name.to_string());
+                }
+            }
+        }
+    }
+
+    // Resolve parents within each group
+    let index: HashMap<(String, String), usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| ((e.group.to_lowercase(), e.name.to_lowercase()), i))
+        .collect();
+
+    let mut parents: Vec<HashSet<String>> = vec![HashSet::new(); entries.len()];
+    for entry in &entries {
+        for dep in &entry.dependencies {
+            if let Some(&i) = index.get(&(entry.group.to_lowercase(), dep.to_lowercase())) {
+                parents[i].insert(entry.dependency.purl.clone());
+            }
+        }
+    }
+
+    entries
+        .into_iter()
+        .zip(parents)
+        .map(|(entry, parents)| {
+            let mut dep = entry.dependency;
+            if manifest.contains(&entry.group, &entry.name) || parents.is_empty() {
+                dep.relation.insert(Relation::Direct);
+            }
+            if !parents.is_empty() {
+                dep.relation.insert(Relation::Indirect);
+            }
+            dep.parents = parents;
+            dep
+        })
+        .collect()
+}
+
+pub struct Paket {}
+
+impl Paket {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    fn parse(path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let manifest = PaketDependencies::parse(&content);
+
+        let lock_path = path.with_file_name("paket.lock");
+        if lock_path.exists() {
+            log::info!("Found paket.lock file, using it to provide resolved versions");
+            match std::fs::read_to_string(&lock_path) {
+                Ok(lock) => {
+                    return Ok(Package {
+                        dependencies: parse_lock(&lock, &manifest),
+                        ..Default::default()
+                    })
+                }
+                Err(e) => {
+                    log::warn!("Failed to read paket.lock: {}", e);
+                }
+            }
+        }
+
+        let mut dependencies = vec![];
+        for (group, deps) in manifest.groups {
+            for (name, constraint) in deps {
+                dependencies.push(DependentPackage {
+                    purl: PackageUrl::new("nuget", name)
+                        .expect("purl arguments are invalid")
+                        .to_string(),
+                    requirement: constraint,
+                    scope: group.clone(),
+                    is_runtime: is_runtime_group(&group),
+                    is_resolved: false,
+                    relation: maplit::hashset! {Relation::Direct},
+                    ..Default::default()
+                });
+            }
+        }
+
+        Ok(Package {
+            dependencies,
+            ..Default::default()
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Paket {
+    fn get_name(&self) -> String {
+        "nuget".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "paket".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        if path.ends_with("paket.lock") {
+            if path.with_file_name("paket.dependencies").exists() {
+                // Already covered by the manifest
+                return Err(SourcePkgError::Skipped);
+            }
+            let lock = std::fs::read_to_string(path)?;
+            return Ok(Package {
+                dependencies: parse_lock(&lock, &PaketDependencies::default()),
+                ..Default::default()
+            });
+        }
+
+        Self::parse(path)
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["paket.dependencies", "paket.lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paket_lock() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/paket/paket.dependencies"
+        ));
+
+        let package = Paket::parse(path).unwrap();
+        let find = |prefix: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(prefix))
+                .unwrap()
+        };
+
+        let console = find("pkg:nuget/Serilog.Sinks.Console@4.1");
+        assert_eq!(console.relation, maplit::hashset! {Relation::Direct});
+
+        let serilog = find("pkg:nuget/Serilog@2.12");
+        assert!(serilog.relation.contains(&Relation::Direct));
+        assert!(serilog.parents.contains(&console.purl));
+
+        let memory = find("pkg:nuget/System.Memory@4.5.5");
+        assert_eq!(memory.relation, maplit::hashset! {Relation::Indirect});
+        assert!(memory
+            .purl
+            .ends_with("?restriction=%7C%7C%20(%3D%3D%20net461)%20(%3D%3D%20net6.0)"));
+        assert_eq!(
+            find("pkg:nuget/Serilog@2.12").purl,
+            "pkg:nuget/Serilog@2.12"
+        );
+
+        let private = find("pkg:nuget/Contoso.Internal@1.0.0");
+        assert!(private.purl.contains("repository_url="));
+
+        let fake = find("pkg:nuget/FAKE@5.0");
+        assert_eq!(fake.scope, "Build");
+        assert!(!fake.is_runtime);
+
+        let globbing = find("pkg:github/fsharp/FAKE@0341a2e614eb2a7f34607cec914eb0ed83ce9add");
+        assert!(globbing
+            .purl
+            .ends_with("#src/app/FakeLib/Globbing/Globbing.fs"));
+
+        assert!(package
+            .dependencies
+            .iter()
+            .any(|d| d.purl.starts_with("pkg:generic/test1.fs?download_url=")));
+    }
+
+    #[tokio::test]
+    async fn paket_lock_only() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/paket"));
+        assert!(matches!(
+            Paket::new().recognize(&dir.join("paket.lock")).await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/paket/lock-only/paket.lock"
+        ));
+        let package = Paket::new().recognize(path).await.unwrap();
+        let deps = &package.dependencies;
+        assert_eq!(deps.len(), 2);
+        assert_eq!(deps[0].purl, "pkg:nuget/Newtonsoft.Json@13.0.3");
+        assert_eq!(deps[0].relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(
+            deps[1].purl,
+            "pkg:nuget/System.Buffers@4.5.1?restriction=%3D%3D%20netstandard2.0"
+        );
+        assert_eq!(deps[1].relation, maplit::hashset! {Relation::Indirect});
+        assert!(deps[1].parents.contains(&deps[0].purl));
+    }
+}
@@ -0,0 +1,224 @@
+//! Parser for NuGet `obj/project.assets.json` files.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::types::Relation;
+
+use super::make_purl;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectAssets {
+    /// Resolved libraries, keyed by target (`net6.0`, `net6.0/win-x64`) and
+    /// then by `Name/Version`.
+    #[serde(default)]
+    targets: IndexMap<String, IndexMap<String, TargetLibrary>>,
+    #[serde(default)]
+    libraries: HashMap<String, Library>,
+    /// Direct dependencies as `Name >= Version`, keyed by framework.
+    #[serde(default)]
+    project_file_dependency_groups: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TargetLibrary {
+    #[serde(rename = "type")]
+    typ: String,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+    /// Reference assemblies, `_._` for packages without any.
+    #[serde(default)]
+    compile: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    runtime: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Library {
+    sha512: Option<String>,
+}
+
+fn split_library_key(key: &str) -> Option<(&str, &str)> {
+    key.split_once('/')
+}
+
+pub fn parse_str(content: &str) -> Result<Package, SourcePkgError> {
+    let assets: ProjectAssets = serde_json::from_str(content)?;
+
+    let mut packages: IndexMap<String, DependentPackage> = IndexMap::new();
+
+    for (target, libraries) in &assets.targets {
+        // Runtime specific targets share the direct dependencies of their framework
+        let framework = target.split('/').next().unwrap_or(target);
+        let direct = assets
+            .project_file_dependency_groups
+            .get(framework)
+            .into_iter()
+            .chain(assets.project_file_dependency_groups.get(""))
+            .flatten()
+            .filter_map(|dep| dep.split_whitespace().next())
+            .map(|name| name.to_lowercase())
+            .collect::<HashSet<_>>();
+
+        let mut purls = HashMap::new();
+
+        for (key, library) in libraries {
+            if library.typ != "package" {
+                // Project references are not packages
+                continue;
+            }
+
+            let (name, version) = if let Some(x) = split_library_key(key) {
+                x
+            } else {
+                log::warn!("Invalid library key: {}", key);
+                continue;
+            };
+
+            let sha512 = assets.libraries.get(key).and_then(|l| l.sha512.as_deref());
+            let purl = make_purl(name, version, sha512);
+            purls.insert(name.to_lowercase(), purl.clone());
+
+            let dep = packages
+                .entry(purl.clone())
+                .or_insert_with(|| DependentPackage {
+                    purl,
+                    requirement: version.to_string(),
+                    scope: framework.to_string(),
+                    is_resolved: true,
+                    ..Default::default()
+                });
+
+            if direct.contains(&name.to_lowercase()) {
+                dep.relation.insert(Relation::Direct);
+            }
+        }
+
+        for (key, library) in libraries {
+            let parent = if let Some(p) =
+                split_library_key(key).and_then(|(name, _)| purls.get(&name.to_lowercase()))
+            {
+                p
+            } else {
+                continue;
+            };
+
+            for dep_name in library.dependencies.keys() {
+                let child = if let Some(c) = purls.get(&dep_name.to_lowercase()) {
+                    c
+                } else {
+                    log::warn!("Could not find dependency {} in {}", dep_name, target);
+                    continue;
+                };
+
+                if let Some(dep) = packages.get_mut(child) {
+                    dep.parents.insert(parent.clone());
+                    dep.relation.insert(Relation::Indirect);
+                }
+            }
+        }
+    }
+
+    Ok(Package {
+        dependencies: packages.into_values().collect(),
+        ..Default::default()
+    })
+}
+
+/// Names of the assemblies of every package, keyed by lower-cased package id.
+///
+/// Packages without assemblies, like analyzers and meta-packages, have none.
+pub fn assemblies(content: &str) -> Result<HashMap<String, HashSet<String>>, SourcePkgError> {
+    let assets: ProjectAssets = serde_json::from_str(content)?;
+
+    let mut ret: HashMap<String, HashSet<String>> = HashMap::new();
+
+    for libraries in assets.targets.values() {
+        for (key, library) in libraries {
+            if library.typ != "package" {
+                continue;
+            }
+            let name = if let Some((name, _)) = split_library_key(key) {
+                name.to_lowercase()
+            } else {
+                continue;
+            };
+
+            let entry = ret.entry(name).or_default();
+            for file in library.compile.keys().chain(library.runtime.keys()) {
+                let file_name = file.rsplit('/').next().unwrap_or(file);
+                if let Some(assembly) = file_name.strip_suffix(".dll") {
+                    entry.insert(assembly.to_string());
+                }
+            }
+        }
+    }
+
+    Ok(ret)
+}
+
+pub struct NuGetProjectAssets {}
+
+impl NuGetProjectAssets {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for NuGetProjectAssets {
+    fn get_name(&self) -> String {
+        "nuget".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "nuget-assets".into()
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["project.assets.json"]
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = tokio::fs::read_to_string(path).await?;
+        parse_str(&content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn project_assets() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nuget/assets/obj/project.assets.json"
+        ));
+
+        let package = NuGetProjectAssets::new().recognize(path).await.unwrap();
+        assert_eq!(package.dependencies.len(), 2);
+
+        let json = package
+            .dependencies
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:nuget/Newtonsoft.Json@13.0.1"))
+            .unwrap();
+        assert_eq!(json.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(json.parents.len(), 1);
+
+        let bson = package
+            .dependencies
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:nuget/Newtonsoft.Json.Bson@1.0.2"))
+            .unwrap();
+        assert!(bson.relation.contains(&Relation::Direct));
+        assert!(bson.purl.contains("checksum=sha512:"));
+    }
+}
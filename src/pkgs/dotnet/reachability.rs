@@ -0,0 +1,309 @@
+//! Reachability of NuGet packages from the namespaces used by C# sources.
+//!
+//! Assemblies are assumed to be named after their root namespace, as is the
+//! convention for NuGet packages: `Newtonsoft.Json.dll` provides
+//! `Newtonsoft.Json` and its sub-namespaces.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use tree_sitter::{Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp, query_matches};
+use crate::pkgs::common::model::Package;
+use crate::types::{Reachability, Relation};
+
+use super::project_assets;
+
+static CS_USING_QUERY: &str = "(using_directive) @using";
+/// Fully qualified names used without a `using`, `Newtonsoft.Json.JsonConvert`.
+static CS_QUALIFIED_NAME_QUERY: &str = "
+[
+    (qualified_name) @name
+    (member_access_expression) @name
+]
+";
+
+/// Build outputs of MSBuild projects.
+const BUILD_DIRS: &[&str] = &["bin", "obj"];
+
+/// Assemblies commonly split from the namespace they provide types for, like
+/// `Microsoft.Extensions.Logging.Abstractions` for `Microsoft.Extensions.Logging`.
+const ASSEMBLY_SUFFIXES: &[&str] = &[".Abstractions", ".Core"];
+
+lazy_static! {
+    static ref DOTTED_NAME: Regex = Regex::new(r"^[A-Za-z_]\w*(\.[A-Za-z_]\w*)+$").unwrap();
+}
+
+/// Returns the namespaces imported by `code` and the qualified names it uses.
+pub fn process_cs(code: &str) -> Result<HashSet<String>, SourcePkgError> {
+    lazy_static! {
+        static ref USING_QUERY: Query =
+            Query::new(&tree_sitter_c_sharp::language(), CS_USING_QUERY).unwrap();
+        static ref QUALIFIED_NAME_QUERY: Query =
+            Query::new(&tree_sitter_c_sharp::language(), CS_QUALIFIED_NAME_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut names = HashSet::new();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_c_sharp::language())
+        .unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    for captures in query_matches(root, &USING_QUERY, code_raw) {
+        for capture in captures {
+            // `using Alias = Some.Namespace;` names the alias first
+            let count = capture.node.named_child_count();
+            if let Some(namespace) = count
+                .checked_sub(1)
+                .and_then(|i| capture.node.named_child(i))
+            {
+                let namespace = namespace.utf8_text(code_raw)?;
+                names.insert(namespace.trim_start_matches("global::").to_string());
+            }
+        }
+    }
+
+    for captures in query_matches(root, &QUALIFIED_NAME_QUERY, code_raw) {
+        for capture in captures {
+            let text = capture.node.utf8_text(code_raw)?;
+            let text = text.trim_start_matches("global::");
+            if DOTTED_NAME.is_match(text) {
+                names.insert(text.to_string());
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+/// Whether any of `names` may refer to a type of `assembly`.
+pub fn is_referenced(assembly: &str, names: &HashSet<String>) -> bool {
+    let mut namespaces = std::iter::once(assembly).chain(
+        ASSEMBLY_SUFFIXES
+            .iter()
+            .filter_map(|suffix| assembly.strip_suffix(suffix)),
+    );
+
+    namespaces.any(|namespace| {
+        names.iter().any(|name| {
+            name == namespace
+                || name
+                    .strip_prefix(namespace)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    })
+}
+
+/// Default location of the global NuGet packages folder.
+fn nuget_packages_folder() -> Option<PathBuf> {
+    if let Some(folder) = std::env::var_os("NUGET_PACKAGES") {
+        return Some(PathBuf::from(folder));
+    }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".nuget").join("packages"))
+}
+
+/// Assemblies of a package in the NuGet cache, in
+/// `<id>/<version>/{lib,ref}/<framework>/*.dll`, the latest version available
+/// if it's unknown.
+fn find_in_cache(folder: &Path, name: &str, version: &str) -> Option<HashSet<String>> {
+    let package_dir = folder.join(name.to_lowercase());
+
+    let version = if !version.is_empty() && package_dir.join(version.to_lowercase()).is_dir() {
+        version.to_lowercase()
+    } else {
+        let mut versions = std::fs::read_dir(&package_dir)
+            .ok()?
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .collect::<Vec<_>>();
+        versions.sort_by(
+            |a, b| match (semver::Version::parse(a), semver::Version::parse(b)) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.cmp(b),
+            },
+        );
+        versions.pop()?
+    };
+
+    let mut assemblies = HashSet::new();
+    for dir in ["lib", "ref"] {
+        let frameworks = if let Ok(f) = std::fs::read_dir(package_dir.join(&version).join(dir)) {
+            f
+        } else {
+            continue;
+        };
+        for framework in frameworks.flatten() {
+            for file in std::fs::read_dir(framework.path())
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let file_name = file.file_name().to_string_lossy().into_owned();
+                if let Some(assembly) = file_name.strip_suffix(".dll") {
+                    assemblies.insert(assembly.to_string());
+                }
+            }
+        }
+    }
+
+    Some(assemblies)
+}
+
+fn walk_reachability(path: &Path) -> HashSet<String> {
+    let (walk, matcher) = build_walker_excluding(path, &["csharp"], BUILD_DIRS);
+
+    let mut ret = HashSet::new();
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        if match_ftyp(&entry, &matcher).is_none() {
+            continue;
+        }
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        match process_cs(&content) {
+            Ok(names) => ret.extend(names),
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+
+    ret
+}
+
+/// Marks the direct runtime dependencies of the project in `project_dir`
+/// whose assemblies no `.cs` file uses as unreachable.
+///
+/// `usings` are the global usings of the project evaluation. Assemblies are
+/// read from `obj/project.assets.json`, then from the NuGet cache. Packages
+/// without any assembly, like analyzers, are left undecided.
+pub async fn mark_reachability(
+    project_dir: &Path,
+    usings: Vec<String>,
+    mut res: Package,
+) -> Package {
+    if res.dependencies.is_empty() {
+        return res;
+    }
+
+    let project_dir = project_dir.to_path_buf();
+    let (mut names, assets) = tokio::task::spawn_blocking(move || {
+        let assets_path = project_dir.join("obj").join("project.assets.json");
+        let assets = std::fs::read_to_string(assets_path)
+            .ok()
+            .and_then(|content| {
+                project_assets::assemblies(&content)
+                    .map_err(|e| log::warn!("Failed to read assemblies: {}", e))
+                    .ok()
+            })
+            .unwrap_or_default();
+
+        (walk_reachability(&project_dir), assets)
+    })
+    .await
+    .unwrap();
+
+    names.extend(usings);
+    log::debug!("Referenced namespaces: {:?}", names);
+
+    let cache = nuget_packages_folder();
+    let assemblies_of = |name: &str, version: &str| -> HashSet<String> {
+        if let Some(a) = assets.get(&name.to_lowercase()) {
+            return a.clone();
+        }
+        if let Some(a) = cache
+            .as_deref()
+            .and_then(|folder| find_in_cache(folder, name, version))
+        {
+            return a;
+        }
+        maplit::hashset! {name.to_string()}
+    };
+
+    for dep in res.dependencies.iter_mut() {
+        if dep.is_runtime && dep.relation.contains(&Relation::Direct) {
+            let purl = if let Ok(p) = dep.purl.parse::<packageurl::PackageUrl>() {
+                p
+            } else {
+                continue;
+            };
+
+            let assemblies = assemblies_of(purl.name(), purl.version().unwrap_or_default());
+            if assemblies.is_empty() {
+                continue;
+            }
+
+            if !assemblies.iter().any(|a| is_referenced(a, &names)) {
+                dep.reachable = Reachability::No;
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cs_usings() {
+        let code = r#"
+global using System.Text;
+global using global::System.Linq;
+using Newtonsoft.Json;
+using static Serilog.Log;
+using Policy = Polly.Policy;
+
+namespace App;
+
+public class Service
+{
+    public object Load(string s) => Dapper.SqlMapper.Query(s);
+}
+"#;
+
+        let names = process_cs(code).unwrap();
+        assert!(names.contains("System.Text"));
+        assert!(names.contains("System.Linq"));
+        assert!(names.contains("Newtonsoft.Json"));
+        assert!(names.contains("Serilog.Log"));
+        assert!(names.contains("Polly.Policy"));
+        assert!(!names.contains("Policy"));
+        assert!(names.contains("Dapper.SqlMapper.Query"));
+
+        assert!(is_referenced("Newtonsoft.Json", &names));
+        assert!(is_referenced("Serilog", &names));
+        assert!(!is_referenced("Newtonsoft.Json.Bson", &names));
+        assert!(is_referenced("Dapper", &names));
+        assert!(!is_referenced("Polly.Extensions.Http", &names));
+        assert!(is_referenced(
+            "Microsoft.Extensions.Logging.Abstractions",
+            &maplit::hashset! {"Microsoft.Extensions.Logging".to_string()}
+        ));
+    }
+}
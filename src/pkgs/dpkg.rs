@@ -0,0 +1,347 @@
+//! Packages installed on Debian-based systems, from `var/lib/dpkg/status`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use packageurl::PackageUrl;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::os_release::{self, OsRelease};
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+/// Parses the paragraphs of a Debian control file, with the lines continuing
+/// a field joined by newlines.
+pub fn parse_paragraphs(content: &str) -> Vec<HashMap<String, String>> {
+    let mut ret = vec![];
+    let mut fields = HashMap::new();
+    let mut last_key: Option<String> = None;
+
+    for line in content.lines() {
+        if line.trim().is_empty() {
+            if !fields.is_empty() {
+                ret.push(std::mem::take(&mut fields));
+            }
+            last_key = None;
+        } else if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(value) = last_key.as_ref().and_then(|k| fields.get_mut(k)) {
+                let value: &mut String = value;
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim().to_string();
+            fields.insert(key.clone(), value.trim().to_string());
+            last_key = Some(key);
+        }
+    }
+    if !fields.is_empty() {
+        ret.push(fields);
+    }
+
+    ret
+}
+
+/// The groups of alternative package names of a relationship field like
+/// `Depends`, `libc6 (>= 2.34), libgcc-s1 | libgcc1`.
+fn relationship(field: &str) -> Vec<Vec<&str>> {
+    field
+        .split(',')
+        .map(|group| {
+            group
+                .split('|')
+                .filter_map(|alternative| {
+                    // `name:arch (>= version) [arch] <profile>`
+                    let name = alternative.split_whitespace().next()?;
+                    Some(name.split(':').next().unwrap_or(name))
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|group| !group.is_empty())
+        .collect()
+}
+
+/// The licenses of the machine-readable `copyright` file of a package.
+fn copyright_license(root: &Path, name: &str) -> String {
+    let path = root.join("usr/share/doc").join(name).join("copyright");
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) if c.starts_with("Format:") => c,
+        _ => return String::new(),
+    };
+
+    let mut licenses: Vec<String> = vec![];
+    for fields in parse_paragraphs(&content) {
+        // The name is on the first line, the text on the following ones
+        let license = fields
+            .get("License")
+            .and_then(|l| l.lines().next())
+            .map(str::trim)
+            .filter(|l| !l.is_empty());
+        if let Some(license) = license {
+            if !licenses.iter().any(|l| l == license) {
+                licenses.push(license.to_string());
+            }
+        }
+    }
+
+    licenses.join(" AND ")
+}
+
+/// Installed packages of the paragraphs of a status database, with the
+/// packages depending on them as parents.
+///
+/// Licenses are read from the documentation of the packages under `root`.
+pub fn build_graph(
+    paragraphs: &[HashMap<String, String>],
+    root: &Path,
+    release: Option<&OsRelease>,
+) -> Vec<DependentPackage> {
+    let namespace = release.map_or("debian", |r| r.id.as_str());
+    let distro = release.and_then(|r| r.distro());
+
+    let installed = paragraphs
+        .iter()
+        .filter(|fields| {
+            fields
+                .get("Status")
+                .is_none_or(|s| s.ends_with(" installed"))
+        })
+        .filter_map(|fields| {
+            let name = fields.get("Package")?;
+            let version = fields.get("Version")?;
+
+            let mut purl = PackageUrl::new("deb", name.as_str()).ok()?;
+            purl.with_namespace(namespace)
+                .with_version(version.as_str());
+            if let Some(arch) = fields.get("Architecture") {
+                purl.add_qualifier("arch", arch.as_str()).ok()?;
+            }
+            if let Some(distro) = &distro {
+                purl.add_qualifier("distro", distro.as_str()).ok()?;
+            }
+            // `Source: gcc-12 (12.2.0-14)` when the version differs
+            if let Some(source) = fields.get("Source") {
+                let upstream = match source.split_once(' ') {
+                    Some((source, version)) => {
+                        format!("{}@{}", source, version.trim_matches(&['(', ')', ' '][..]))
+                    }
+                    None => source.clone(),
+                };
+                purl.add_qualifier("upstream", upstream).ok()?;
+            }
+
+            Some((fields, purl.to_string()))
+        })
+        .collect::<Vec<_>>();
+
+    // Virtual packages resolve to the packages providing them
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, (fields, _)) in installed.iter().enumerate() {
+        index.insert(fields["Package"].as_str(), i);
+    }
+    for (i, (fields, _)) in installed.iter().enumerate() {
+        for group in fields
+            .get("Provides")
+            .map(|p| relationship(p))
+            .unwrap_or_default()
+        {
+            for name in group {
+                index.entry(name).or_insert(i);
+            }
+        }
+    }
+
+    let mut dependencies = installed
+        .iter()
+        .map(|(fields, purl)| DependentPackage {
+            purl: purl.clone(),
+            requirement: fields["Version"].clone(),
+            is_resolved: true,
+            declared_license: copyright_license(root, &fields["Package"]),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    for (i, (fields, purl)) in installed.iter().enumerate() {
+        let groups = ["Pre-Depends", "Depends"]
+            .iter()
+            .filter_map(|key| fields.get(*key))
+            .flat_map(|field| relationship(field));
+        for group in groups {
+            // The first alternative installed satisfies the dependency
+            match group.iter().find_map(|name| index.get(name)) {
+                Some(&child) if child != i => {
+                    dependencies[child].parents.insert(purl.clone());
+                    dependencies[child].relation.insert(Relation::Indirect);
+                }
+                Some(_) => {}
+                None => log::debug!("{} depends on {:?}, which is not installed", purl, group),
+            }
+        }
+    }
+
+    for dep in dependencies.iter_mut() {
+        if dep.parents.is_empty() {
+            dep.relation.insert(Relation::Direct);
+        }
+    }
+
+    dependencies
+}
+
+/// The files of a distroless `status.d` database, one per package, without
+/// their `.md5sums`.
+fn status_d_files(dir: &Path) -> Vec<PathBuf> {
+    let mut ret = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_none_or(|e| e != "md5sums"))
+        .collect::<Vec<_>>();
+    ret.sort();
+    ret
+}
+
+pub struct DpkgStatus {}
+
+impl DpkgStatus {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for DpkgStatus {
+    fn get_name(&self) -> String {
+        "deb".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "dpkg-status".into()
+    }
+
+    /// The whole database is reported once, by `status` if it exists, or by
+    /// the first file of `status.d` otherwise.
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        // `<root>/var/lib/dpkg/status` or `<root>/var/lib/dpkg/status.d/<name>`
+        let dpkg_dir = if path.parent().is_some_and(|p| p.ends_with("status.d")) {
+            path.parent().and_then(|p| p.parent())
+        } else {
+            path.parent()
+        };
+        let dpkg_dir = dpkg_dir
+            .filter(|p| p.ends_with("var/lib/dpkg"))
+            .ok_or(SourcePkgError::NotSupported)?;
+        let root = dpkg_dir.ancestors().nth(3).unwrap_or(dpkg_dir);
+
+        let status_path = dpkg_dir.join("status");
+        let status_d = status_d_files(&dpkg_dir.join("status.d"));
+        if path != status_path {
+            if !status_d.iter().any(|p| p == path) {
+                return Err(SourcePkgError::NotSupported);
+            }
+            if status_path.exists() || status_d.first().is_some_and(|p| p != path) {
+                return Err(SourcePkgError::Skipped);
+            }
+        }
+
+        let mut paragraphs = vec![];
+        if status_path.exists() {
+            paragraphs.extend(parse_paragraphs(
+                &tokio::fs::read_to_string(&status_path).await?,
+            ));
+        }
+        for file in status_d {
+            paragraphs.extend(parse_paragraphs(&tokio::fs::read_to_string(file).await?));
+        }
+
+        let release = os_release::read(root);
+
+        Ok(Package {
+            dependencies: build_graph(&paragraphs, root, release.as_ref()),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["status", "status.d/*"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn status() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/dpkg/rootfs/var/lib/dpkg/status"
+        ));
+
+        let package = DpkgStatus::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+
+        let base_files = "pkg:deb/debian/base-files@12.4+deb12u5?arch=amd64&distro=debian-12";
+        let libc6 =
+            "pkg:deb/debian/libc6@2.36-9+deb12u4?arch=amd64&distro=debian-12&upstream=glibc";
+        let libgcc = "pkg:deb/debian/libgcc-s1@12.2.0-14?arch=amd64&distro=debian-12&upstream=gcc-12%4012.2.0-14";
+        let tzdata = "pkg:deb/debian/tzdata@2024a-0+deb12u1?arch=all&distro=debian-12";
+
+        assert_eq!(
+            deps[base_files].parents,
+            maplit::hashset! {libgcc.to_string(), tzdata.to_string()}
+        );
+        assert_eq!(
+            deps[base_files].relation,
+            maplit::hashset! {Relation::Indirect}
+        );
+        // Through the virtual `libgcc1`
+        assert_eq!(deps[libgcc].parents, maplit::hashset! {libc6.to_string()});
+        assert_eq!(deps[libc6].relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(deps[tzdata].relation, maplit::hashset! {Relation::Direct});
+
+        assert_eq!(deps[libc6].declared_license, "LGPL-2.1+ AND BSD-3-clause");
+        assert_eq!(deps[tzdata].declared_license, "");
+    }
+
+    #[tokio::test]
+    async fn status_d() {
+        let dir = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/dpkg/distroless/var/lib/dpkg/status.d"
+        ));
+
+        let scanner = crate::Scanner::new();
+        let (name, package) = scanner.scan(dir.join("base-files"), dir).await.unwrap();
+        assert_eq!(name, "deb");
+        let purls = package
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:deb/debian/base-files@12.4+deb12u5?arch=amd64&distro=debian-12",
+                "pkg:deb/debian/libssl3@3.0.11-1~deb12u2?arch=amd64&distro=debian-12&upstream=openssl",
+            ]
+        );
+
+        // Reported once for the whole database
+        assert!(matches!(
+            scanner.scan(dir.join("libssl3"), dir).await,
+            Err(SourcePkgError::Skipped)
+        ));
+        assert!(matches!(
+            scanner.scan(dir.join("base-files.md5sums"), dir).await,
+            Err(SourcePkgError::NotSupported)
+        ));
+    }
+}
@@ -73,6 +73,7 @@ impl ElmJson {
                         relation: maplit::hashset! { crate::types::Relation::Direct },
                         parents: HashSet::new(),
                         reachable: Default::default(),
+                        declared_license: Default::default(),
                     });
                 }
 
@@ -89,6 +90,7 @@ impl ElmJson {
                         relation: maplit::hashset! { crate::types::Relation::Indirect },
                         parents: HashSet::new(),
                         reachable: Default::default(),
+                        declared_license: Default::default(),
                     });
                 }
             }
@@ -106,6 +108,7 @@ impl ElmJson {
                         relation: maplit::hashset! { crate::types::Relation::Direct },
                         parents: HashSet::new(),
                         reachable: Default::default(),
+                        declared_license: Default::default(),
                     });
                 }
             }
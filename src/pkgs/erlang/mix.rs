@@ -0,0 +1,469 @@
+//! Mix projects: the packages locked in `mix.lock` along with the `deps`
+//! declared in `mix.exs`, or else those `deps` alone.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::term::{parse_term, Dialect, Term};
+use super::{locked, make_purl, Source};
+
+/// The source of a `mix.lock` entry, `{:hex, name, version, inner_checksum,
+/// managers, deps, repo, outer_checksum}`, `{:git, url, rev, opts}` or a
+/// `{:path, ...}`. Only the outer checksum is of the tarball.
+fn lock_source(entry: &[Term]) -> Option<Source<'_>> {
+    match entry.first()?.as_atom()? {
+        "hex" => Some(Source::Hex(
+            entry.get(1)?.as_name()?,
+            entry.get(2)?.as_str()?,
+            entry.get(7).and_then(Term::as_str),
+        )),
+        "git" => Some(Source::Git(
+            entry.get(1)?.as_str()?,
+            entry.get(2).and_then(Term::as_str),
+        )),
+        "path" => Some(Source::Path),
+        _ => None,
+    }
+}
+
+/// Parses a `mix.lock`, the dependencies of the Hex packages being their
+/// parents. The packages `declared` by the `mix.exs`, by name, are the direct
+/// dependencies with their environments and options, and only the packages
+/// they need at runtime are. Without it, packages no other package depends on
+/// are direct dependencies.
+pub fn parse_lock(
+    content: &str,
+    declared: Option<&[(String, DependentPackage)]>,
+) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let entries = match parse_term(content, Dialect::Elixir)? {
+        Term::Map(entries) => entries,
+        _ => return Err(SourcePkgError::GenericsError("Invalid mix.lock")),
+    };
+
+    let mut dependencies = vec![];
+    let mut index = HashMap::new();
+    let mut children = vec![];
+    for (key, value) in &entries {
+        let (name, entry) = match (key.as_name(), value.as_tuple()) {
+            (Some(name), Some(entry)) => (name, entry),
+            _ => continue,
+        };
+        let dep = match lock_source(entry).and_then(|source| locked(name, &source)) {
+            Some(dep) => dep,
+            None => {
+                log::warn!("Unknown mix.lock entry {}", name);
+                continue;
+            }
+        };
+        index.insert(name, dependencies.len());
+        dependencies.push(dep);
+
+        // `{:cowlib, "2.12.1", [hex: :cowlib, repo: "hexpm", optional: false]}`
+        let deps = entry
+            .get(5)
+            .filter(|_| entry[0].as_atom() == Some("hex"))
+            .and_then(Term::as_list)
+            .unwrap_or_default();
+        children.push(
+            deps.iter()
+                .filter_map(|d| d.as_tuple()?.first()?.as_name())
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    for (parent, names) in children.iter().enumerate() {
+        let purl = dependencies[parent].purl.clone();
+        for name in names {
+            match index.get(name) {
+                Some(&child) => {
+                    dependencies[child].parents.insert(purl.clone());
+                }
+                // Optional dependencies not needed by the project
+                None => log::debug!("{} depends on {}, which is not locked", purl, name),
+            }
+        }
+    }
+
+    let declared = match declared {
+        Some(declared) => declared,
+        None => {
+            for dep in dependencies.iter_mut() {
+                if dep.parents.is_empty() {
+                    dep.relation.insert(Relation::Direct);
+                } else {
+                    dep.relation.insert(Relation::Indirect);
+                }
+            }
+            return Ok(dependencies);
+        }
+    };
+
+    // The packages needed at runtime, from the runtime direct dependencies.
+    // Those nothing depends on but that are not found in the `mix.exs`, which
+    // is not evaluated, are still taken as direct ones.
+    let mut runtime = vec![false; dependencies.len()];
+    let mut queue = vec![];
+    let mut is_declared = vec![false; dependencies.len()];
+    for (name, decl) in declared {
+        if let Some(&i) = index.get(name.as_str()) {
+            let dep = &mut dependencies[i];
+            dep.relation.insert(Relation::Direct);
+            dep.scope = decl.scope.clone();
+            dep.is_optional = decl.is_optional;
+            is_declared[i] = true;
+            if decl.is_runtime {
+                queue.push(i);
+            }
+        }
+    }
+    for (i, dep) in dependencies.iter_mut().enumerate() {
+        if !is_declared[i] && dep.parents.is_empty() {
+            dep.relation.insert(Relation::Direct);
+            queue.push(i);
+        }
+    }
+    while let Some(i) = queue.pop() {
+        if std::mem::replace(&mut runtime[i], true) {
+            continue;
+        }
+        queue.extend(
+            children[i]
+                .iter()
+                .filter_map(|name| index.get(name).copied()),
+        );
+    }
+
+    for (dep, is_runtime) in dependencies.iter_mut().zip(runtime) {
+        dep.is_runtime = is_runtime;
+        if !dep.parents.is_empty() {
+            dep.relation.insert(Relation::Indirect);
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// The items of the list returned by the `deps` function, split on the commas
+/// outside of brackets, strings and comments.
+fn deps_items(content: &str) -> Vec<&str> {
+    lazy_static! {
+        static ref DEPS: Regex = Regex::new(r"defp?\s+deps\s*(?:\(\s*\))?\s*do\s*\[").unwrap();
+    }
+
+    let start = match DEPS.find(content) {
+        Some(m) => m.end(),
+        None => return vec![],
+    };
+
+    let mut ret = vec![];
+    let mut depth = 0;
+    let mut item_start = start;
+    let mut chars = content[start..].char_indices().map(|(i, c)| (start + i, c));
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '#' => {
+                for (_, c) in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' if depth == 0 => {
+                ret.push(content[item_start..i].trim());
+                break;
+            }
+            '}' | ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                ret.push(content[item_start..i].trim());
+                item_start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    ret.into_iter()
+        .map(|mut item| {
+            // Comments before the item
+            while item.starts_with('#') {
+                item = item.split_once('\n').map_or("", |(_, rest)| rest).trim();
+            }
+            item
+        })
+        .filter(|item| item.starts_with('{'))
+        .collect()
+}
+
+/// A dependency declared like `{:plug, "~> 1.14", only: [:dev, :test]}` or
+/// `{:ecto, github: "elixir-ecto/ecto", tag: "v3.11.0"}`. Dependencies on
+/// other applications of an umbrella project are skipped.
+fn parse_declared(item: &str) -> Option<(String, DependentPackage)> {
+    lazy_static! {
+        static ref NAME: Regex = Regex::new(r#"^\{\s*:(\w+)\s*(?:,\s*"([^"]*)")?"#).unwrap();
+        static ref OPTION: Regex = Regex::new(r#"(\w+):\s*(\[[^\]]*\]|"[^"]*"|:?\w+)"#).unwrap();
+    }
+
+    let caps = NAME.captures(item)?;
+    let name = caps.get(1)?.as_str();
+    let mut requirement = caps.get(2).map_or("", |m| m.as_str()).to_string();
+
+    let options = OPTION
+        .captures_iter(&item[caps.get(0)?.end()..])
+        .filter_map(|c| Some((c.get(1)?.as_str(), c.get(2)?.as_str().trim_matches('"'))))
+        .collect::<HashMap<_, _>>();
+    if options.get("in_umbrella") == Some(&"true") {
+        return None;
+    }
+
+    let hex_name = options
+        .get("hex")
+        .map(|h| h.trim_start_matches(':'))
+        .unwrap_or(name);
+    let git = options.get("git").map(|g| g.to_string()).or_else(|| {
+        options
+            .get("github")
+            .map(|g| format!("https://github.com/{}", g))
+    });
+    let purl = match &git {
+        Some(git) => {
+            let rev = ["ref", "tag", "branch"]
+                .iter()
+                .find_map(|k| options.get(k).copied());
+            requirement = rev.unwrap_or_default().to_string();
+            make_purl(name, &Source::Git(git, rev))?
+        }
+        None if options.contains_key("path") => make_purl(name, &Source::Path)?,
+        None => make_purl(hex_name, &Source::Path)?,
+    };
+
+    // `only: :test` or `only: [:dev, :test]`
+    let only = options
+        .get("only")
+        .map(|o| {
+            o.trim_matches(['[', ']'])
+                .split(',')
+                .map(|env| env.trim().trim_start_matches(':'))
+                .filter(|env| !env.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let is_runtime =
+        options.get("runtime") != Some(&"false") && (only.is_empty() || only.contains(&"prod"));
+
+    let dep = DependentPackage {
+        purl,
+        requirement,
+        scope: only.join(","),
+        is_runtime,
+        is_optional: options.get("optional") == Some(&"true"),
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    };
+    Some((name.to_string(), dep))
+}
+
+/// The dependencies declared in a `mix.exs`, by name.
+pub fn declared(content: &str) -> Vec<(String, DependentPackage)> {
+    deps_items(content)
+        .into_iter()
+        .filter_map(parse_declared)
+        .collect()
+}
+
+/// Reads the dependencies declared in a `mix.exs` statically, without
+/// evaluating it.
+pub fn parse_mix_exs(content: &str) -> Package {
+    lazy_static! {
+        static ref APP: Regex = Regex::new(r"\bapp:\s*:(\w+)").unwrap();
+        static ref VERSION: Regex = Regex::new(r#"\bversion:\s*"([^"]+)""#).unwrap();
+    }
+
+    Package {
+        name: APP
+            .captures(content)
+            .map_or(String::new(), |c| c[1].to_string()),
+        version: VERSION
+            .captures(content)
+            .map_or(String::new(), |c| c[1].to_string()),
+        primary_language: "Elixir".into(),
+        dependencies: declared(content).into_iter().map(|(_, dep)| dep).collect(),
+        ..Default::default()
+    }
+}
+
+pub struct MixExs {}
+
+impl MixExs {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for MixExs {
+    fn get_name(&self) -> String {
+        "mix".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        if path.with_file_name("mix.lock").exists() {
+            // Already covered by the lock file
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        Ok(parse_mix_exs(&content))
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["mix.exs"]
+    }
+}
+
+pub struct MixLock {}
+
+impl MixLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for MixLock {
+    fn get_name(&self) -> String {
+        "mix".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "mix-lock".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let declared = std::fs::read_to_string(path.with_file_name("mix.exs"))
+            .ok()
+            .map(|mix_exs| declared(&mix_exs));
+        Ok(Package {
+            primary_language: "Elixir".into(),
+            dependencies: parse_lock(&content, declared.as_deref())?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["mix.lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mix_lock() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/erlang/mix"));
+
+        assert!(matches!(
+            MixExs::new().recognize(&dir.join("mix.exs")).await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let package = MixLock::new()
+            .recognize(&dir.join("mix.lock"))
+            .await
+            .unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.split('?').next().unwrap(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 7);
+
+        let plug_cowboy = deps["pkg:hex/plug_cowboy@2.6.1"];
+        assert_eq!(
+            plug_cowboy.purl,
+            "pkg:hex/plug_cowboy@2.6.1?checksum=sha256:de36e1a21f451a18b790f37765db198075c25875c64834bcc82d90b309eb6613"
+        );
+        assert_eq!(plug_cowboy.relation, maplit::hashset! {Relation::Direct});
+
+        let cowboy = deps["pkg:hex/cowboy@2.10.0"];
+        assert_eq!(cowboy.parents, maplit::hashset! {plug_cowboy.purl.clone()});
+        assert_eq!(cowboy.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(
+            deps["pkg:hex/cowlib@2.12.1"].parents,
+            maplit::hashset! {cowboy.purl.clone()}
+        );
+
+        let phoenix = deps["pkg:hex/phoenix"];
+        assert_eq!(
+            phoenix.purl,
+            "pkg:hex/phoenix?vcs_url=git+https://github.com/phoenixframework/phoenix.git%40f1a7b8b0e3a3d2c4e5f6a7b8c9d0e1f2a3b4c5d6"
+        );
+        assert!(phoenix.is_resolved);
+        assert_eq!(phoenix.relation, maplit::hashset! {Relation::Direct});
+        assert!(!deps["pkg:hex/local_lib"].is_resolved);
+
+        // `only: [:dev, :test], runtime: false` in the `mix.exs`
+        let credo = deps["pkg:hex/credo@1.7.5"];
+        assert_eq!(credo.relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(credo.scope, "dev,test");
+        assert!(!credo.is_runtime);
+        let bunt = deps["pkg:hex/bunt@1.0.0"];
+        assert_eq!(bunt.relation, maplit::hashset! {Relation::Indirect});
+        assert!(!bunt.is_runtime);
+        assert!(plug_cowboy.is_runtime);
+        assert!(cowboy.is_runtime);
+    }
+
+    #[tokio::test]
+    async fn mix_exs() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/erlang/mix-exs/mix.exs"
+        ));
+
+        let package = MixExs::new().recognize(filepath).await.unwrap();
+        assert_eq!(package.name, "my_app");
+        assert_eq!(package.version, "0.1.0");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 6);
+
+        assert_eq!(deps["pkg:hex/phoenix"].requirement, "~> 1.7.10");
+        assert!(deps["pkg:hex/phoenix"].is_runtime);
+        let live_reload = deps["pkg:hex/phoenix_live_reload"];
+        assert_eq!(live_reload.scope, "dev");
+        assert!(!live_reload.is_runtime);
+        assert_eq!(deps["pkg:hex/floki"].scope, "dev,test");
+        // `runtime: Mix.env() == :dev`
+        assert!(deps["pkg:hex/esbuild"].is_runtime);
+        assert!(deps["pkg:hex/jason"].is_optional);
+        assert_eq!(
+            deps["pkg:hex/ecto?vcs_url=git+https://github.com/elixir-ecto/ecto%40v3.11.0"]
+                .requirement,
+            "v3.11.0"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Erlang and Elixir packages from Hex, declared by rebar3 and Mix projects.
+
+use packageurl::PackageUrl;
+
+use crate::types::DependentPackage;
+
+pub mod mix;
+pub mod rebar;
+pub mod term;
+
+/// Where a locked package comes from.
+pub enum Source<'a> {
+    /// A Hex package, its name, version and the SHA-256 checksum of its
+    /// tarball.
+    Hex(&'a str, &'a str, Option<&'a str>),
+    /// A git repository at a revision.
+    Git(&'a str, Option<&'a str>),
+    /// A local directory.
+    Path,
+}
+
+/// The purl of the package `name` from `source`, with a `checksum` or
+/// `vcs_url` qualifier.
+pub fn make_purl(name: &str, source: &Source) -> Option<String> {
+    let (name, version) = match source {
+        Source::Hex(hex_name, version, _) => (*hex_name, Some(*version)),
+        _ => (name, None),
+    };
+    let mut purl = PackageUrl::new("hex", name.to_lowercase()).ok()?;
+    if let Some(version) = version {
+        purl.with_version(version.to_string());
+    }
+
+    match source {
+        Source::Hex(_, _, Some(checksum)) => {
+            purl.add_qualifier("checksum", format!("sha256:{}", checksum.to_lowercase()))
+                .ok()?;
+        }
+        Source::Git(url, rev) => {
+            let vcs_url = match rev {
+                Some(rev) => format!("git+{}@{}", url, rev),
+                None => format!("git+{}", url),
+            };
+            purl.add_qualifier("vcs_url", vcs_url).ok()?;
+        }
+        _ => {}
+    }
+    Some(purl.to_string())
+}
+
+/// A locked dependency on `name` from `source`.
+pub fn locked(name: &str, source: &Source) -> Option<DependentPackage> {
+    let requirement = match source {
+        Source::Hex(_, version, _) => version,
+        Source::Git(_, rev) => rev.unwrap_or_default(),
+        Source::Path => "",
+    };
+    Some(DependentPackage {
+        purl: make_purl(name, source)?,
+        requirement: requirement.to_string(),
+        is_resolved: !matches!(source, Source::Path),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,324 @@
+//! rebar3 projects: the packages locked in `rebar.lock`, or else the `deps`
+//! declared in `rebar.config`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::term::{parse_consult, Term};
+use super::{locked, make_purl, Source};
+
+/// The checksums of the `pkg_hash_ext` section of a lock, of the package
+/// tarballs, or else of its `pkg_hash` section.
+fn lock_checksums(sections: Option<&Term>) -> HashMap<&str, &str> {
+    let mut ret = HashMap::new();
+    for key in ["pkg_hash", "pkg_hash_ext"] {
+        let hashes = sections
+            .and_then(|s| s.get(key))
+            .and_then(Term::as_list)
+            .unwrap_or_default();
+        for hash in hashes {
+            if let Some([name, hash]) = hash.as_tuple() {
+                if let (Some(name), Some(hash)) = (name.as_str(), hash.as_str()) {
+                    ret.insert(name, hash);
+                }
+            }
+        }
+    }
+    ret
+}
+
+/// Parses a `rebar.lock`, either a list of locks, or a version with the list
+/// of locks, followed by the checksums of the packages. Locks at level 0 are
+/// direct dependencies.
+pub fn parse_lock(content: &str) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let invalid = || SourcePkgError::GenericsError("Invalid rebar.lock");
+
+    let terms = parse_consult(content)?;
+    let locks = match terms.first() {
+        // `{"1.2.0", [...]}`
+        Some(Term::Tuple(versioned)) => versioned.get(1).and_then(Term::as_list),
+        Some(Term::List(locks)) => Some(locks.as_slice()),
+        None => Some(&[][..]),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    let checksums = lock_checksums(terms.get(1));
+
+    let mut ret = vec![];
+    for lock in locks {
+        // `{<<"cowboy">>, {pkg, <<"cowboy">>, <<"2.10.0">>}, 0}`
+        let (name, source, level) = match lock.as_tuple() {
+            Some([name, source, level, ..]) => (name, source, level),
+            _ => continue,
+        };
+        let (name, source) = match (name.as_name(), source.as_tuple()) {
+            (Some(name), Some(source)) => (name, source),
+            _ => continue,
+        };
+        let source = match source {
+            [kind, pkg_name, version, ..] if kind.as_atom() == Some("pkg") => {
+                match (pkg_name.as_name(), version.as_str()) {
+                    (Some(pkg_name), Some(version)) => {
+                        Source::Hex(pkg_name, version, checksums.get(name).copied())
+                    }
+                    _ => continue,
+                }
+            }
+            // `{git, "url", {ref, "sha"}}`
+            [kind, url, rev, ..] if kind.as_atom() == Some("git") => match url.as_str() {
+                Some(url) => Source::Git(url, rev.as_tuple().and_then(|r| r.get(1)?.as_str())),
+                None => continue,
+            },
+            _ => {
+                log::warn!("Unknown rebar.lock source of {}", name);
+                continue;
+            }
+        };
+
+        if let Some(mut dep) = locked(name, &source) {
+            dep.relation.insert(match level {
+                Term::Number(level) if level == "0" => Relation::Direct,
+                _ => Relation::Indirect,
+            });
+            ret.push(dep);
+        }
+    }
+
+    Ok(ret)
+}
+
+/// A dependency declared like `cowboy`, `{cowboy, "2.10.0"}`,
+/// `{cowboy, "2.10.0", {pkg, other_name}}` or `{cowboy, {git, Url, {tag,
+/// "2.10.0"}}}`.
+fn parse_declared(dep: &Term, scope: &str) -> Option<DependentPackage> {
+    let (name, rest) = match dep {
+        Term::Atom(name) => (name.as_str(), &[][..]),
+        Term::Tuple(items) => (items.first()?.as_name()?, &items[1..]),
+        _ => return None,
+    };
+
+    let mut requirement = "";
+    let mut hex_name = name;
+    let mut git = None;
+    for item in rest {
+        match item {
+            Term::Str(version) => requirement = version,
+            Term::Tuple(source) => match source.first().and_then(Term::as_atom) {
+                Some("pkg") => hex_name = source.get(1)?.as_name()?,
+                Some("git") | Some("git_subdir") => {
+                    let rev = source.get(2).and_then(|r| r.as_tuple()?.get(1)?.as_str());
+                    git = Some((source.get(1)?.as_str()?, rev));
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    let exact = !requirement.is_empty()
+        && requirement
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    let (purl, requirement, is_resolved) = match git {
+        Some((url, rev)) => (
+            make_purl(name, &Source::Git(url, rev))?,
+            rev.unwrap_or_default(),
+            false,
+        ),
+        // A plain version is the exact version of the package
+        None if exact => (
+            make_purl(name, &Source::Hex(hex_name, requirement, None))?,
+            requirement,
+            true,
+        ),
+        None => (make_purl(hex_name, &Source::Path)?, requirement, false),
+    };
+
+    Some(DependentPackage {
+        purl,
+        requirement: requirement.to_string(),
+        scope: scope.to_string(),
+        is_runtime: scope.is_empty() || scope == "prod",
+        is_resolved,
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
+
+/// Reads the `deps` of a `rebar.config`, and of its `profiles` in the scope
+/// of the profile.
+pub fn parse_config(content: &str) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let terms = Term::List(parse_consult(content)?);
+
+    let mut ret = vec![];
+    let deps = terms
+        .get("deps")
+        .and_then(Term::as_list)
+        .unwrap_or_default();
+    ret.extend(deps.iter().filter_map(|d| parse_declared(d, "")));
+
+    let profiles = terms
+        .get("profiles")
+        .and_then(Term::as_list)
+        .unwrap_or_default();
+    for profile in profiles {
+        if let Some([name, options]) = profile.as_tuple() {
+            let name = name.as_name().unwrap_or_default();
+            let deps = options
+                .get("deps")
+                .and_then(Term::as_list)
+                .unwrap_or_default();
+            ret.extend(deps.iter().filter_map(|d| parse_declared(d, name)));
+        }
+    }
+
+    Ok(ret)
+}
+
+pub struct RebarConfig {}
+
+impl RebarConfig {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for RebarConfig {
+    fn get_name(&self) -> String {
+        "rebar".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        if path.with_file_name("rebar.lock").exists() {
+            // Already covered by the lock file
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Erlang".into(),
+            dependencies: parse_config(&content)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["rebar.config"]
+    }
+}
+
+pub struct RebarLock {}
+
+impl RebarLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for RebarLock {
+    fn get_name(&self) -> String {
+        "rebar".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "rebar-lock".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Erlang".into(),
+            dependencies: parse_lock(&content)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["rebar.lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn rebar_lock() {
+        let dir = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/erlang/rebar"
+        ));
+
+        assert!(matches!(
+            RebarConfig::new()
+                .recognize(&dir.join("rebar.config"))
+                .await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let package = RebarLock::new()
+            .recognize(&dir.join("rebar.lock"))
+            .await
+            .unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+
+        let cowboy = deps["pkg:hex/cowboy@2.10.0?checksum=sha256:3afdccb7183cc6f143cb14d3cf51fa00e53db9ec80cdcd525482f5e99bc41d6b"];
+        assert_eq!(cowboy.relation, maplit::hashset! {Relation::Direct});
+        let cowlib = deps["pkg:hex/cowlib@2.12.1?checksum=sha256:163b73f6367a7341b33c794c4e88e7dbfe6498ac42dcd69ef44c5bc5507c8db0"];
+        assert_eq!(cowlib.relation, maplit::hashset! {Relation::Indirect});
+        assert!(deps.contains_key("pkg:hex/jsx@3.1.0"));
+        let recon = deps["pkg:hex/recon?vcs_url=git+https://github.com/ferd/recon.git%40c2a76855be3a226a3148c0dfc21ce000b6186ef8"];
+        assert_eq!(
+            recon.requirement,
+            "c2a76855be3a226a3148c0dfc21ce000b6186ef8"
+        );
+    }
+
+    #[test]
+    fn unversioned_lock() {
+        let deps = parse_lock(
+            "[{<<\"cowboy\">>,{pkg,<<\"cowboy\">>,<<\"2.10.0\">>},0},\n {<<\"cowlib\">>,{pkg,<<\"cowlib\">>,<<\"2.12.1\">>},1}].\n",
+        )
+        .unwrap();
+        assert_eq!(deps[0].purl, "pkg:hex/cowboy@2.10.0");
+        assert_eq!(deps[1].relation, maplit::hashset! {Relation::Indirect});
+    }
+
+    #[tokio::test]
+    async fn rebar_config() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/erlang/rebar-config/rebar.config"
+        ));
+
+        let package = RebarConfig::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 5);
+
+        assert!(deps["pkg:hex/cowboy@2.10.0"].is_resolved);
+        assert_eq!(deps["pkg:hex/jsx"].requirement, "~> 3.1");
+        assert!(!deps["pkg:hex/lager"].is_resolved);
+        assert_eq!(
+            deps["pkg:hex/recon?vcs_url=git+https://github.com/ferd/recon.git%402.5.3"].requirement,
+            "2.5.3"
+        );
+        let meck = deps["pkg:hex/meck@0.9.2"];
+        assert_eq!(meck.scope, "test");
+        assert!(!meck.is_runtime);
+    }
+}
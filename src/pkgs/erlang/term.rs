@@ -0,0 +1,377 @@
+//! Literal terms of Erlang (`rebar.config`, `rebar.lock`) and Elixir
+//! (`mix.lock`), enough to read the dependencies they declare. Anything
+//! else, like function calls, is an error.
+
+use crate::error::SourcePkgError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    Atom(String),
+    /// Strings, charlists and binaries.
+    Str(String),
+    Number(String),
+    Tuple(Vec<Term>),
+    /// Lists, keyword lists being lists of `{key, value}` tuples.
+    List(Vec<Term>),
+    Map(Vec<(Term, Term)>),
+}
+
+impl Term {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Term::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_atom(&self) -> Option<&str> {
+        match self {
+            Term::Atom(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The name of an atom or a string.
+    pub fn as_name(&self) -> Option<&str> {
+        self.as_atom().or_else(|| self.as_str())
+    }
+
+    pub fn as_tuple(&self) -> Option<&[Term]> {
+        match self {
+            Term::Tuple(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Term]> {
+        match self {
+            Term::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    /// The value of `key` in a proplist or keyword list.
+    pub fn get(&self, key: &str) -> Option<&Term> {
+        self.as_list()?
+            .iter()
+            .find_map(|item| match item.as_tuple() {
+                Some([k, v]) if k.as_name() == Some(key) => Some(v),
+                _ => None,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Erlang,
+    Elixir,
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    dialect: Dialect,
+}
+
+fn invalid() -> SourcePkgError {
+    SourcePkgError::GenericsError("Invalid Erlang term")
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '@'
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn skip_blank(&mut self) {
+        let comment = match self.dialect {
+            Dialect::Erlang => '%',
+            Dialect::Elixir => '#',
+        };
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += 1;
+            } else if c == comment {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), SourcePkgError> {
+        self.skip_blank();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    }
+
+    fn ident(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_ident) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, SourcePkgError> {
+        self.pos += 1;
+        let mut ret = String::new();
+        loop {
+            match self.peek().ok_or_else(invalid)? {
+                c if c == quote => {
+                    self.pos += 1;
+                    return Ok(ret);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let c = self.peek().ok_or_else(invalid)?;
+                    ret.push(match c {
+                        'n' => '\n',
+                        't' => '\t',
+                        c => c,
+                    });
+                    self.pos += 1;
+                }
+                c => {
+                    ret.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// Whether an Elixir keyword like `optional:` follows, consuming it.
+    fn keyword(&mut self) -> Result<Option<String>, SourcePkgError> {
+        if self.dialect != Dialect::Elixir {
+            return Ok(None);
+        }
+        let start = self.pos;
+        let key = match self.peek() {
+            Some('"') => self.quoted('"')?,
+            Some(c) if is_ident(c) => self.ident(),
+            _ => return Ok(None),
+        };
+        if self.peek() == Some(':') && self.peek_at(1).is_some_and(char::is_whitespace) {
+            self.pos += 1;
+            Ok(Some(key))
+        } else {
+            self.pos = start;
+            Ok(None)
+        }
+    }
+
+    /// Items up to `close`, with keywords as `{key, value}` tuples.
+    fn items(&mut self, close: char) -> Result<Vec<Term>, SourcePkgError> {
+        let mut ret = vec![];
+        loop {
+            self.skip_blank();
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(ret);
+            }
+            let item = match self.keyword()? {
+                Some(key) => Term::Tuple(vec![Term::Atom(key), self.value()?]),
+                None => self.value()?,
+            };
+            ret.push(item);
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {}
+                _ => return Err(invalid()),
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Term, SourcePkgError> {
+        let mut ret = vec![];
+        loop {
+            self.skip_blank();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(Term::Map(ret));
+            }
+            let entry = match self.keyword()? {
+                Some(key) => (Term::Atom(key), self.value()?),
+                None => {
+                    let key = self.value()?;
+                    self.skip_blank();
+                    if self.peek() != Some('=') || self.peek_at(1) != Some('>') {
+                        return Err(invalid());
+                    }
+                    self.pos += 2;
+                    (key, self.value()?)
+                }
+            };
+            ret.push(entry);
+            self.skip_blank();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {}
+                _ => return Err(invalid()),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Term, SourcePkgError> {
+        self.skip_blank();
+        let c = self.peek().ok_or_else(invalid)?;
+        match c {
+            '{' => {
+                self.pos += 1;
+                Ok(Term::Tuple(self.items('}')?))
+            }
+            '[' => {
+                self.pos += 1;
+                Ok(Term::List(self.items(']')?))
+            }
+            '%' if self.peek_at(1) == Some('{') => {
+                self.pos += 2;
+                self.map()
+            }
+            '#' if self.peek_at(1) == Some('{') => {
+                self.pos += 2;
+                self.map()
+            }
+            '<' if self.peek_at(1) == Some('<') => {
+                self.pos += 2;
+                self.skip_blank();
+                let ret = if self.peek() == Some('"') {
+                    self.quoted('"')?
+                } else {
+                    String::new()
+                };
+                // Like `<<"text"/utf8>>`
+                while self.peek().is_some_and(|c| c != '>') {
+                    self.pos += 1;
+                }
+                self.expect('>')?;
+                self.expect('>')?;
+                Ok(Term::Str(ret))
+            }
+            '"' => Ok(Term::Str(self.quoted('"')?)),
+            '\'' if self.dialect == Dialect::Erlang => Ok(Term::Atom(self.quoted('\'')?)),
+            '\'' => Ok(Term::Str(self.quoted('\'')?)),
+            ':' if self.dialect == Dialect::Elixir => {
+                self.pos += 1;
+                match self.peek() {
+                    Some('"') => Ok(Term::Atom(self.quoted('"')?)),
+                    _ => Ok(Term::Atom(self.ident())),
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = self.pos;
+                self.pos += 1;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+                {
+                    self.pos += 1;
+                }
+                Ok(Term::Number(self.chars[start..self.pos].iter().collect()))
+            }
+            c if is_ident(c) => {
+                let ident = self.ident();
+                // Elixir aliases like `Mix.Project` are not terms
+                if self.peek() == Some('.') && self.peek_at(1).is_some_and(is_ident) {
+                    return Err(invalid());
+                }
+                Ok(Term::Atom(ident))
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Parses the terms of an Erlang file, each ending with a dot.
+pub fn parse_consult(content: &str) -> Result<Vec<Term>, SourcePkgError> {
+    let mut parser = Parser {
+        chars: content.chars().collect(),
+        pos: 0,
+        dialect: Dialect::Erlang,
+    };
+
+    let mut ret = vec![];
+    loop {
+        parser.skip_blank();
+        if parser.peek().is_none() {
+            return Ok(ret);
+        }
+        ret.push(parser.value()?);
+        parser.expect('.')?;
+    }
+}
+
+/// Parses a single term.
+pub fn parse_term(content: &str, dialect: Dialect) -> Result<Term, SourcePkgError> {
+    let mut parser = Parser {
+        chars: content.chars().collect(),
+        pos: 0,
+        dialect,
+    };
+    parser.value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn terms() {
+        let terms = parse_consult(
+            "% comment\n{deps, [cowboy, {jsx, \"3.1.0\"}, {'my dep', <<\"x\"/utf8>>}]}.\n[].",
+        )
+        .unwrap();
+        assert_eq!(
+            terms,
+            vec![
+                Term::Tuple(vec![
+                    Term::Atom("deps".into()),
+                    Term::List(vec![
+                        Term::Atom("cowboy".into()),
+                        Term::Tuple(vec![Term::Atom("jsx".into()), Term::Str("3.1.0".into())]),
+                        Term::Tuple(vec![Term::Atom("my dep".into()), Term::Str("x".into())]),
+                    ])
+                ]),
+                Term::List(vec![]),
+            ]
+        );
+
+        let term = parse_term(
+            "%{\"jason\": {:hex, :jason, \"1.4.1\", [hex: :jason, optional: false]}}",
+            Dialect::Elixir,
+        )
+        .unwrap();
+        assert_eq!(
+            term,
+            Term::Map(vec![(
+                Term::Atom("jason".into()),
+                Term::Tuple(vec![
+                    Term::Atom("hex".into()),
+                    Term::Atom("jason".into()),
+                    Term::Str("1.4.1".into()),
+                    Term::List(vec![
+                        Term::Tuple(vec![Term::Atom("hex".into()), Term::Atom("jason".into())]),
+                        Term::Tuple(vec![
+                            Term::Atom("optional".into()),
+                            Term::Atom("false".into())
+                        ]),
+                    ]),
+                ])
+            )])
+        );
+    }
+}
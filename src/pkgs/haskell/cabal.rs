@@ -0,0 +1,357 @@
+//! Cabal package descriptions (`*.cabal`) and the versions pinned by
+//! `cabal freeze` (`cabal.project.freeze`).
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::{field, parse_dependency, parse_items, split_list, Item};
+
+/// Components of a package with dependencies, and whether their dependencies
+/// are needed at runtime.
+const COMPONENTS: &[(&str, bool)] = &[
+    ("library", true),
+    ("executable", true),
+    ("foreign-library", true),
+    ("test-suite", false),
+    ("benchmark", false),
+    ("custom-setup", false),
+];
+
+/// A dependency of a component, optional when under a conditional.
+struct Declared<'a> {
+    name: &'a str,
+    range: String,
+    is_optional: bool,
+}
+
+/// Collects the dependencies of the items of a component, the `if` and
+/// `else` blocks are flattened into it and the `import`ed common stanzas
+/// expanded.
+fn collect<'a>(
+    items: &'a [Item],
+    commons: &HashMap<String, &'a [Item]>,
+    conditional: bool,
+    seen: &mut HashSet<String>,
+    out: &mut Vec<Declared<'a>>,
+) {
+    for item in items {
+        match item {
+            Item::Field(name, value) if name == "build-depends" || name == "setup-depends" => {
+                for spec in split_list(value) {
+                    if let Some((name, range)) = parse_dependency(spec) {
+                        out.push(Declared {
+                            name,
+                            range,
+                            is_optional: conditional,
+                        });
+                    }
+                }
+            }
+            Item::Field(name, value) if name == "import" => {
+                for common in split_list(value) {
+                    let common = common.to_lowercase();
+                    if !seen.insert(common.clone()) {
+                        continue;
+                    }
+                    match commons.get(&common) {
+                        Some(items) => collect(items, commons, conditional, seen, out),
+                        None => log::warn!("Unknown common stanza {}", common),
+                    }
+                }
+            }
+            Item::Section(header, children) => {
+                let keyword = header.split_whitespace().next().unwrap_or_default();
+                if keyword.eq_ignore_ascii_case("if") || keyword.eq_ignore_ascii_case("else") {
+                    collect(children, commons, true, seen, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parses a package description, the dependencies of each component being
+/// in a scope like `library` or `test-suite:spec`. The versions pinned by
+/// `freeze` resolve them, and the other pins are indirect dependencies.
+pub fn parse(content: &str, freeze: Option<&[(String, String)]>) -> Package {
+    let items = parse_items(content);
+    let name = field(&items, "name").unwrap_or_default().to_string();
+
+    let commons = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Section(header, children) => {
+                let (keyword, arg) = header.split_once(char::is_whitespace)?;
+                keyword
+                    .eq_ignore_ascii_case("common")
+                    .then(|| (arg.trim().to_lowercase(), children.as_slice()))
+            }
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+
+    // The package itself and its sublibraries
+    let mut internal = HashSet::from([name.clone()]);
+    for item in &items {
+        if let Item::Section(header, _) = item {
+            let mut words = header.split_whitespace();
+            if words
+                .next()
+                .is_some_and(|w| w.eq_ignore_ascii_case("library"))
+            {
+                internal.extend(words.next().map(str::to_string));
+            }
+        }
+    }
+
+    let pins = freeze
+        .unwrap_or_default()
+        .iter()
+        .map(|(name, version)| (name.as_str(), version.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    let mut dependencies = vec![];
+    let mut direct = HashSet::new();
+    for item in &items {
+        let (header, children) = match item {
+            Item::Section(header, children) => (header, children),
+            _ => continue,
+        };
+        let mut words = header.split_whitespace();
+        let keyword = words.next().unwrap_or_default().to_lowercase();
+        let is_runtime = match COMPONENTS.iter().find(|(c, _)| *c == keyword) {
+            Some((_, is_runtime)) => *is_runtime,
+            None => continue,
+        };
+        let scope = match words.next() {
+            Some(component) => format!("{}:{}", keyword, component),
+            None => keyword,
+        };
+
+        let mut declared = vec![];
+        collect(
+            children,
+            &commons,
+            false,
+            &mut HashSet::new(),
+            &mut declared,
+        );
+
+        let mut seen = HashSet::new();
+        for dep in declared {
+            if internal.contains(dep.name) || !seen.insert(dep.name) {
+                continue;
+            }
+            let version = pins
+                .get(dep.name)
+                .copied()
+                .or_else(|| super::exact_version(&dep.range));
+            let purl = match super::make_purl(dep.name, version) {
+                Some(purl) => purl,
+                None => continue,
+            };
+            let is_resolved = version.is_some();
+            direct.insert(dep.name);
+            dependencies.push(DependentPackage {
+                purl: purl.to_string(),
+                requirement: dep.range,
+                scope: scope.clone(),
+                is_runtime,
+                is_optional: dep.is_optional,
+                is_resolved,
+                relation: maplit::hashset! {Relation::Direct},
+                ..Default::default()
+            });
+        }
+    }
+
+    for (pin, version) in freeze.unwrap_or_default() {
+        if direct.contains(pin.as_str()) || internal.contains(pin) {
+            continue;
+        }
+        if let Some(mut dep) = super::resolved(pin, version) {
+            dep.relation.insert(Relation::Indirect);
+            dependencies.push(dep);
+        }
+    }
+
+    Package {
+        name,
+        version: field(&items, "version").unwrap_or_default().to_string(),
+        primary_language: "Haskell".into(),
+        declared_license: field(&items, "license").unwrap_or_default().to_string(),
+        dependencies,
+        ..Default::default()
+    }
+}
+
+/// The packages and versions pinned by the `constraints` of a freeze file,
+/// like `any.aeson ==2.1.2.1`. Flags and installed packages are not pinned.
+pub fn parse_freeze(content: &str) -> Vec<(String, String)> {
+    let items = parse_items(content);
+    let constraints = field(&items, "constraints").unwrap_or_default();
+
+    let mut ret = vec![];
+    for constraint in split_list(constraints) {
+        let (target, range) = match constraint.split_once(char::is_whitespace) {
+            Some((target, range)) => (target, range.trim()),
+            None => continue,
+        };
+        // Constraints qualified otherwise apply to setup scripts only
+        let name = match target.split_once('.') {
+            Some(("any", name)) => name,
+            Some(_) => continue,
+            None => target,
+        };
+        if let Some(version) = super::exact_version(range) {
+            ret.push((name.to_string(), version.to_string()));
+        }
+    }
+    ret
+}
+
+fn read_freeze(path: &Path) -> Option<Vec<(String, String)>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(parse_freeze(&content))
+}
+
+pub struct Cabal {}
+
+impl Cabal {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Cabal {
+    fn get_name(&self) -> String {
+        "cabal".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let freeze = read_freeze(&path.with_file_name("cabal.project.freeze"));
+        Ok(parse(&content, freeze.as_deref()))
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["*.cabal"]
+    }
+}
+
+pub struct CabalFreeze {}
+
+impl CabalFreeze {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for CabalFreeze {
+    fn get_name(&self) -> String {
+        "cabal".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "cabal-freeze".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let dir = path.parent().ok_or(SourcePkgError::NotSupported)?;
+        let has_cabal = std::fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .any(|e| e.path().extension().is_some_and(|ext| ext == "cabal"));
+        if has_cabal {
+            // Already covered by the package description
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Haskell".into(),
+            dependencies: parse_freeze(&content)
+                .iter()
+                .filter_map(|(name, version)| super::resolved(name, version))
+                .collect(),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["cabal.project.freeze"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cabal() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/haskell/cabal/app.cabal"
+        ));
+
+        let package = Cabal::new().recognize(filepath).await.unwrap();
+        assert_eq!(package.name, "app");
+        assert_eq!(package.version, "0.1.0.0");
+        assert_eq!(package.declared_license, "BSD-3-Clause");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| ((d.purl.as_str(), d.scope.as_str()), d))
+            .collect::<HashMap<_, _>>();
+
+        let aeson = deps[&("pkg:hackage/aeson@2.1.2.1", "library")];
+        assert_eq!(aeson.requirement, ">=2.0 && <2.3");
+        assert!(aeson.is_resolved && aeson.is_runtime && !aeson.is_optional);
+        // From the common stanza
+        assert!(deps.contains_key(&("pkg:hackage/base@4.18.0.0", "library")));
+        assert!(deps.contains_key(&("pkg:hackage/base@4.18.0.0", "executable:app")));
+        // Under a conditional
+        assert!(deps[&("pkg:hackage/Win32", "library")].is_optional);
+        assert!(deps[&("pkg:hackage/unix@2.8.1.0", "library")].is_optional);
+        // `servant:server`
+        assert!(deps.contains_key(&("pkg:hackage/servant@0.20.1", "library")));
+
+        let hspec = deps[&("pkg:hackage/hspec@2.11.7", "test-suite:spec")];
+        assert!(!hspec.is_runtime);
+        assert_eq!(hspec.requirement, "==2.11.7");
+
+        // The package and its sublibrary
+        assert!(!package
+            .dependencies
+            .iter()
+            .any(|d| d.purl.starts_with("pkg:hackage/app")));
+
+        let attoparsec = deps[&("pkg:hackage/attoparsec@0.14.4", "")];
+        assert_eq!(attoparsec.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(
+            deps[&("pkg:hackage/text@2.0.2", "library")].relation,
+            maplit::hashset! {Relation::Direct}
+        );
+        // Flags and installed packages
+        assert_eq!(package.dependencies.len(), 12);
+    }
+
+    #[tokio::test]
+    async fn freeze_alongside_cabal() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/haskell/cabal/cabal.project.freeze"
+        ));
+
+        assert!(matches!(
+            CabalFreeze::new().recognize(filepath).await,
+            Err(SourcePkgError::Skipped)
+        ));
+    }
+}
@@ -0,0 +1,216 @@
+//! Haskell packages from Hackage, declared by Cabal and Stack projects.
+
+use lazy_static::lazy_static;
+use packageurl::PackageUrl;
+use regex::Regex;
+
+use crate::types::DependentPackage;
+
+pub mod cabal;
+pub mod stack;
+
+lazy_static! {
+    /// `aeson`, `aeson:{aeson, attoparsec-aeson}` or `servant:server`,
+    /// followed by the version range.
+    static ref DEPENDENCY: Regex = Regex::new(
+        r"^([A-Za-z0-9][A-Za-z0-9-]*)(?::(?:\{[^}]*\}|[A-Za-z0-9-]+))?\s*(.*)$"
+    )
+    .unwrap();
+    /// `acme-missiles-0.3`, the version being the last dash-separated part.
+    static ref PACKAGE_ID: Regex = Regex::new(r"^(.+)-(\d+(?:\.\d+)*)$").unwrap();
+}
+
+/// An item of a file in the Cabal format: a `field: value`, with the lines
+/// continuing the value, or a section like `library` or `if flag(dev)`, with
+/// the items indented under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    Field(String, String),
+    Section(String, Vec<Item>),
+}
+
+/// Parses the items of a file in the Cabal format, as used by `.cabal` and
+/// `cabal.project` files. Field names are lowercase.
+pub fn parse_items(content: &str) -> Vec<Item> {
+    let lines = content
+        .lines()
+        .filter(|l| {
+            let t = l.trim();
+            !t.is_empty() && !t.starts_with("--")
+        })
+        .map(|l| (l.len() - l.trim_start().len(), l.trim()))
+        .collect::<Vec<_>>();
+
+    let mut pos = 0;
+    let mut ret = vec![];
+    while pos < lines.len() {
+        ret.extend(parse_block(&lines, &mut pos));
+    }
+    ret
+}
+
+fn parse_block(lines: &[(usize, &str)], pos: &mut usize) -> Vec<Item> {
+    lazy_static! {
+        static ref FIELD: Regex = Regex::new(r"^([A-Za-z][A-Za-z0-9_-]*)\s*:(.*)$").unwrap();
+    }
+
+    let indent = lines[*pos].0;
+    let mut ret = vec![];
+
+    while *pos < lines.len() && lines[*pos].0 >= indent {
+        let (_, text) = lines[*pos];
+        *pos += 1;
+
+        if let Some(caps) = FIELD.captures(text) {
+            let mut value = caps[2].trim().to_string();
+            while *pos < lines.len() && lines[*pos].0 > indent {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(lines[*pos].1);
+                *pos += 1;
+            }
+            ret.push(Item::Field(caps[1].to_lowercase(), value));
+        } else {
+            let children = if *pos < lines.len() && lines[*pos].0 > indent {
+                parse_block(lines, pos)
+            } else {
+                vec![]
+            };
+            ret.push(Item::Section(text.to_string(), children));
+        }
+    }
+
+    ret
+}
+
+/// The value of the first field `name` of `items`.
+pub fn field<'a>(items: &'a [Item], name: &str) -> Option<&'a str> {
+    items.iter().find_map(|item| match item {
+        Item::Field(n, v) if n == name => Some(v.as_str()),
+        _ => None,
+    })
+}
+
+/// Splits a comma-separated list, not within braces like
+/// `pkg:{lib1, lib2}`.
+pub fn split_list(value: &str) -> Vec<&str> {
+    let mut ret = vec![];
+    let mut depth = 0i32;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                ret.push(value[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    ret.push(value[start..].trim());
+    ret.retain(|s| !s.is_empty());
+    ret
+}
+
+/// The package name and version range of a `build-depends` entry.
+pub fn parse_dependency(spec: &str) -> Option<(&str, String)> {
+    let caps = DEPENDENCY.captures(spec.trim())?;
+    let range = caps.get(2).map_or("", |m| m.as_str());
+    // `text >= 1.2 && < 2.1` spanning lines
+    let range = range.split_whitespace().collect::<Vec<_>>().join(" ");
+    Some((caps.get(1)?.as_str(), range))
+}
+
+/// Splits a package identifier like `acme-missiles-0.3` into its name and
+/// version.
+pub fn split_package_id(id: &str) -> Option<(&str, &str)> {
+    let caps = PACKAGE_ID.captures(id)?;
+    Some((caps.get(1)?.as_str(), caps.get(2)?.as_str()))
+}
+
+/// The exact version of a range like `==1.2.3`.
+pub fn exact_version(range: &str) -> Option<&str> {
+    let version = range.strip_prefix("==")?.trim();
+    (!version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        .then_some(version)
+}
+
+pub fn make_purl(name: &str, version: Option<&str>) -> Option<PackageUrl<'static>> {
+    let mut purl = PackageUrl::new("hackage", name.to_string()).ok()?;
+    if let Some(version) = version {
+        purl.with_version(version.to_string());
+    }
+    Some(purl)
+}
+
+/// A resolved dependency on `name` at `version`.
+pub fn resolved(name: &str, version: &str) -> Option<DependentPackage> {
+    Some(DependentPackage {
+        purl: make_purl(name, Some(version))?.to_string(),
+        requirement: version.to_string(),
+        is_resolved: true,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items() {
+        let content = "\
+name: app
+-- a comment
+build-depends:
+    base,
+    text
+
+library
+  build-depends: base >=4 && <5,
+                 containers
+  if flag(dev)
+    build-depends: pretty-simple
+  else
+    ghc-options: -O2
+";
+
+        let items = parse_items(content);
+        assert_eq!(
+            items,
+            vec![
+                Item::Field("name".into(), "app".into()),
+                Item::Field("build-depends".into(), "base,\ntext".into()),
+                Item::Section(
+                    "library".into(),
+                    vec![
+                        Item::Field("build-depends".into(), "base >=4 && <5,\ncontainers".into()),
+                        Item::Section(
+                            "if flag(dev)".into(),
+                            vec![Item::Field("build-depends".into(), "pretty-simple".into())]
+                        ),
+                        Item::Section(
+                            "else".into(),
+                            vec![Item::Field("ghc-options".into(), "-O2".into())]
+                        ),
+                    ]
+                ),
+            ]
+        );
+
+        assert_eq!(
+            split_list("aeson:{aeson, attoparsec-aeson} >=2, text"),
+            vec!["aeson:{aeson, attoparsec-aeson} >=2", "text"]
+        );
+        assert_eq!(
+            parse_dependency("servant:server ^>=0.20"),
+            Some(("servant", "^>=0.20".to_string()))
+        );
+        assert_eq!(
+            split_package_id("acme-missiles-0.3"),
+            Some(("acme-missiles", "0.3"))
+        );
+    }
+}
@@ -0,0 +1,287 @@
+//! Stack projects: the `extra-deps` of `stack.yaml`, completed in
+//! `stack.yaml.lock`. The other packages come from the snapshot of the
+//! project and are not listed.
+
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package};
+use crate::PackageManifest;
+
+use super::split_package_id;
+
+/// The name of a package in a git repository, its last path segment.
+fn repository_name(url: &str) -> Option<&str> {
+    let name = url.trim_end_matches('/').rsplit(['/', ':']).next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    (!name.is_empty()).then_some(name)
+}
+
+/// Adds the `vcs_url` qualifier of a package from a git repository.
+fn from_git(
+    name: &str,
+    version: Option<&str>,
+    git: &str,
+    commit: Option<&str>,
+) -> Option<DependentPackage> {
+    let mut purl = super::make_purl(name, version)?;
+    let vcs_url = match commit {
+        Some(commit) => format!("git+{}@{}", git, commit),
+        None => format!("git+{}", git),
+    };
+    purl.add_qualifier("vcs_url", vcs_url).ok()?;
+    Some(DependentPackage {
+        purl: purl.to_string(),
+        requirement: commit.or(version).unwrap_or_default().to_string(),
+        is_resolved: commit.is_some(),
+        ..Default::default()
+    })
+}
+
+/// A package from Hackage, like `acme-missiles-0.3@sha256:<hash>,613` or
+/// `acme-missiles-0.3@rev:1`.
+fn from_hackage(spec: &str) -> Option<DependentPackage> {
+    let id = spec.split('@').next()?.trim();
+    let (name, version) = split_package_id(id)?;
+    super::resolved(name, version)
+}
+
+/// The `extra-deps` of a `stack.yaml`. Local packages are skipped, and the
+/// packages of a repository are named after it, or its `subdirs`.
+pub fn parse_stack_yaml(root: &Value) -> Vec<DependentPackage> {
+    let extra_deps = match root.get("extra-deps") {
+        Some(Value::Sequence(deps)) => deps,
+        _ => return vec![],
+    };
+
+    let mut ret = vec![];
+    for dep in extra_deps {
+        match dep {
+            Value::String(spec) if !spec.contains('/') => ret.extend(from_hackage(spec)),
+            Value::Mapping(_) => {
+                let git = match (dep.get("git"), dep.get("github")) {
+                    (Some(Value::String(git)), _) => git.clone(),
+                    (_, Some(Value::String(github))) => format!("https://github.com/{}", github),
+                    _ => {
+                        if let Some(Value::String(hackage)) = dep.get("hackage") {
+                            ret.extend(from_hackage(hackage));
+                        }
+                        continue;
+                    }
+                };
+                let commit = dep.get("commit").and_then(Value::as_str);
+
+                let names = match dep.get("subdirs") {
+                    Some(Value::Sequence(subdirs)) => subdirs
+                        .iter()
+                        .filter_map(|s| s.as_str().and_then(repository_name))
+                        .collect(),
+                    _ => repository_name(&git).into_iter().collect::<Vec<_>>(),
+                };
+                for name in names {
+                    ret.extend(from_git(name, None, &git, commit));
+                }
+            }
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// The completed packages of a `stack.yaml.lock`, from Hackage, git
+/// repositories or archives.
+pub fn parse_lock(root: &Value) -> Vec<DependentPackage> {
+    let packages = match root.get("packages") {
+        Some(Value::Sequence(packages)) => packages,
+        _ => return vec![],
+    };
+
+    let mut ret = vec![];
+    for package in packages {
+        let completed = match package.get("completed") {
+            Some(completed) => completed,
+            None => continue,
+        };
+        if let Some(hackage) = completed.get("hackage").and_then(Value::as_str) {
+            ret.extend(from_hackage(hackage));
+            continue;
+        }
+
+        let name = completed.get("name").and_then(Value::as_str);
+        let version = completed.get("version").and_then(|v| match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        });
+        let name = match name {
+            Some(name) => name,
+            None => continue,
+        };
+
+        if let Some(git) = completed.get("git").and_then(Value::as_str) {
+            let commit = completed.get("commit").and_then(Value::as_str);
+            let mut dep = match from_git(name, version.as_deref(), git, commit) {
+                Some(dep) => dep,
+                None => continue,
+            };
+            dep.requirement = version.unwrap_or_default();
+            ret.push(dep);
+        } else if let Some(url) = completed.get("url").and_then(Value::as_str) {
+            let mut purl = match super::make_purl(name, version.as_deref()) {
+                Some(purl) => purl,
+                None => continue,
+            };
+            if purl.add_qualifier("download_url", url.to_string()).is_err() {
+                continue;
+            }
+            ret.push(DependentPackage {
+                purl: purl.to_string(),
+                requirement: version.unwrap_or_default(),
+                is_resolved: true,
+                ..Default::default()
+            });
+        }
+    }
+    ret
+}
+
+fn read_yaml(path: &Path) -> Result<Value, SourcePkgError> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_yaml::from_reader(file)?)
+}
+
+pub struct StackYaml {}
+
+impl StackYaml {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for StackYaml {
+    fn get_name(&self) -> String {
+        "stack".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        if path.with_file_name("stack.yaml.lock").exists() {
+            // Already covered by the lock file
+            return Err(SourcePkgError::Skipped);
+        }
+
+        Ok(Package {
+            primary_language: "Haskell".into(),
+            dependencies: parse_stack_yaml(&read_yaml(path)?),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["stack.yaml"]
+    }
+}
+
+pub struct StackLock {}
+
+impl StackLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for StackLock {
+    fn get_name(&self) -> String {
+        "stack".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "stack-lock".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        Ok(Package {
+            primary_language: "Haskell".into(),
+            dependencies: parse_lock(&read_yaml(path)?),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["stack.yaml.lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stack_lock() {
+        let dir = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/haskell/stack"
+        ));
+
+        assert!(matches!(
+            StackYaml::new().recognize(&dir.join("stack.yaml")).await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let package = StackLock::new()
+            .recognize(&dir.join("stack.yaml.lock"))
+            .await
+            .unwrap();
+        let purls = package
+            .dependencies
+            .iter()
+            .map(|d| d.purl.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            purls,
+            vec![
+                "pkg:hackage/acme-missiles@0.3",
+                "pkg:hackage/servant@0.20.1?vcs_url=git+https://github.com/haskell-servant/servant.git%40e3e5d2b23057c2c3409e5e210b613527baf3b77d",
+                "pkg:hackage/fast-logger@3.2.2?download_url=https://hackage.haskell.org/package/fast-logger-3.2.2/fast-logger-3.2.2.tar.gz",
+            ]
+        );
+        assert!(package.dependencies.iter().all(|d| d.is_resolved));
+    }
+
+    #[tokio::test]
+    async fn stack_yaml() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/haskell/stack-extra-deps/stack.yaml"
+        ));
+
+        let package = StackYaml::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d.requirement.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deps,
+            vec![
+                ("pkg:hackage/acme-missiles@0.3", "0.3"),
+                ("pkg:hackage/text-short@0.1.5", "0.1.5"),
+                (
+                    "pkg:hackage/servant?vcs_url=git+https://github.com/haskell-servant/servant%40e3e5d2b23057c2c3409e5e210b613527baf3b77d",
+                    "e3e5d2b23057c2c3409e5e210b613527baf3b77d"
+                ),
+                (
+                    "pkg:hackage/servant-server?vcs_url=git+https://github.com/haskell-servant/servant%40e3e5d2b23057c2c3409e5e210b613527baf3b77d",
+                    "e3e5d2b23057c2c3409e5e210b613527baf3b77d"
+                ),
+                (
+                    "pkg:hackage/wai-extra?vcs_url=git+https://github.com/yesodweb/wai.git%4082a0a8f0d0fa7be6c3236f3e1e6fd3e6e0b4c2b1",
+                    "82a0a8f0d0fa7be6c3236f3e1e6fd3e6e0b4c2b1"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! Clojure CLI projects: the `:deps` of `deps.edn`, and the deps of its
+//! `:aliases`, in the scope of the alias.
+
+use std::path::Path;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{Package, PackageManifest};
+use crate::types::DependentPackage;
+
+use super::edn::{self, Edn};
+
+/// Splits a lib like `org.clojure/clojure` into its group and artifact, the
+/// group of a lib without one being its name.
+pub fn split_lib(lib: &str) -> (&str, &str) {
+    // Classifiers like `org.lwjgl/lwjgl$natives-linux`
+    let lib = lib.split('$').next().unwrap_or(lib);
+    lib.split_once('/').unwrap_or((lib, lib))
+}
+
+/// The git repository of a lib without `:git/url`, from its group like
+/// `io.github.user/repo`.
+fn inferred_git_url(group: &str, artifact: &str) -> Option<String> {
+    let (host, user) = group
+        .strip_prefix("io.github.")
+        .or_else(|| group.strip_prefix("com.github."))
+        .map(|user| ("github.com", user))
+        .or_else(|| {
+            group
+                .strip_prefix("io.gitlab.")
+                .or_else(|| group.strip_prefix("com.gitlab."))
+                .map(|user| ("gitlab.com", user))
+        })?;
+    Some(format!("https://{}/{}/{}.git", host, user, artifact))
+}
+
+/// A dependency on `lib` with the coordinate `coord`: a Maven version, a git
+/// revision or a local directory.
+fn parse_coord(lib: &str, coord: &Edn, scope: &str) -> Option<DependentPackage> {
+    let (group, artifact) = split_lib(lib);
+    let string = |key: &str| coord.get(key).and_then(Edn::as_str);
+
+    if let Some(version) = string("mvn/version") {
+        return super::declared(group, artifact, version, scope, None);
+    }
+
+    let sha = string("git/sha").or_else(|| string("sha"));
+    let tag = string("git/tag").or_else(|| string("tag"));
+    if sha.is_some() || tag.is_some() {
+        let url = string("git/url")
+            .map(str::to_string)
+            .or_else(|| inferred_git_url(group, artifact))?;
+        let rev = sha.or(tag).unwrap_or_default();
+        let vcs_url = format!("git+{}@{}", url, rev);
+        let version = tag.or(sha).unwrap_or_default();
+        let mut dep = super::declared(group, artifact, version, scope, Some(vcs_url))?;
+        dep.is_resolved = sha.is_some();
+        return Some(dep);
+    }
+
+    if coord.get("local/root").is_some() {
+        return super::declared(group, artifact, "", scope, None);
+    }
+
+    log::warn!("Unknown coordinate of {}", lib);
+    None
+}
+
+fn parse_deps(deps: Option<&Edn>, scope: &str) -> Vec<DependentPackage> {
+    deps.and_then(Edn::as_map)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|(lib, coord)| parse_coord(lib.as_symbol()?, coord, scope))
+        .collect()
+}
+
+/// Reads the dependencies of a `deps.edn`, those added by the aliases in
+/// `:extra-deps`, `:replace-deps` or `:deps` being in the scope of the alias.
+pub fn parse(content: &str) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let root = edn::parse(content)?
+        .into_iter()
+        .next()
+        .ok_or(SourcePkgError::GenericsError("Empty deps.edn"))?;
+
+    let mut ret = parse_deps(root.get("deps"), "compile");
+
+    let aliases = root
+        .get("aliases")
+        .and_then(Edn::as_map)
+        .unwrap_or_default();
+    for (alias, config) in aliases {
+        let alias = match alias.as_keyword() {
+            Some(alias) => alias,
+            None => continue,
+        };
+        for key in ["extra-deps", "replace-deps", "deps"] {
+            ret.extend(parse_deps(config.get(key), alias));
+        }
+    }
+
+    Ok(ret)
+}
+
+pub struct DepsEdn {}
+
+impl DepsEdn {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for DepsEdn {
+    fn get_name(&self) -> String {
+        "maven".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "deps-edn".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Clojure".into(),
+            dependencies: parse(&content)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["deps.edn"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn deps_edn() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/clojure/deps.edn"
+        ));
+
+        let package = DepsEdn::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 6);
+
+        let clojure = deps["pkg:maven/org.clojure/clojure"];
+        assert_eq!(clojure.requirement, "1.11.1");
+        assert_eq!(clojure.scope, "compile");
+        assert!(clojure.is_runtime);
+        assert_eq!(deps["pkg:maven/clj-time/clj-time"].requirement, "0.15.2");
+
+        let git = deps["pkg:maven/io.github.seancorfield/honeysql?vcs_url=git+https://github.com/seancorfield/honeysql.git%40b3e7d2a"];
+        assert_eq!(git.requirement, "v2.5.1103");
+        assert!(git.is_resolved);
+        assert!(!deps["pkg:maven/my/local-lib"].is_resolved);
+
+        let kaocha = deps["pkg:maven/lambdaisland/kaocha"];
+        assert_eq!(kaocha.scope, "test");
+        assert!(!kaocha.is_runtime);
+        assert_eq!(
+            deps["pkg:maven/io.github.clojure/tools.build?vcs_url=git+https://github.com/clojure/tools.build.git%408e78bcc"].scope,
+            "build"
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! A reader of the EDN data of `deps.edn`, and of Clojure forms like
+//! `project.clj`, as data: reader macros like quotes or metadata are
+//! dropped, and tagged literals are read as their value.
+
+use crate::error::SourcePkgError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edn {
+    Nil,
+    Bool(bool),
+    Str(String),
+    /// A keyword, without its leading colon.
+    Keyword(String),
+    Symbol(String),
+    Number(String),
+    List(Vec<Edn>),
+    Vector(Vec<Edn>),
+    Set(Vec<Edn>),
+    Map(Vec<(Edn, Edn)>),
+}
+
+impl Edn {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Edn::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_symbol(&self) -> Option<&str> {
+        match self {
+            Edn::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_keyword(&self) -> Option<&str> {
+        match self {
+            Edn::Keyword(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The items of a list or vector.
+    pub fn as_seq(&self) -> Option<&[Edn]> {
+        match self {
+            Edn::List(items) | Edn::Vector(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&[(Edn, Edn)]> {
+        match self {
+            Edn::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// The value of the keyword `key` of a map.
+    pub fn get(&self, key: &str) -> Option<&Edn> {
+        self.as_map()?
+            .iter()
+            .find_map(|(k, v)| (k.as_keyword() == Some(key)).then_some(v))
+    }
+}
+
+fn invalid() -> SourcePkgError {
+    SourcePkgError::GenericsError("Invalid EDN")
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || c == ',' || "()[]{}\";".contains(c)
+}
+
+struct Reader {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Reader {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_blank(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == ',' {
+                self.pos += 1;
+            } else if c == ';' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !is_delimiter(c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn string(&mut self) -> Result<String, SourcePkgError> {
+        self.pos += 1;
+        let mut ret = String::new();
+        loop {
+            match self.peek().ok_or_else(invalid)? {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(ret);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let c = self.peek().ok_or_else(invalid)?;
+                    ret.push(match c {
+                        'n' => '\n',
+                        't' => '\t',
+                        c => c,
+                    });
+                    self.pos += 1;
+                }
+                c => {
+                    ret.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    /// The forms up to `close`.
+    fn forms(&mut self, close: char) -> Result<Vec<Edn>, SourcePkgError> {
+        self.pos += 1;
+        let mut ret = vec![];
+        loop {
+            self.skip_blank();
+            match self.peek().ok_or_else(invalid)? {
+                c if c == close => {
+                    self.pos += 1;
+                    return Ok(ret);
+                }
+                _ => {
+                    if let Some(form) = self.form()? {
+                        ret.push(form);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The next form, none for a discarded one like `#_form`.
+    fn form(&mut self) -> Result<Option<Edn>, SourcePkgError> {
+        self.skip_blank();
+        let c = self.peek().ok_or_else(invalid)?;
+        let form = match c {
+            '(' => Edn::List(self.forms(')')?),
+            '[' => Edn::Vector(self.forms(']')?),
+            '{' => {
+                let items = self.forms('}')?;
+                let mut entries = vec![];
+                let mut items = items.into_iter();
+                while let (Some(k), Some(v)) = (items.next(), items.next()) {
+                    entries.push((k, v));
+                }
+                Edn::Map(entries)
+            }
+            '"' => Edn::Str(self.string()?),
+            '\\' => {
+                self.pos += 1;
+                let c = self.peek().ok_or_else(invalid)?;
+                self.pos += 1;
+                let rest = self.token();
+                Edn::Str(format!("{}{}", c, rest))
+            }
+            // Quotes, syntax quotes and unquotes, derefs and metadata
+            '\'' | '`' | '~' | '@' | '^' => {
+                self.pos += 1;
+                if c == '~' && self.peek() == Some('@') {
+                    self.pos += 1;
+                }
+                if c == '^' {
+                    self.form()?;
+                }
+                return self.form();
+            }
+            '#' => {
+                self.pos += 1;
+                match self.peek().ok_or_else(invalid)? {
+                    '{' => Edn::Set(self.forms('}')?),
+                    '_' => {
+                        self.pos += 1;
+                        self.form()?;
+                        return Ok(None);
+                    }
+                    '"' => Edn::Str(self.string()?),
+                    '(' | '\'' => return self.form(),
+                    // Namespaced maps like `#:mvn{:version "1.0"}`
+                    ':' => {
+                        self.token();
+                        return self.form();
+                    }
+                    // Tagged literals like `#inst "..."`
+                    _ => {
+                        self.token();
+                        return self.form();
+                    }
+                }
+            }
+            ':' => {
+                self.pos += 1;
+                Edn::Keyword(self.token())
+            }
+            _ => {
+                let token = self.token();
+                if token.is_empty() {
+                    return Err(invalid());
+                }
+                match token.as_str() {
+                    "nil" => Edn::Nil,
+                    "true" => Edn::Bool(true),
+                    "false" => Edn::Bool(false),
+                    t if t.starts_with(|c: char| c.is_ascii_digit())
+                        || (t.len() > 1
+                            && t.starts_with(['-', '+'])
+                            && t[1..].starts_with(|c: char| c.is_ascii_digit())) =>
+                    {
+                        Edn::Number(token)
+                    }
+                    _ => Edn::Symbol(token),
+                }
+            }
+        };
+        Ok(Some(form))
+    }
+}
+
+/// Reads the top-level forms of `content`.
+pub fn parse(content: &str) -> Result<Vec<Edn>, SourcePkgError> {
+    let mut reader = Reader {
+        chars: content.chars().collect(),
+        pos: 0,
+    };
+
+    let mut ret = vec![];
+    loop {
+        reader.skip_blank();
+        if reader.peek().is_none() {
+            return Ok(ret);
+        }
+        ret.extend(reader.form()?);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forms() {
+        let forms = parse(
+            "; comment\n{:deps {org.clojure/clojure {:mvn/version \"1.11.1\"}} #_ :ignored\n :tags #{:a}}\n(defproject app \"0.1.0\" :jvm-opts ^:replace [])",
+        )
+        .unwrap();
+        assert_eq!(
+            forms,
+            vec![
+                Edn::Map(vec![
+                    (
+                        Edn::Keyword("deps".into()),
+                        Edn::Map(vec![(
+                            Edn::Symbol("org.clojure/clojure".into()),
+                            Edn::Map(vec![(
+                                Edn::Keyword("mvn/version".into()),
+                                Edn::Str("1.11.1".into())
+                            )])
+                        )])
+                    ),
+                    (
+                        Edn::Keyword("tags".into()),
+                        Edn::Set(vec![Edn::Keyword("a".into())])
+                    ),
+                ]),
+                Edn::List(vec![
+                    Edn::Symbol("defproject".into()),
+                    Edn::Symbol("app".into()),
+                    Edn::Str("0.1.0".into()),
+                    Edn::Keyword("jvm-opts".into()),
+                    Edn::Vector(vec![]),
+                ]),
+            ]
+        );
+    }
+}
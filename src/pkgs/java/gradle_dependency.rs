@@ -9,21 +9,25 @@ use nom::{
     IResult,
 };
 use packageurl::PackageUrl;
-use crate::types::DependentPackage;
+use crate::types::{DependentPackage, Relation};
 
 use crate::error::SourcePkgError;
-use crate::pkgs::common::model::{Package, PackageManifest};
+use crate::pkgs::common::model::{Package, PackageManifest, RecognizeContext};
 
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
 
+use super::reachability;
+
 type NomResult<T, U> = IResult<T, U, VerboseError<T>>;
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Debug)]
 pub struct PackageDescriptor {
     pub name: String,
     pub version: String,
+    /// Declared by the project itself, at the top of the tree.
+    pub is_direct: bool,
 }
 
 // https://github.com/phylum-dev/cli/blob/ecd02687f694356f3c71630d40cff73b9e84084a/cli/tests/fixtures/gradle-dependencies.txt
@@ -53,6 +57,7 @@ fn filter_line(input: &str) -> NomResult<&str, &str> {
 }
 
 fn package(input: &str) -> Option<PackageDescriptor> {
+    let is_direct = input.starts_with("+--- ") || input.starts_with("\\--- ");
     let (_, input) = filter_line(input).ok()?;
     let (input, group_id) = group_id(input).ok()?;
     let (artifact_id, version) = artifact_id_version(input).ok()?;
@@ -60,6 +65,7 @@ fn package(input: &str) -> Option<PackageDescriptor> {
     Some(PackageDescriptor {
         name: format!("{}:{}", group_id, artifact_id),
         version: version.to_string(),
+        is_direct,
     })
 }
 
@@ -85,6 +91,11 @@ impl GradleDependencies {
                     .to_string(),
                 requirement: desc.version,
                 is_resolved: true,
+                relation: if desc.is_direct {
+                    maplit::hashset! {Relation::Direct}
+                } else {
+                    maplit::hashset! {Relation::Indirect}
+                },
                 ..Default::default()
             })
             .collect::<Vec<_>>();
@@ -104,8 +115,23 @@ impl PackageManifest for GradleDependencies {
         "maven".into()
     }
 
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let package = Self::parse_gradle_lock(path)?;
+        match path.parent() {
+            Some(root) => {
+                Ok(reachability::mark_reachability(root, &context.reachability, package).await)
+            }
+            None => Ok(package),
+        }
+    }
+
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        Self::parse_gradle_lock(path)
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
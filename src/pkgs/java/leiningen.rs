@@ -0,0 +1,160 @@
+//! Leiningen projects: the `:dependencies` and `:plugins` of the
+//! `defproject` of `project.clj`, and those of its `:profiles`, read as data.
+
+use std::path::Path;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{Package, PackageManifest};
+use crate::types::DependentPackage;
+
+use super::deps_edn::split_lib;
+use super::edn::{self, Edn};
+
+/// A dependency like `[ring/ring-core "1.10.0" :scope "test"]`, in `scope`
+/// unless it has its own.
+fn parse_dependency(dep: &Edn, scope: &str) -> Option<DependentPackage> {
+    let items = dep.as_seq()?;
+    let (group, artifact) = split_lib(items.first()?.as_symbol()?);
+    // Computed like `~clojure-version`
+    let version = items.get(1).and_then(Edn::as_str).unwrap_or_default();
+
+    let options = items.get(2..).unwrap_or_default();
+    let option = |key: &str| {
+        options.chunks(2).find_map(|kv| match kv {
+            [k, v] if k.as_keyword() == Some(key) => Some(v),
+            _ => None,
+        })
+    };
+    let scope = option("scope").and_then(Edn::as_str).unwrap_or(scope);
+
+    let mut ret = super::declared(group, artifact, version, scope, None)?;
+    ret.is_optional = option("optional") == Some(&Edn::Bool(true));
+    Some(ret)
+}
+
+fn parse_dependencies(deps: Option<&Edn>, scope: &str) -> Vec<DependentPackage> {
+    deps.and_then(Edn::as_seq)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|dep| parse_dependency(dep, scope))
+        .collect()
+}
+
+/// Reads the `defproject` of a `project.clj`, the dependencies of a profile
+/// being in the scope of its name.
+pub fn parse(content: &str) -> Result<Package, SourcePkgError> {
+    let forms = edn::parse(content)?;
+    let project = forms
+        .iter()
+        .filter_map(Edn::as_seq)
+        .find(|form| form.first().and_then(Edn::as_symbol) == Some("defproject"))
+        .ok_or(SourcePkgError::GenericsError(
+            "No defproject in project.clj",
+        ))?;
+
+    let name = project.get(1).and_then(Edn::as_symbol).unwrap_or_default();
+    let version = project.get(2).and_then(Edn::as_str).unwrap_or_default();
+    let options = Edn::Map(
+        project
+            .get(3..)
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(|kv| (kv[0].clone(), kv[1].clone()))
+            .collect(),
+    );
+
+    let mut dependencies = parse_dependencies(options.get("dependencies"), "compile");
+    dependencies.extend(parse_dependencies(options.get("plugins"), "plugin"));
+
+    let profiles = options
+        .get("profiles")
+        .and_then(Edn::as_map)
+        .unwrap_or_default();
+    for (profile, config) in profiles {
+        let profile = match profile.as_keyword() {
+            Some(profile) => profile,
+            None => continue,
+        };
+        dependencies.extend(parse_dependencies(config.get("dependencies"), profile));
+        dependencies.extend(parse_dependencies(config.get("plugins"), "plugin"));
+    }
+
+    let (group, artifact) = split_lib(name);
+    Ok(Package {
+        namespace: group.to_string(),
+        name: artifact.to_string(),
+        version: version.to_string(),
+        primary_language: "Clojure".into(),
+        dependencies,
+        ..Default::default()
+    })
+}
+
+pub struct Leiningen {}
+
+impl Leiningen {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Leiningen {
+    fn get_name(&self) -> String {
+        "maven".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "leiningen".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        parse(&content)
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["project.clj"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn project_clj() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/clojure/project.clj"
+        ));
+
+        let package = Leiningen::new().recognize(filepath).await.unwrap();
+        assert_eq!(package.namespace, "com.example");
+        assert_eq!(package.name, "app");
+        assert_eq!(package.version, "0.1.0-SNAPSHOT");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 7);
+
+        let ring = deps["pkg:maven/ring/ring-core"];
+        assert_eq!(ring.requirement, "1.10.0");
+        assert_eq!(ring.scope, "compile");
+        assert!(ring.is_runtime);
+        assert!(deps["pkg:maven/cheshire/cheshire"].is_optional);
+        assert_eq!(deps["pkg:maven/midje/midje"].scope, "test");
+        assert_eq!(deps["pkg:maven/ring/ring-mock"].scope, "dev");
+        assert!(!deps["pkg:maven/ring/ring-mock"].is_runtime);
+        assert_eq!(
+            deps["pkg:maven/javax.servlet/servlet-api"].scope,
+            "provided"
+        );
+        assert_eq!(deps["pkg:maven/lein-ring/lein-ring"].scope, "plugin");
+        assert_eq!(deps["pkg:maven/org.clojure/clojure"].requirement, "");
+    }
+}
@@ -6,12 +6,13 @@ use crate::types::Relation;
 use serde::{Deserialize, Serialize};
 
 use crate::error::SourcePkgError;
-use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest, RecognizeContext};
 
 use std::collections::HashMap;
 use std::io::BufRead;
 use std::path::Path;
 
+use super::reachability;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename = "metadata")]
@@ -256,8 +257,23 @@ impl PackageManifest for JavaMavenPom {
         "maven".into()
     }
 
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let package = self.parse(path).await?;
+        match path.parent() {
+            Some(root) => {
+                Ok(reachability::mark_reachability(root, &context.reachability, package).await)
+            }
+            None => Ok(package),
+        }
+    }
+
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        self.parse(path).await
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
@@ -1,3 +1,40 @@
+use packageurl::PackageUrl;
+
+use crate::types::{DependentPackage, Relation};
+
+pub mod deps_edn;
+pub mod edn;
 pub mod gradle_dependency;
 pub mod gradlelock;
+pub mod leiningen;
 pub mod maven;
+pub mod reachability;
+pub mod sbt;
+
+/// A dependency declared by a build file on the artifact `group:artifact`,
+/// in a Maven scope like `compile` or `test`, or a scope of the build tool,
+/// from a git repository for `vcs_url`.
+fn declared(
+    group: &str,
+    artifact: &str,
+    version: &str,
+    scope: &str,
+    vcs_url: Option<String>,
+) -> Option<DependentPackage> {
+    let mut purl = PackageUrl::new("maven", artifact).ok()?;
+    if !group.is_empty() {
+        purl.with_namespace(group);
+    }
+    if let Some(vcs_url) = vcs_url {
+        purl.add_qualifier("vcs_url", vcs_url).ok()?;
+    }
+
+    Some(DependentPackage {
+        purl: purl.to_string(),
+        requirement: version.to_string(),
+        scope: scope.to_string(),
+        is_runtime: matches!(scope, "compile" | "runtime" | "provided"),
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
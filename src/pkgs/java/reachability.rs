@@ -0,0 +1,454 @@
+//! Reachability of Maven artifacts from the imports of Java and Kotlin sources.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use lazy_static::lazy_static;
+use packageurl::PackageUrl;
+use regex::Regex;
+use tree_sitter::{Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp, query_matches};
+use crate::pkgs::common::model::ReachabilityConfig;
+use crate::types::{Package, Reachability, Relation};
+
+static JAVA_IMPORT_QUERY: &str = "(import_declaration [(scoped_identifier) (identifier)] @import)";
+
+/// Build output directories, which may contain generated or copied sources.
+const BUILD_DIRS: &[&str] = &["target", "build", "out"];
+
+lazy_static! {
+    static ref KOTLIN_IMPORT: Regex =
+        Regex::new(r"(?m)^\s*import\s+([\w.`]+)(?:\.\*)?(?:\s+as\s+\w+)?\s*;?\s*$").unwrap();
+}
+
+/// Returns the imported names of a Java file, without the trailing `.*`.
+pub fn process_java(code: &str) -> Result<HashSet<String>, SourcePkgError> {
+    lazy_static! {
+        static ref IMPORT_QUERY: Query =
+            Query::new(&tree_sitter_java::language(), JAVA_IMPORT_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut imports = HashSet::new();
+
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_java::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    for captures in query_matches(root, &IMPORT_QUERY, code_raw) {
+        for capture in captures {
+            imports.insert(capture.node.utf8_text(code_raw)?.to_string());
+        }
+    }
+
+    Ok(imports)
+}
+
+/// Returns the imported names of a Kotlin file.
+///
+/// There is no Kotlin grammar linked, imports are simple enough to be matched
+/// line by line.
+pub fn process_kotlin(code: &str) -> HashSet<String> {
+    KOTLIN_IMPORT
+        .captures_iter(code)
+        .map(|caps| caps[1].replace('`', ""))
+        .collect()
+}
+
+/// Java packages provided by the JARs of dependencies, keyed by purl without version.
+#[derive(Debug, Default)]
+pub struct JarIndex {
+    packages: HashMap<String, HashSet<String>>,
+}
+
+/// `group:artifact` coordinates and purl (without version) of a dependency.
+struct Coordinates {
+    group_id: String,
+    artifact_id: String,
+    version: Option<String>,
+    purl: String,
+}
+
+impl Coordinates {
+    fn from_purl(purl: &str) -> Option<Self> {
+        let parsed = PackageUrl::from_str(purl).ok()?;
+        // `pkg:maven/group:artifact` for the Gradle dependency tree
+        let (group_id, artifact_id) = match parsed.namespace() {
+            Some(namespace) => (namespace, parsed.name()),
+            None => parsed.name().split_once(':')?,
+        };
+        Some(Self {
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+            version: parsed.version().map(|v| v.to_string()),
+            purl: purl.split('@').next().unwrap_or(purl).to_string(),
+        })
+    }
+}
+
+/// Default location of the local Maven repository.
+fn maven_local_repository() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".m2").join("repository"))
+}
+
+/// Java packages of the classes in a JAR, and the Maven coordinates recorded
+/// under `META-INF/maven/<group>/<artifact>/` if any.
+struct JarContents {
+    packages: HashSet<String>,
+    coordinates: Option<(String, String)>,
+}
+
+fn read_jar(path: &Path) -> Result<JarContents, SourcePkgError> {
+    let file = File::open(path)?;
+    let archive = zip::ZipArchive::new(file)
+        .map_err(|e| SourcePkgError::GenericsError2(format!("{}: {}", path.display(), e)))?;
+
+    let mut packages = HashSet::new();
+    let mut coordinates = None;
+
+    for name in archive.file_names() {
+        if let Some(rest) = name.strip_prefix("META-INF/maven/") {
+            let parts = rest.split('/').collect::<Vec<_>>();
+            if let [group_id, artifact_id, "pom.properties"] = parts.as_slice() {
+                coordinates = Some((group_id.to_string(), artifact_id.to_string()));
+            }
+            continue;
+        }
+
+        // Multi-release JARs keep classes for newer runtimes in `META-INF/versions/<n>/`
+        let name = match name.strip_prefix("META-INF/versions/") {
+            Some(rest) => rest.split_once('/').map(|(_, n)| n).unwrap_or(rest),
+            None => name,
+        };
+
+        if let Some(class) = name.strip_suffix(".class") {
+            if let Some((package, _)) = class.rsplit_once('/') {
+                if !package.starts_with("META-INF") {
+                    packages.insert(package.replace('/', "."));
+                }
+            }
+        }
+    }
+
+    Ok(JarContents {
+        packages,
+        coordinates,
+    })
+}
+
+/// Whether `stem` is the name of a JAR of `artifact_id`, like
+/// `guava-32.1.jar` but not `guava-testlib-32.1.jar`.
+fn is_artifact_jar(stem: &str, artifact_id: &str) -> bool {
+    match stem.strip_prefix(artifact_id) {
+        Some("") => true,
+        Some(rest) => rest
+            .strip_prefix('-')
+            .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit())),
+        None => false,
+    }
+}
+
+impl JarIndex {
+    /// Indexes the JARs of `dependencies`, from the configured directories
+    /// first and then from the local Maven repository.
+    fn build(dependencies: &[Coordinates], config: &ReachabilityConfig) -> Self {
+        let mut index = Self::default();
+
+        let by_coordinates: HashMap<(&str, &str), &Coordinates> = dependencies
+            .iter()
+            .map(|c| ((c.group_id.as_str(), c.artifact_id.as_str()), c))
+            .collect();
+
+        for dir in &config.jar_dirs {
+            let walk = ignore::WalkBuilder::new(dir)
+                .standard_filters(false)
+                .build();
+            for entry in walk.flatten() {
+                let path = entry.path();
+                if path.extension() != Some("jar".as_ref()) {
+                    continue;
+                }
+
+                let JarContents {
+                    packages,
+                    coordinates,
+                } = match read_jar(path) {
+                    Ok(x) => x,
+                    Err(e) => {
+                        log::warn!("Failed to read {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+
+                // Fall back to `<artifact>-<version>.jar` for JARs built without Maven
+                let dependency = coordinates
+                    .and_then(|(g, a)| by_coordinates.get(&(g.as_str(), a.as_str())).copied())
+                    .or_else(|| {
+                        let stem = path.file_stem()?.to_str()?;
+                        dependencies
+                            .iter()
+                            .find(|c| is_artifact_jar(stem, &c.artifact_id))
+                    });
+
+                if let Some(dependency) = dependency {
+                    index
+                        .packages
+                        .entry(dependency.purl.clone())
+                        .or_default()
+                        .extend(packages);
+                }
+            }
+        }
+
+        if let Some(repository) = maven_local_repository() {
+            for dependency in dependencies {
+                if index.packages.contains_key(&dependency.purl) {
+                    continue;
+                }
+
+                if let Some(jar) = find_in_repository(&repository, dependency) {
+                    match read_jar(&jar) {
+                        Ok(contents) => {
+                            index
+                                .packages
+                                .insert(dependency.purl.clone(), contents.packages);
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to read {}: {}", jar.display(), e);
+                        }
+                    }
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Whether `imports` use any package of the dependency, `None` if its JAR
+    /// was not found.
+    fn is_imported(&self, purl: &str, imports: &HashSet<String>) -> Option<bool> {
+        let packages = self.packages.get(purl)?;
+
+        // `a.b.C`, `a.b.C.member` for static imports, `a.b` for `a.b.*`
+        Some(imports.iter().any(|import| {
+            let mut name = import.as_str();
+            loop {
+                if packages.contains(name) {
+                    return true;
+                }
+                match name.rsplit_once('.') {
+                    Some((parent, _)) => name = parent,
+                    None => return false,
+                }
+            }
+        }))
+    }
+}
+
+/// `<group path>/<artifact>/<version>/<artifact>-<version>.jar`, the latest
+/// version available if it's unknown.
+fn find_in_repository(repository: &Path, dependency: &Coordinates) -> Option<PathBuf> {
+    let artifact_dir = repository
+        .join(dependency.group_id.replace('.', "/"))
+        .join(&dependency.artifact_id);
+
+    let version = match &dependency.version {
+        Some(v) if artifact_dir.join(v).is_dir() => v.clone(),
+        _ => {
+            let mut versions = std::fs::read_dir(&artifact_dir)
+                .ok()?
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .collect::<Vec<_>>();
+            versions.sort_by(
+                |a, b| match (semver::Version::parse(a), semver::Version::parse(b)) {
+                    (Ok(a), Ok(b)) => a.cmp(&b),
+                    _ => a.cmp(b),
+                },
+            );
+            versions.pop()?
+        }
+    };
+
+    let jar = artifact_dir
+        .join(&version)
+        .join(format!("{}-{}.jar", dependency.artifact_id, version));
+    jar.exists().then_some(jar)
+}
+
+fn walk_reachability(path: &Path) -> HashSet<String> {
+    let (walk, matcher) = build_walker_excluding(path, &["java", "kotlin"], BUILD_DIRS);
+
+    let mut ret = HashSet::new();
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        let ftyp = if let Some(t) = match_ftyp(&entry, &matcher) {
+            t
+        } else {
+            continue;
+        };
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        match ftyp.as_str() {
+            "java" => match process_java(&content) {
+                Ok(imports) => ret.extend(imports),
+                Err(e) => {
+                    log::error!("Failed to parse {}: {}", entry.path().display(), e);
+                }
+            },
+            "kotlin" => ret.extend(process_kotlin(&content)),
+            _ => {}
+        }
+    }
+
+    ret
+}
+
+/// Marks the direct runtime dependencies of `res` that no Java or Kotlin file
+/// under `root` imports from as unreachable.
+///
+/// Dependencies whose JAR can't be found are left untouched.
+pub async fn mark_reachability(
+    root: &Path,
+    config: &ReachabilityConfig,
+    mut res: Package,
+) -> Package {
+    let candidates = res
+        .dependencies
+        .iter()
+        .filter(|d| d.is_runtime && d.relation.contains(&Relation::Direct))
+        .filter_map(|d| Coordinates::from_purl(&d.purl))
+        .collect::<Vec<_>>();
+    if candidates.is_empty() {
+        return res;
+    }
+
+    let root = root.to_path_buf();
+    let config = config.clone();
+    let (imports, index) = tokio::task::spawn_blocking(move || {
+        (
+            walk_reachability(&root),
+            JarIndex::build(&candidates, &config),
+        )
+    })
+    .await
+    .unwrap();
+
+    log::debug!("Imports: {:?}", imports);
+
+    for dep in res.dependencies.iter_mut() {
+        if dep.is_runtime && dep.relation.contains(&Relation::Direct) {
+            let purl_without_version = dep.purl.split('@').next().unwrap();
+
+            if index.is_imported(purl_without_version, &imports) == Some(false) {
+                dep.reachable = Reachability::No;
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_imports() {
+        let code = r#"
+package com.example;
+
+import java.util.List;
+import com.google.common.collect.*;
+import static org.junit.Assert.assertEquals;
+
+class App {}
+"#;
+
+        let imports = process_java(code).unwrap();
+        assert!(imports.contains("java.util.List"));
+        assert!(imports.contains("com.google.common.collect"));
+        assert!(imports.contains("org.junit.Assert.assertEquals"));
+
+        let imports = process_kotlin("package app\n\nimport io.ktor.server.engine.*\nimport kotlinx.coroutines.launch as go\n");
+        assert!(imports.contains("io.ktor.server.engine"));
+        assert!(imports.contains("kotlinx.coroutines.launch"));
+    }
+
+    #[test]
+    fn artifact_jars() {
+        assert!(is_artifact_jar("guava-32.1.3-jre", "guava"));
+        assert!(is_artifact_jar("guava", "guava"));
+        assert!(!is_artifact_jar("guava-testlib-32.1.3-jre", "guava"));
+        assert!(!is_artifact_jar("jackson-databind-2.17.0", "jackson"));
+        assert!(!is_artifact_jar("guavaextra-1.0", "guava"));
+    }
+
+    #[test]
+    fn gradle_coordinates() {
+        let purl = PackageUrl::new("maven", "com.google.guava:guava")
+            .unwrap()
+            .to_string();
+        let coordinates = Coordinates::from_purl(&purl).unwrap();
+        assert_eq!(coordinates.group_id, "com.google.guava");
+        assert_eq!(coordinates.artifact_id, "guava");
+        assert_eq!(coordinates.purl, purl);
+    }
+
+    #[tokio::test]
+    async fn maven_reachability() {
+        use crate::pkgs::common::model::{PackageManifest, RecognizeContext};
+        use crate::pkgs::java::maven::JavaMavenPom;
+
+        let root = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/maven/reachability"
+        ));
+        let context = RecognizeContext {
+            reachability: ReachabilityConfig {
+                jar_dirs: vec![root.join("libs")],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let package = JavaMavenPom::new()
+            .recognize_with_config(&root.join("pom.xml"), &context)
+            .await
+            .unwrap();
+        let reachable = |name: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl.contains(name))
+                .unwrap()
+                .reachable
+        };
+
+        assert_eq!(reachable("/gson"), Reachability::Possible);
+        assert_eq!(reachable("/commons-lang3"), Reachability::No);
+        // No JAR to index
+        assert_eq!(reachable("/not-downloaded"), Reachability::Possible);
+    }
+}
@@ -0,0 +1,206 @@
+//! sbt builds: the `libraryDependencies` of `build.sbt`, and the plugins
+//! added by the `project/*.sbt` files, read statically.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::model::{Package, PackageManifest};
+use crate::types::DependentPackage;
+
+/// The Scala version of the builds without `scalaVersion`.
+const DEFAULT_SCALA_VERSION: &str = "2.12";
+
+/// The suffix of the artifacts of sbt 1.x plugins.
+const SBT_PLUGIN_SUFFIX: &str = "_2.12_1.0";
+
+lazy_static! {
+    static ref BLOCK_COMMENT: Regex = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    static ref LINE_COMMENT: Regex = Regex::new(r"(?m)^\s*//.*$").unwrap();
+    static ref STRING_VAL: Regex =
+        Regex::new(r#"\b(?:lazy\s+)?val\s+(\w+)(?:\s*:\s*String)?\s*=\s*"([^"]*)""#).unwrap();
+    static ref SCALA_VERSION: Regex =
+        Regex::new(r#"\bscalaVersion\b(?:\s+in\s+\w+)?\s*:=\s*("[^"]*"|[\w.]+)"#).unwrap();
+    static ref PLUGIN: Regex =
+        Regex::new(r#"addSbtPlugin\(\s*"([^"\s]+)"\s*%\s*"([^"\s]+)"\s*%\s*("[^"]*"|[\w.]+)\s*\)"#)
+            .unwrap();
+    static ref DEPENDENCY: Regex = Regex::new(
+        r#""([^"\s]+)"\s*(%%%|%%|%)\s*"([^"\s]+)"\s*%\s*("[^"]*"|[\w.]+)(?:\s*%\s*("[^"]*"|\w+))?"#
+    )
+    .unwrap();
+}
+
+/// The binary version of a Scala version, like `2.13` for `2.13.12` or `3`
+/// for `3.3.1`.
+fn binary_version(version: &str) -> String {
+    let mut parts = version.split('.');
+    match (parts.next(), parts.next()) {
+        (Some("3"), _) => "3".into(),
+        (Some(major), Some(minor)) => format!("{}.{}", major, minor),
+        _ => version.into(),
+    }
+}
+
+/// Reads the build definition `content` statically: the string values of
+/// vals are substituted, like `akkaVersion` or `V.akka`, and `%%`
+/// dependencies get the Scala binary version suffix. In the `project`
+/// directory, everything is a dependency of the build itself.
+pub fn parse(content: &str, is_meta_build: bool) -> Vec<DependentPackage> {
+    let content = BLOCK_COMMENT.replace_all(content, "");
+    let content = LINE_COMMENT.replace_all(&content, "");
+
+    let vals = STRING_VAL
+        .captures_iter(&content)
+        .map(|c| (c[1].to_string(), c[2].to_string()))
+        .collect::<HashMap<_, _>>();
+    let value = |expr: &str| -> String {
+        match expr.strip_prefix('"').and_then(|e| e.strip_suffix('"')) {
+            Some(literal) => literal.to_string(),
+            None => {
+                let name = expr.rsplit('.').next().unwrap_or(expr);
+                vals.get(name).cloned().unwrap_or_else(|| expr.to_string())
+            }
+        }
+    };
+
+    let scala_version = SCALA_VERSION
+        .captures(&content)
+        .map(|c| binary_version(&value(&c[1])))
+        .unwrap_or_else(|| DEFAULT_SCALA_VERSION.into());
+
+    let mut ret = vec![];
+    for caps in PLUGIN.captures_iter(&content) {
+        let artifact = format!("{}{}", &caps[2], SBT_PLUGIN_SUFFIX);
+        ret.extend(super::declared(
+            &caps[1],
+            &artifact,
+            &value(&caps[3]),
+            "plugin",
+            None,
+        ));
+    }
+    let content = PLUGIN.replace_all(&content, "");
+
+    for caps in DEPENDENCY.captures_iter(&content) {
+        let artifact = match &caps[2] {
+            "%" => caps[3].to_string(),
+            _ => format!("{}_{}", &caps[3], scala_version),
+        };
+        let scope = match caps.get(5) {
+            _ if is_meta_build => "build".to_string(),
+            Some(scope) => scope_name(scope.as_str()),
+            None => "compile".to_string(),
+        };
+        ret.extend(super::declared(
+            &caps[1],
+            &artifact,
+            &value(&caps[4]),
+            &scope,
+            None,
+        ));
+    }
+
+    ret
+}
+
+/// The Maven scope of a configuration like `Test`, `"provided"` or
+/// `"test->compile"`.
+fn scope_name(config: &str) -> String {
+    let config = config.trim_matches('"');
+    let config = config.split(['-', ',', ';']).next().unwrap_or(config);
+    match config.to_lowercase().as_str() {
+        "integrationtest" | "it" => "test".into(),
+        config => config.into(),
+    }
+}
+
+pub struct Sbt {}
+
+impl Sbt {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Sbt {
+    fn get_name(&self) -> String {
+        "maven".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "sbt".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let is_meta_build = path
+            .parent()
+            .is_some_and(|dir| dir.file_name().is_some_and(|n| n == "project"));
+
+        Ok(Package {
+            primary_language: "Scala".into(),
+            dependencies: parse(&content, is_meta_build),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["build.sbt", "project/*.sbt"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn build_sbt() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/sbt"));
+
+        let package = Sbt::new().recognize(&dir.join("build.sbt")).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 5);
+
+        let akka = deps["pkg:maven/com.typesafe.akka/akka-actor-typed_2.13"];
+        assert_eq!(akka.requirement, "2.6.20");
+        assert_eq!(akka.scope, "compile");
+        assert!(akka.is_runtime);
+        assert_eq!(
+            deps["pkg:maven/com.typesafe.akka/akka-stream_2.13"].requirement,
+            "2.6.20"
+        );
+        assert_eq!(
+            deps["pkg:maven/org.postgresql/postgresql"].requirement,
+            "42.7.1"
+        );
+        let scalatest = deps["pkg:maven/org.scalatest/scalatest_2.13"];
+        assert_eq!(scalatest.scope, "test");
+        assert!(!scalatest.is_runtime);
+        assert_eq!(
+            deps["pkg:maven/javax.servlet/javax.servlet-api"].scope,
+            "provided"
+        );
+
+        let package = Sbt::new()
+            .recognize(&dir.join("project/plugins.sbt"))
+            .await
+            .unwrap();
+        let plugin = &package.dependencies[0];
+        assert_eq!(package.dependencies.len(), 2);
+        assert_eq!(
+            plugin.purl,
+            "pkg:maven/com.github.sbt/sbt-native-packager_2.12_1.0"
+        );
+        assert_eq!(plugin.requirement, "1.9.16");
+        assert_eq!(plugin.scope, "plugin");
+        assert!(!plugin.is_runtime);
+    }
+}
@@ -125,6 +125,7 @@ impl PackageJson {
                 parents: Default::default(),
                 relation: hashset! {Relation::Direct},
                 reachable: Default::default(),
+                declared_license: Default::default(),
             }
         };
 
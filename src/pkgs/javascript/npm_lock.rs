@@ -212,6 +212,7 @@ impl PackageLock {
                         relation: HashSet::new(),
                         parents: HashSet::new(),
                         reachable: Default::default(),
+                        declared_license: Default::default(),
                     },
                     package.requires.into_keys().collect(),
                 ),
@@ -0,0 +1,458 @@
+//! Julia environments: the packages resolved in `Manifest.toml`, or else the
+//! `[deps]` declared in `Project.toml`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use packageurl::PackageUrl;
+use serde::Deserialize;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct Project {
+    name: String,
+    version: String,
+    deps: BTreeMap<String, String>,
+    weakdeps: BTreeMap<String, String>,
+    compat: BTreeMap<String, String>,
+    extras: BTreeMap<String, String>,
+    targets: BTreeMap<String, Vec<String>>,
+}
+
+/// The dependencies of a manifest entry, by name, or by name and uuid when
+/// several packages have the same name.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum EntryDeps {
+    Names(Vec<String>),
+    Uuids(BTreeMap<String, String>),
+}
+
+impl Default for EntryDeps {
+    fn default() -> Self {
+        EntryDeps::Names(vec![])
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "kebab-case", default)]
+struct Entry {
+    uuid: String,
+    version: Option<String>,
+    git_tree_sha1: Option<String>,
+    deps: EntryDeps,
+    repo_url: Option<String>,
+    repo_rev: Option<String>,
+}
+
+fn make_purl(
+    name: &str,
+    uuid: &str,
+    version: Option<&str>,
+    vcs_url: Option<String>,
+) -> Option<String> {
+    let mut purl = PackageUrl::new("julia", name).ok()?;
+    if let Some(version) = version {
+        purl.with_version(version);
+    }
+    if !uuid.is_empty() {
+        purl.add_qualifier("uuid", uuid).ok()?;
+    }
+    if let Some(vcs_url) = vcs_url {
+        purl.add_qualifier("vcs_url", vcs_url).ok()?;
+    }
+    Some(purl.to_string())
+}
+
+/// The entries of a manifest, `[[deps.Name]]` in the format 2.0 and
+/// `[[Name]]` before.
+fn manifest_entries(content: &str) -> Result<Vec<(String, Entry)>, SourcePkgError> {
+    let manifest: toml::Table = toml::from_str(content)?;
+    let packages = match manifest.get("manifest_format") {
+        Some(_) => match manifest.get("deps") {
+            Some(toml::Value::Table(deps)) => deps.clone(),
+            _ => toml::Table::new(),
+        },
+        None => manifest,
+    };
+
+    let mut ret = vec![];
+    for (name, entries) in packages {
+        let entries = match entries {
+            toml::Value::Array(entries) => entries,
+            _ => continue,
+        };
+        for entry in entries {
+            match entry.try_into::<Entry>() {
+                Ok(entry) => ret.push((name.clone(), entry)),
+                Err(e) => log::warn!("Invalid manifest entry {}: {}", name, e),
+            }
+        }
+    }
+    Ok(ret)
+}
+
+/// Parses a `Manifest.toml`, the packages depending on a package being its
+/// parents. With the `Project.toml` of the environment, its `[deps]` and the
+/// `[extras]` of its targets are the direct dependencies, those of the
+/// targets in the scope of the target, and otherwise the packages no other
+/// package depends on are.
+fn parse_manifest(
+    content: &str,
+    project: Option<&Project>,
+) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let entries = manifest_entries(content)?;
+
+    let mut by_name = HashMap::new();
+    let mut by_uuid = HashMap::new();
+    for (i, (name, entry)) in entries.iter().enumerate() {
+        by_name.entry(name.as_str()).or_insert(i);
+        by_uuid.insert(entry.uuid.as_str(), i);
+    }
+
+    let mut dependencies = vec![];
+    let mut index = HashMap::new();
+    for (i, (name, entry)) in entries.iter().enumerate() {
+        let vcs_url = entry.repo_url.as_ref().map(|url| match &entry.repo_rev {
+            Some(rev) => format!("git+{}@{}", url, rev),
+            None => format!("git+{}", url),
+        });
+        let purl = match make_purl(name, &entry.uuid, entry.version.as_deref(), vcs_url) {
+            Some(purl) => purl,
+            None => continue,
+        };
+        index.insert(i, dependencies.len());
+        dependencies.push(DependentPackage {
+            purl,
+            requirement: entry.version.clone().unwrap_or_default(),
+            is_runtime: true,
+            is_resolved: entry.version.is_some() || entry.git_tree_sha1.is_some(),
+            ..Default::default()
+        });
+    }
+
+    for (i, (_, entry)) in entries.iter().enumerate() {
+        let parent = match index.get(&i) {
+            Some(&parent) => dependencies[parent].purl.clone(),
+            None => continue,
+        };
+        let children = match &entry.deps {
+            EntryDeps::Names(names) => names
+                .iter()
+                .filter_map(|name| by_name.get(name.as_str()))
+                .collect::<Vec<_>>(),
+            EntryDeps::Uuids(uuids) => uuids
+                .values()
+                .filter_map(|uuid| by_uuid.get(uuid.as_str()))
+                .collect(),
+        };
+        for child in children {
+            if let Some(&child) = index.get(child) {
+                dependencies[child].parents.insert(parent.clone());
+            }
+        }
+    }
+
+    // The uuids of the direct dependencies, with their scope
+    let direct = project.map(|project| {
+        let mut direct = project
+            .deps
+            .values()
+            .map(|uuid| (uuid.as_str(), ""))
+            .collect::<HashMap<_, _>>();
+        for (target, names) in &project.targets {
+            for uuid in names.iter().filter_map(|name| project.extras.get(name)) {
+                direct.entry(uuid.as_str()).or_insert(target.as_str());
+            }
+        }
+        direct
+    });
+
+    for (i, (_, entry)) in entries.iter().enumerate() {
+        let dep = match index.get(&i) {
+            Some(&dep) => &mut dependencies[dep],
+            None => continue,
+        };
+        let is_direct = match &direct {
+            Some(direct) => match direct.get(entry.uuid.as_str()) {
+                Some(scope) => {
+                    dep.scope = scope.to_string();
+                    dep.is_runtime = scope.is_empty();
+                    true
+                }
+                None => false,
+            },
+            None => dep.parents.is_empty(),
+        };
+        dep.relation.insert(match is_direct {
+            true => Relation::Direct,
+            false => Relation::Indirect,
+        });
+    }
+
+    Ok(dependencies)
+}
+
+/// Reads the `[deps]` of a `Project.toml` with their `[compat]` bounds, its
+/// `[weakdeps]` as optional, and the `[extras]` of its `[targets]` in the
+/// scope of the target.
+fn parse_project(project: &Project) -> Vec<DependentPackage> {
+    let declared = |name: &str, uuid: &str, scope: &str| -> Option<DependentPackage> {
+        Some(DependentPackage {
+            purl: make_purl(name, uuid, None, None)?,
+            requirement: project.compat.get(name).cloned().unwrap_or_default(),
+            scope: scope.to_string(),
+            is_runtime: scope.is_empty(),
+            relation: maplit::hashset! {Relation::Direct},
+            ..Default::default()
+        })
+    };
+
+    let mut ret = vec![];
+    for (name, uuid) in &project.deps {
+        ret.extend(declared(name, uuid, ""));
+    }
+    for (name, uuid) in &project.weakdeps {
+        ret.extend(declared(name, uuid, "").map(|mut dep| {
+            dep.is_optional = true;
+            dep
+        }));
+    }
+    for (target, names) in &project.targets {
+        for name in names {
+            if let Some(uuid) = project.extras.get(name) {
+                ret.extend(declared(name, uuid, target));
+            }
+        }
+    }
+    ret
+}
+
+/// The manifest of the environment in `dir`, the versioned ones like
+/// `Manifest-v1.11.toml` included.
+fn manifest_path(dir: &Path) -> Option<PathBuf> {
+    for name in ["JuliaManifest.toml", "Manifest.toml"] {
+        if dir.join(name).exists() {
+            return Some(dir.join(name));
+        }
+    }
+    std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("Manifest-v") && n.ends_with(".toml"))
+        })
+}
+
+fn read_project(dir: &Path) -> Option<Project> {
+    let path = ["JuliaProject.toml", "Project.toml"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())?;
+    let content = std::fs::read_to_string(&path).ok()?;
+    match toml::from_str(&content) {
+        Ok(project) => Some(project),
+        Err(e) => {
+            log::warn!("Invalid {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+pub struct JuliaProject {}
+
+impl JuliaProject {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for JuliaProject {
+    fn get_name(&self) -> String {
+        "julia".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        if manifest_path(dir).is_some() {
+            // Already covered by the manifest
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let project: Project = toml::from_str(&content)?;
+        Ok(Package {
+            name: project.name.clone(),
+            version: project.version.clone(),
+            primary_language: "Julia".into(),
+            dependencies: parse_project(&project),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["Project.toml", "JuliaProject.toml"]
+    }
+}
+
+pub struct JuliaManifest {}
+
+impl JuliaManifest {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for JuliaManifest {
+    fn get_name(&self) -> String {
+        "julia".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "julia-manifest".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        if manifest_path(dir).as_deref() != Some(path) {
+            // Another manifest of the environment is used
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let project = read_project(dir);
+        let dependencies = parse_manifest(&content, project.as_ref())?;
+        let project = project.unwrap_or_default();
+        Ok(Package {
+            name: project.name,
+            version: project.version,
+            primary_language: "Julia".into(),
+            dependencies,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["Manifest.toml", "JuliaManifest.toml", "Manifest-v*.toml"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn manifest_v2() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/julia/v2"));
+
+        assert!(matches!(
+            JuliaProject::new()
+                .recognize(&dir.join("Project.toml"))
+                .await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let package = JuliaManifest::new()
+            .recognize(&dir.join("Manifest.toml"))
+            .await
+            .unwrap();
+        assert_eq!(package.name, "Analysis");
+        assert_eq!(package.version, "0.1.0");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 6);
+
+        let dataframes =
+            deps["pkg:julia/DataFrames@1.6.1?uuid=a93c6f00-e57d-5684-b7b6-d8193f3e46c0"];
+        assert_eq!(dataframes.relation, maplit::hashset! {Relation::Direct});
+        assert!(dataframes.is_resolved);
+        assert!(dataframes.is_runtime);
+
+        let tables = deps["pkg:julia/Tables@1.11.1?uuid=bd369af6-aec1-5ad0-b16a-f7cc5008161c"];
+        assert_eq!(tables.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(
+            tables.parents,
+            maplit::hashset! {
+                dataframes.purl.clone(),
+                "pkg:julia/CSV@0.10.12?uuid=336ed68f-0bac-5ca0-87d4-7b16caf5d00b".to_string(),
+            }
+        );
+
+        let test = deps["pkg:julia/Test?uuid=8dfed614-e22c-5e08-85e1-65c5234f0b40"];
+        assert_eq!(test.scope, "test");
+        assert!(!test.is_runtime);
+        assert!(!test.is_resolved);
+
+        assert!(deps.contains_key(
+            "pkg:julia/Parsers@2.8.1?uuid=69de0a69-1ddd-5017-9359-2bf0b02dc9f0&vcs_url=git+https://github.com/JuliaData/Parsers.jl.git%40main"
+        ));
+    }
+
+    #[test]
+    fn manifest_v1() {
+        let manifest = r#"
+[[Example]]
+deps = ["Random"]
+git-tree-sha1 = "46e44e869b4d90b96bd8ed1fdcf32244fddfb6cc"
+uuid = "7876af07-990d-54b4-ab0e-23690620f79a"
+version = "0.5.3"
+
+[[Random]]
+uuid = "9a3f8284-a2c9-5f02-9a11-845980a1fd5c"
+"#;
+        let deps = parse_manifest(manifest, None).unwrap();
+        assert_eq!(deps.len(), 2);
+        let example = deps
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:julia/Example@0.5.3"))
+            .unwrap();
+        assert_eq!(example.relation, maplit::hashset! {Relation::Direct});
+        let random = deps
+            .iter()
+            .find(|d| d.purl.starts_with("pkg:julia/Random"))
+            .unwrap();
+        assert_eq!(random.parents, maplit::hashset! {example.purl.clone()});
+        assert_eq!(random.relation, maplit::hashset! {Relation::Indirect});
+    }
+
+    #[tokio::test]
+    async fn project() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/julia/project/Project.toml"
+        ));
+
+        let package = JuliaProject::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+
+        let dataframes = deps["pkg:julia/DataFrames?uuid=a93c6f00-e57d-5684-b7b6-d8193f3e46c0"];
+        assert_eq!(dataframes.requirement, "1.6");
+        assert!(!dataframes.is_resolved);
+        assert_eq!(
+            deps["pkg:julia/Plots?uuid=91a5bcdd-55d7-5caf-9e0b-520d859cae80"].requirement,
+            ""
+        );
+        assert!(deps["pkg:julia/Makie?uuid=ee78f7c6-11fb-53f2-987a-cfe4a2b5a57a"].is_optional);
+        let test = deps["pkg:julia/Test?uuid=8dfed614-e22c-5e08-85e1-65c5234f0b40"];
+        assert_eq!(test.scope, "test");
+        assert!(!test.is_runtime);
+    }
+}
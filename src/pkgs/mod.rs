@@ -1,7 +1,9 @@
 use std::sync::Arc;
 
-pub use self::common::model::{PackageManifest, RecognizeContext};
+pub use self::common::model::{PackageManifest, ReachabilityConfig, RecognizeContext};
 
+pub mod apk;
+pub mod bazel;
 pub mod cargo;
 pub mod chef;
 pub mod chef_json;
@@ -11,19 +13,29 @@ pub mod composer;
 pub mod conan;
 pub mod cran;
 pub mod dart;
+pub mod docker;
 pub mod dotnet;
+pub mod dpkg;
 pub mod elm;
+pub mod erlang;
 pub mod fortran;
+pub mod haskell;
 pub mod haxe;
 pub mod java;
 pub mod javascript;
+pub mod julia;
+pub mod nix;
 pub mod opam;
+pub mod perl;
 pub mod python;
 pub mod renv;
+pub mod rpm;
 pub mod ruby;
 pub mod rubygems;
+pub mod shell;
 pub mod spec;
 pub mod swift;
+pub mod terraform;
 
 use java::*;
 use python::*;
@@ -38,24 +50,51 @@ where
 pub fn create_scanners() -> Vec<Arc<dyn PackageManifest + Send + Sync>> {
     vec![
         wrap_scanner(javascript::manifest::PackageJson::new()),
+        wrap_scanner(apk::ApkInstalled::new()),
+        wrap_scanner(bazel::module::ModuleBazel::new()),
+        wrap_scanner(bazel::module::ModuleBazelLock::new()),
+        wrap_scanner(bazel::maven_install::MavenInstall::new()),
         wrap_scanner(cargo::CargoToml::new()),
         wrap_scanner(cargo::CargoLock::new()),
         wrap_scanner(chef::Chef::new()),
         wrap_scanner(cocoapods::CocoaPods::new()),
         wrap_scanner(composer::PhpComposer::new()),
+        wrap_scanner(composer::PhpComposerInstalled::new()),
         wrap_scanner(conan::ConanLock::new()),
         wrap_scanner(cran::Cran::new()),
+        wrap_scanner(docker::Dockerfile::new()),
         wrap_scanner(dotnet::csproj::CSharpCsproj::new()),
+        wrap_scanner(dpkg::DpkgStatus::new()),
+        wrap_scanner(dotnet::deps_json::DotnetDepsJson::new()),
         wrap_scanner(elm::ElmJson::new()),
+        wrap_scanner(erlang::mix::MixExs::new()),
+        wrap_scanner(erlang::mix::MixLock::new()),
+        wrap_scanner(erlang::rebar::RebarConfig::new()),
+        wrap_scanner(erlang::rebar::RebarLock::new()),
         wrap_scanner(fortran::FpmToml::new()),
         wrap_scanner(ruby::gemfile::Gemfile::new()),
         wrap_scanner(gradlelock::GradleLock::new()),
         wrap_scanner(gradle_dependency::GradleDependencies::new()),
+        wrap_scanner(haskell::cabal::Cabal::new()),
+        wrap_scanner(haskell::cabal::CabalFreeze::new()),
+        wrap_scanner(haskell::stack::StackYaml::new()),
+        wrap_scanner(haskell::stack::StackLock::new()),
         wrap_scanner(haxe::Haxe::new()),
+        wrap_scanner(julia::JuliaProject::new()),
+        wrap_scanner(julia::JuliaManifest::new()),
         wrap_scanner(maven::JavaMavenPom::new()),
+        wrap_scanner(sbt::Sbt::new()),
+        wrap_scanner(deps_edn::DepsEdn::new()),
+        wrap_scanner(leiningen::Leiningen::new()),
         wrap_scanner(dotnet::nuspec::DotnetNuSpec::new()),
         wrap_scanner(dotnet::nuget_central::NuGetCentral::new()),
+        wrap_scanner(dotnet::paket::Paket::new()),
+        wrap_scanner(nix::flake_lock::FlakeLock::new()),
+        wrap_scanner(nix::flake_nix::FlakeNix::new()),
         wrap_scanner(opam::OcamlOpam::new()),
+        wrap_scanner(perl::cpanfile::Cpanfile::new()),
+        wrap_scanner(perl::meta::CpanMeta::new()),
+        wrap_scanner(perl::snapshot::CpanfileSnapshot::new()),
         wrap_scanner(pipfile::Pipfile::new()),
         wrap_scanner(pipfilelock::Pipfilelock::new()),
         wrap_scanner(renv::RenvLock::new()),
@@ -66,7 +105,11 @@ pub fn create_scanners() -> Vec<Arc<dyn PackageManifest + Send + Sync>> {
         wrap_scanner(pysetup_cfg::PySetupCfg::new()),
         wrap_scanner(pyproject::PyProject::new()),
         wrap_scanner(pysetup::PySetup::new()),
+        wrap_scanner(rpm::RpmDb::new()),
         wrap_scanner(rubygems::RubyGems::new()),
+        wrap_scanner(shell::ShellScript::new()),
         wrap_scanner(swift::SwiftPmLock::new()),
+        wrap_scanner(terraform::lock::TerraformLock::new()),
+        wrap_scanner(terraform::module::TerraformModules::new()),
     ]
 }
@@ -0,0 +1,216 @@
+//! The inputs of a flake, and their own inputs, locked in `flake.lock`.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+use serde_json::{Map, Value as Json};
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::FlakeRef;
+
+/// The inputs of nested flakes `follows` at most this deep.
+const MAX_FOLLOWS_DEPTH: usize = 32;
+
+/// The node an input refers to, by its key, or by the path of a `follows`
+/// from the root node like `["nixpkgs"]` or `["utils", "systems"]`.
+fn resolve_input(
+    nodes: &Map<String, Json>,
+    root: &str,
+    input: &Json,
+    depth: usize,
+) -> Option<String> {
+    if let Some(key) = input.as_str() {
+        return Some(key.to_string());
+    }
+    if depth > MAX_FOLLOWS_DEPTH {
+        log::warn!("Too deep follows in flake.lock");
+        return None;
+    }
+
+    let mut key = root.to_string();
+    for name in input.as_array()? {
+        let input = nodes.get(&key)?.get("inputs")?.get(name.as_str()?)?;
+        key = resolve_input(nodes, root, input, depth + 1)?;
+    }
+    Some(key)
+}
+
+/// Parses a `flake.lock`, walking the graph of inputs from the root node.
+/// The inputs of the root are direct dependencies, and the nodes whose
+/// inputs refer to a node are its parents. Local paths are left out.
+pub fn parse(content: &str) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    let lock: Json = serde_json::from_str(content)?;
+    let invalid = || SourcePkgError::GenericsError("Invalid flake.lock");
+    let nodes = lock
+        .get("nodes")
+        .and_then(Json::as_object)
+        .ok_or_else(invalid)?;
+    let root = lock.get("root").and_then(Json::as_str).unwrap_or("root");
+
+    let mut dependencies: Vec<DependentPackage> = vec![];
+    let mut index: HashMap<String, Option<usize>> = HashMap::new();
+    let mut queue = VecDeque::from([root.to_string()]);
+    index.insert(root.to_string(), None);
+
+    while let Some(key) = queue.pop_front() {
+        let node = match nodes.get(&key) {
+            Some(node) => node,
+            None => continue,
+        };
+        let parent = index[&key].map(|i| dependencies[i].purl.clone());
+
+        let inputs = node
+            .get("inputs")
+            .and_then(Json::as_object)
+            .cloned()
+            .unwrap_or_default();
+        for (name, input) in &inputs {
+            let child_key = match resolve_input(nodes, root, input, 0) {
+                Some(child_key) if child_key != key => child_key,
+                _ => continue,
+            };
+
+            let child = match index.get(&child_key) {
+                Some(child) => *child,
+                None => {
+                    let child = nodes
+                        .get(&child_key)
+                        .and_then(|child| locked(name, child))
+                        .map(|dep| {
+                            dependencies.push(dep);
+                            dependencies.len() - 1
+                        });
+                    index.insert(child_key.clone(), child);
+                    queue.push_back(child_key);
+                    child
+                }
+            };
+
+            let dep = match child {
+                Some(child) => &mut dependencies[child],
+                None => continue,
+            };
+            match &parent {
+                Some(parent) => {
+                    dep.parents.insert(parent.clone());
+                    dep.relation.insert(Relation::Indirect);
+                }
+                None if key == root => {
+                    dep.relation.insert(Relation::Direct);
+                }
+                // An input of a local flake
+                None => {
+                    dep.relation.insert(Relation::Indirect);
+                }
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
+
+/// The locked input `name`, at its revision and with its NAR hash.
+fn locked(name: &str, node: &Json) -> Option<DependentPackage> {
+    let attrs = node.get("locked")?;
+    let flake_ref = FlakeRef::from_attrs(attrs)?;
+    let nar_hash = attrs.get("narHash").and_then(Json::as_str);
+    let requirement = node
+        .get("original")
+        .and_then(FlakeRef::from_attrs)
+        .and_then(|original| original.git_ref.or(original.rev))
+        .or_else(|| flake_ref.rev.clone())
+        .unwrap_or_default();
+
+    Some(DependentPackage {
+        purl: flake_ref.purl(name, nar_hash)?,
+        requirement,
+        is_runtime: true,
+        is_resolved: flake_ref.rev.is_some() || nar_hash.is_some(),
+        ..Default::default()
+    })
+}
+
+pub struct FlakeLock {}
+
+impl FlakeLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for FlakeLock {
+    fn get_name(&self) -> String {
+        "nix".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "flake-lock".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Nix".into(),
+            dependencies: parse(&content)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["flake.lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flake_lock() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/nix/flake/flake.lock"
+        ));
+
+        let package = FlakeLock::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.split('?').next().unwrap(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 5);
+
+        let nixpkgs = deps["pkg:github/nixos/nixpkgs@a77ab169a83a4175169d78684ddd2e54486ac651"];
+        assert_eq!(
+            nixpkgs.purl,
+            "pkg:github/nixos/nixpkgs@a77ab169a83a4175169d78684ddd2e54486ac651?nar_hash=sha256-r3dWjT8P9/Ah5m5ul4WqIWD8muj5F+/gbCdjiNVBKmU%3D"
+        );
+        assert_eq!(nixpkgs.requirement, "nixos-23.11");
+        assert!(nixpkgs.is_resolved);
+
+        // Follows the inputs of the root
+        let overlay =
+            deps["pkg:github/oxalica/rust-overlay@cc6b3ff1c8f3d4bdf9bb9d64cbbb5c4a1fde3e8d"];
+        assert_eq!(overlay.relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(
+            nixpkgs.relation,
+            maplit::hashset! {Relation::Direct, Relation::Indirect}
+        );
+        assert!(nixpkgs.parents.contains(&overlay.purl));
+
+        let systems =
+            deps["pkg:github/nix-systems/default@da67096a3b9bf56a91d16901293e51ba5b49a27e"];
+        assert_eq!(systems.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(
+            systems.parents,
+            maplit::hashset! {deps["pkg:github/numtide/flake-utils@1ef2e671c3b0c19053962c07dbda38332dcebf26"].purl.clone()}
+        );
+
+        let tools = deps["pkg:generic/tools@0f1e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7d6"];
+        assert_eq!(tools.requirement, "main");
+    }
+}
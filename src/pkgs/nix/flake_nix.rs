@@ -0,0 +1,143 @@
+//! The inputs declared in `flake.nix`, for the flakes without a lock file.
+
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::FlakeRef;
+
+lazy_static! {
+    static ref BLOCK_COMMENT: Regex = Regex::new(r"(?s)/\*.*?\*/").unwrap();
+    static ref LINE_COMMENT: Regex = Regex::new(r"(?m)(^|\s)#.*$").unwrap();
+    /// Like `inputs.nixpkgs.url = "..."` or `nixpkgs.url = "..."`.
+    static ref DOTTED_URL: Regex =
+        Regex::new(r#"(?:^|[\s{;.])([A-Za-z_][\w'-]*)\.url\s*=\s*"([^"]*)""#).unwrap();
+    /// Like `nixpkgs = { url = "..."; flake = false; }`.
+    static ref BLOCK_URL: Regex =
+        Regex::new(r#"([A-Za-z_][\w'-]*)\s*=\s*\{\s*(?:[^{}]*?[\s;])?url\s*=\s*"([^"]*)""#)
+            .unwrap();
+}
+
+/// Reads the `url` of the inputs of a `flake.nix` statically, in the order
+/// they are declared. Inputs without one, like those of the registry only
+/// declared by name, are left out.
+pub fn parse(content: &str) -> Vec<DependentPackage> {
+    let content = BLOCK_COMMENT.replace_all(content, "");
+    let content = LINE_COMMENT.replace_all(&content, "$1");
+
+    let mut inputs = DOTTED_URL
+        .captures_iter(&content)
+        .chain(BLOCK_URL.captures_iter(&content))
+        .map(|caps| {
+            let start = caps.get(0).map(|m| m.start()).unwrap_or_default();
+            (start, caps[1].to_string(), caps[2].to_string())
+        })
+        .filter(|(_, name, _)| name != "inputs")
+        .collect::<Vec<_>>();
+    inputs.sort();
+
+    let mut ret: Vec<DependentPackage> = vec![];
+    let mut seen = vec![];
+    for (_, name, url) in inputs {
+        if seen.contains(&name) {
+            continue;
+        }
+        let flake_ref = match FlakeRef::parse(&url) {
+            Some(flake_ref) => flake_ref,
+            None => continue,
+        };
+        if let Some(purl) = flake_ref.purl(&name, None) {
+            ret.push(DependentPackage {
+                purl,
+                requirement: flake_ref
+                    .git_ref
+                    .clone()
+                    .or_else(|| flake_ref.rev.clone())
+                    .unwrap_or_default(),
+                is_runtime: true,
+                is_resolved: flake_ref.rev.is_some(),
+                relation: maplit::hashset! {Relation::Direct},
+                ..Default::default()
+            });
+        }
+        seen.push(name);
+    }
+    ret
+}
+
+pub struct FlakeNix {}
+
+impl FlakeNix {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for FlakeNix {
+    fn get_name(&self) -> String {
+        "nix".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        if path.with_file_name("flake.lock").exists() {
+            // Already covered by the lock file
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Nix".into(),
+            dependencies: parse(&content),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["flake.nix"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flake_nix() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/nix"));
+
+        assert!(matches!(
+            FlakeNix::new()
+                .recognize(&dir.join("flake/flake.nix"))
+                .await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let package = FlakeNix::new()
+            .recognize(&dir.join("flake-nix/flake.nix"))
+            .await
+            .unwrap();
+        let deps = &package.dependencies;
+        assert_eq!(
+            deps.iter().map(|d| d.purl.as_str()).collect::<Vec<_>>(),
+            vec![
+                "pkg:github/nixos/nixpkgs",
+                "pkg:github/numtide/flake-utils",
+                "pkg:github/oxalica/rust-overlay",
+                "pkg:generic/tools?vcs_url=git+https://example.com/tools.git",
+                "pkg:generic/nixpkgs",
+                "pkg:generic/src?download_url=https://example.com/src-1.0.tar.gz",
+            ]
+        );
+        assert_eq!(deps[0].requirement, "nixos-unstable");
+        assert!(!deps[0].is_resolved);
+        assert_eq!(deps[3].requirement, "main");
+        assert_eq!(deps[4].requirement, "nixos-23.11");
+        assert_eq!(deps[0].relation, maplit::hashset! {Relation::Direct});
+    }
+}
@@ -0,0 +1,251 @@
+//! Nix flakes: the inputs locked in `flake.lock`, or else those declared in
+//! `flake.nix`.
+
+use packageurl::PackageUrl;
+use serde_json::Value as Json;
+
+pub mod flake_lock;
+pub mod flake_nix;
+
+/// The type of a flake reference.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Kind {
+    GitHub,
+    GitLab,
+    SourceHut,
+    Git,
+    Mercurial,
+    Tarball,
+    File,
+    #[default]
+    Path,
+    /// A flake of the registry, like `nixpkgs`.
+    Indirect,
+}
+
+/// A flake reference, like `github:NixOS/nixpkgs/nixos-23.11` or the
+/// `locked` attributes of a `flake.lock` node.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FlakeRef {
+    pub kind: Kind,
+    pub owner: String,
+    pub repo: String,
+    /// The URL of the repository or archive, or the id of an indirect flake.
+    pub url: String,
+    /// The host of a GitLab or SourceHut instance.
+    pub host: Option<String>,
+    pub git_ref: Option<String>,
+    pub rev: Option<String>,
+}
+
+fn is_rev(s: &str) -> bool {
+    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+impl FlakeRef {
+    /// Parses the URL-like syntax of a flake reference.
+    pub fn parse(reference: &str) -> Option<FlakeRef> {
+        let (location, query) = reference.split_once('?').unwrap_or((reference, ""));
+        let param = |name: &str| {
+            query
+                .split('&')
+                .find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='))
+                .map(str::to_string)
+        };
+        let mut ret = FlakeRef {
+            host: param("host"),
+            git_ref: param("ref"),
+            rev: param("rev"),
+            ..Default::default()
+        };
+
+        let (scheme, rest) = location.split_once(':').unwrap_or(("", location));
+        match scheme {
+            "github" | "gitlab" | "sourcehut" => {
+                ret.kind = match scheme {
+                    "github" => Kind::GitHub,
+                    "gitlab" => Kind::GitLab,
+                    _ => Kind::SourceHut,
+                };
+                let mut parts = rest.splitn(3, '/');
+                ret.owner = parts.next()?.to_string();
+                ret.repo = parts.next()?.to_string();
+                match parts.next() {
+                    Some(rev) if is_rev(rev) => ret.rev = Some(rev.to_string()),
+                    Some(git_ref) => ret.git_ref = Some(git_ref.to_string()),
+                    None => {}
+                }
+                ret.url = location.to_string();
+            }
+            "path" => ret.kind = Kind::Path,
+            "flake" | "" => {
+                if rest.starts_with('/') || rest.starts_with('.') {
+                    ret.kind = Kind::Path;
+                    return Some(ret);
+                }
+                ret.kind = Kind::Indirect;
+                let mut parts = rest.splitn(2, '/');
+                ret.url = parts.next()?.to_string();
+                match parts.next() {
+                    Some(rev) if is_rev(rev) => ret.rev = Some(rev.to_string()),
+                    Some(git_ref) => ret.git_ref = Some(git_ref.to_string()),
+                    None => {}
+                }
+            }
+            _ => {
+                let (kind, url) = if let Some(url) = location.strip_prefix("git+") {
+                    (Kind::Git, url)
+                } else if let Some(url) = location.strip_prefix("hg+") {
+                    (Kind::Mercurial, url)
+                } else if let Some(url) = location.strip_prefix("tarball+") {
+                    (Kind::Tarball, url)
+                } else if let Some(url) = location.strip_prefix("file+") {
+                    (Kind::File, url)
+                } else if scheme == "git" {
+                    (Kind::Git, location)
+                } else if [
+                    ".tar", ".tar.gz", ".tgz", ".tar.xz", ".tar.bz2", ".tar.zst", ".zip",
+                ]
+                .iter()
+                .any(|ext| location.ends_with(ext))
+                {
+                    (Kind::Tarball, location)
+                } else {
+                    (Kind::File, location)
+                };
+                ret.kind = kind;
+                ret.url = url.to_string();
+            }
+        }
+        Some(ret)
+    }
+
+    /// Reads the `locked` or `original` attributes of a `flake.lock` node.
+    pub fn from_attrs(attrs: &Json) -> Option<FlakeRef> {
+        let string = |name: &str| attrs.get(name).and_then(Json::as_str).map(str::to_string);
+        let kind = match string("type")?.as_str() {
+            "github" => Kind::GitHub,
+            "gitlab" => Kind::GitLab,
+            "sourcehut" => Kind::SourceHut,
+            "git" => Kind::Git,
+            "hg" => Kind::Mercurial,
+            "tarball" => Kind::Tarball,
+            "file" => Kind::File,
+            "path" => Kind::Path,
+            "indirect" => Kind::Indirect,
+            kind => {
+                log::warn!("Unknown flake input type {}", kind);
+                return None;
+            }
+        };
+        Some(FlakeRef {
+            kind,
+            owner: string("owner").unwrap_or_default(),
+            repo: string("repo").unwrap_or_default(),
+            url: string("url").or_else(|| string("id")).unwrap_or_default(),
+            host: string("host"),
+            git_ref: string("ref"),
+            rev: string("rev"),
+        })
+    }
+
+    /// The purl of the flake, the input `name` for those not from a forge,
+    /// at its revision and with the hash of its contents `nar_hash`. Local
+    /// paths have none.
+    pub fn purl(&self, name: &str, nar_hash: Option<&str>) -> Option<String> {
+        let vcs_url = |url: String| match &self.rev {
+            Some(rev) => format!("{}@{}", url, rev),
+            None => url,
+        };
+
+        let mut purl = match self.kind {
+            Kind::GitHub => {
+                let mut purl = PackageUrl::new("github", self.repo.as_str()).ok()?;
+                purl.with_namespace(self.owner.as_str());
+                purl
+            }
+            Kind::GitLab if self.host.as_deref().unwrap_or("gitlab.com") == "gitlab.com" => {
+                let mut purl = PackageUrl::new("gitlab", self.repo.as_str()).ok()?;
+                purl.with_namespace(self.owner.as_str());
+                purl
+            }
+            Kind::GitLab | Kind::SourceHut => {
+                let host = match (&self.host, self.kind) {
+                    (Some(host), _) => host.as_str(),
+                    (None, Kind::SourceHut) => "git.sr.ht",
+                    (None, _) => "gitlab.com",
+                };
+                let owner = match self.kind {
+                    Kind::SourceHut if !self.owner.starts_with('~') => format!("~{}", self.owner),
+                    _ => self.owner.clone(),
+                };
+                let mut purl = PackageUrl::new("generic", self.repo.as_str()).ok()?;
+                let url = format!("git+https://{}/{}/{}", host, owner, self.repo);
+                purl.add_qualifier("vcs_url", vcs_url(url)).ok()?;
+                purl
+            }
+            Kind::Git | Kind::Mercurial => {
+                let scheme = if self.kind == Kind::Git { "git" } else { "hg" };
+                let mut purl = PackageUrl::new("generic", name).ok()?;
+                let url = format!("{}+{}", scheme, self.url);
+                purl.add_qualifier("vcs_url", vcs_url(url)).ok()?;
+                purl
+            }
+            Kind::Tarball | Kind::File => {
+                let mut purl = PackageUrl::new("generic", name).ok()?;
+                purl.add_qualifier("download_url", self.url.as_str()).ok()?;
+                purl
+            }
+            Kind::Indirect => PackageUrl::new("generic", self.url.as_str()).ok()?,
+            Kind::Path => return None,
+        };
+
+        if let Some(rev) = &self.rev {
+            purl.with_version(rev.as_str());
+        }
+        if let Some(nar_hash) = nar_hash {
+            purl.add_qualifier("nar_hash", nar_hash).ok()?;
+        }
+        Some(purl.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flake_refs() {
+        let github = FlakeRef::parse("github:NixOS/nixpkgs/nixos-23.11").unwrap();
+        assert_eq!(github.kind, Kind::GitHub);
+        assert_eq!(github.git_ref.as_deref(), Some("nixos-23.11"));
+        assert_eq!(
+            github.purl("nixpkgs", None).unwrap(),
+            "pkg:github/nixos/nixpkgs"
+        );
+
+        let git = FlakeRef::parse(
+            "git+https://example.com/tools.git?ref=main&rev=0f1e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7d6",
+        )
+        .unwrap();
+        assert_eq!(git.kind, Kind::Git);
+        assert_eq!(git.url, "https://example.com/tools.git");
+        assert_eq!(
+            git.purl("tools", None).unwrap(),
+            "pkg:generic/tools@0f1e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7d6?vcs_url=git+https://example.com/tools.git%400f1e2d3c4b5a69788796a5b4c3d2e1f0a9b8c7d6"
+        );
+
+        let indirect = FlakeRef::parse("nixpkgs/nixos-unstable").unwrap();
+        assert_eq!(indirect.kind, Kind::Indirect);
+        assert_eq!(indirect.url, "nixpkgs");
+
+        assert_eq!(
+            FlakeRef::parse("https://example.com/src.tar.gz")
+                .unwrap()
+                .kind,
+            Kind::Tarball
+        );
+        assert_eq!(FlakeRef::parse("path:./sub").unwrap().kind, Kind::Path);
+        assert_eq!(FlakeRef::parse("./sub").unwrap().purl("sub", None), None);
+    }
+}
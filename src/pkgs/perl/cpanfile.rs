@@ -0,0 +1,158 @@
+//! The prereqs declared in a `cpanfile`, read statically.
+
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package};
+use crate::PackageManifest;
+
+use super::prereq;
+
+lazy_static! {
+    static ref POD: Regex = Regex::new(r"(?ms)^=\w+.*?(?:^=cut\b.*?$|\z)").unwrap();
+    static ref COMMENT: Regex = Regex::new(r"(?m)#.*$").unwrap();
+    static ref TOKEN: Regex = Regex::new(
+        r#"(?x)
+        \bon\s*\(?\s*['"]?(?P<phase>\w+)['"]?\s*(?:=>|,)\s*sub\s*\{
+        | \bfeature\s*\(?\s*['"][^'"]*['"]\s*(?:(?:=>|,)\s*['"][^'"]*['"]\s*)?(?:=>|,)\s*sub\s*\{
+        | \b(?P<keyword>requires|recommends|suggests|conflicts
+            |test_requires|build_requires|configure_requires|author_requires)
+          \s*\(?\s*['"](?P<module>[^'"]+)['"]
+          (?:\s*(?:=>|,)\s*(?:['"](?P<quoted>[^'"]*)['"]|(?P<number>[\d._]+)))?
+        | (?P<open>\{)
+        | (?P<close>\})
+        "#
+    )
+    .unwrap();
+}
+
+/// The blocks of a `cpanfile`.
+enum Block {
+    /// `on 'test' => sub { ... }`
+    Phase(String),
+    /// `feature 'sqlite' => sub { ... }`, of optional prereqs.
+    Feature,
+    Other,
+}
+
+/// Reads the prereqs of a `cpanfile`, in the phase of their `on` block or
+/// of their keyword like `test_requires`, `runtime` otherwise. Those of the
+/// optional features are optional.
+pub fn parse(content: &str) -> Vec<DependentPackage> {
+    let content = POD.replace_all(content, "");
+    let content = COMMENT.replace_all(&content, "");
+
+    let mut blocks = vec![];
+    let mut ret = vec![];
+    for caps in TOKEN.captures_iter(&content) {
+        if let Some(phase) = caps.name("phase") {
+            blocks.push(Block::Phase(phase.as_str().to_string()));
+        } else if caps.name("open").is_some() {
+            blocks.push(Block::Other);
+        } else if caps.name("close").is_some() {
+            blocks.pop();
+        } else if let Some(keyword) = caps.name("keyword") {
+            let (phase, relationship) = match keyword.as_str() {
+                "test_requires" => ("test", "requires"),
+                "build_requires" => ("build", "requires"),
+                "configure_requires" => ("configure", "requires"),
+                "author_requires" => ("develop", "requires"),
+                relationship => {
+                    let phase = blocks.iter().rev().find_map(|block| match block {
+                        Block::Phase(phase) => Some(phase.as_str()),
+                        _ => None,
+                    });
+                    (phase.unwrap_or("runtime"), relationship)
+                }
+            };
+            let version = caps
+                .name("quoted")
+                .or_else(|| caps.name("number"))
+                .map_or("", |m| m.as_str());
+            let in_feature = blocks.iter().any(|block| matches!(block, Block::Feature));
+            if let Some(mut dep) = prereq(&caps["module"], version, phase, relationship) {
+                dep.is_optional |= in_feature;
+                ret.push(dep);
+            }
+        } else {
+            blocks.push(Block::Feature);
+        }
+    }
+    ret
+}
+
+pub struct Cpanfile {}
+
+impl Cpanfile {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Cpanfile {
+    fn get_name(&self) -> String {
+        "cpan".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        if path.with_file_name("cpanfile.snapshot").exists() {
+            // Already covered by the lock file
+            return Err(SourcePkgError::Skipped);
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "Perl".into(),
+            dependencies: parse(&content),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["cpanfile"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn cpanfile() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/perl/cpanfile/cpanfile"
+        ));
+
+        let package = Cpanfile::new().recognize(filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 9);
+
+        let plack = deps["pkg:cpan/Plack"];
+        assert_eq!(plack.requirement, "1.0047");
+        assert_eq!(plack.scope, "runtime");
+        assert!(plack.is_runtime);
+        assert!(!plack.is_optional);
+        assert_eq!(deps["pkg:cpan/JSON::XS"].requirement, ">= 4.0, < 5.0");
+        assert_eq!(deps["pkg:cpan/Try::Tiny"].requirement, "");
+        assert!(deps["pkg:cpan/Cpanel::JSON::XS"].is_optional);
+        assert!(deps["pkg:cpan/Log::Any"].is_optional);
+
+        let test_more = deps["pkg:cpan/Test::More"];
+        assert_eq!(test_more.scope, "test");
+        assert!(!test_more.is_runtime);
+        assert_eq!(deps["pkg:cpan/Test::Deep"].scope, "test");
+        assert_eq!(deps["pkg:cpan/Module::Build"].scope, "configure");
+        assert!(deps["pkg:cpan/DBD::SQLite"].is_optional);
+        assert_eq!(deps["pkg:cpan/DBD::SQLite"].scope, "runtime");
+    }
+}
@@ -0,0 +1,180 @@
+//! The prereqs of a CPAN distribution in its `META.json` or `META.yml`.
+
+use std::path::Path;
+
+use serde_json::Value as Json;
+
+use crate::error::SourcePkgError;
+use crate::types::Package;
+use crate::PackageManifest;
+
+use super::prereq;
+
+/// The prereqs of the 1.x metadata, by key, with their phase and
+/// relationship.
+const V1_PREREQS: &[(&str, &str, &str)] = &[
+    ("requires", "runtime", "requires"),
+    ("recommends", "runtime", "recommends"),
+    ("conflicts", "runtime", "conflicts"),
+    ("build_requires", "build", "requires"),
+    ("test_requires", "test", "requires"),
+    ("configure_requires", "configure", "requires"),
+];
+
+/// The modules of a map of prereqs, like `{"Moo": "2.0"}`.
+fn modules(prereqs: Option<&Json>) -> impl Iterator<Item = (&str, String)> {
+    prereqs
+        .and_then(Json::as_object)
+        .into_iter()
+        .flatten()
+        .map(|(module, version)| {
+            let version = match version {
+                Json::String(version) => version.clone(),
+                Json::Null => String::new(),
+                version => version.to_string(),
+            };
+            (module.as_str(), version)
+        })
+}
+
+/// Reads the metadata of a distribution, the `prereqs` by phase and
+/// relationship of the version 2, or the `requires` and the like of the
+/// versions 1.x.
+pub fn parse(meta: &Json) -> Package {
+    let mut dependencies = vec![];
+    match meta.get("prereqs").and_then(Json::as_object) {
+        Some(phases) => {
+            for (phase, relationships) in phases {
+                let relationships = relationships.as_object().into_iter().flatten();
+                for (relationship, prereqs) in relationships {
+                    for (module, version) in modules(Some(prereqs)) {
+                        dependencies.extend(prereq(module, &version, phase, relationship));
+                    }
+                }
+            }
+        }
+        None => {
+            for (key, phase, relationship) in V1_PREREQS {
+                for (module, version) in modules(meta.get(*key)) {
+                    dependencies.extend(prereq(module, &version, phase, relationship));
+                }
+            }
+        }
+    }
+
+    let string = |key: &str| match meta.get(key) {
+        Some(Json::String(s)) => s.clone(),
+        Some(Json::Number(n)) => n.to_string(),
+        _ => String::new(),
+    };
+    let declared_license = match meta.get("license") {
+        Some(Json::Array(licenses)) => licenses
+            .iter()
+            .filter_map(Json::as_str)
+            .collect::<Vec<_>>()
+            .join(" OR "),
+        _ => string("license"),
+    };
+
+    Package {
+        name: string("name"),
+        version: string("version"),
+        primary_language: "Perl".into(),
+        declared_license,
+        dependencies,
+        ..Default::default()
+    }
+}
+
+pub struct CpanMeta {}
+
+impl CpanMeta {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for CpanMeta {
+    fn get_name(&self) -> String {
+        "cpan".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "cpan-meta".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        let meta: Json = if is_json {
+            serde_json::from_str(&content)?
+        } else {
+            serde_yaml::from_str(&content)?
+        };
+        Ok(parse(&meta))
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["META.json", "META.yml", "MYMETA.json", "MYMETA.yml"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn meta_json() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/perl/meta/META.json"
+        ));
+
+        let package = CpanMeta::new().recognize(filepath).await.unwrap();
+        assert_eq!(package.name, "Web-App");
+        assert_eq!(package.version, "1.02");
+        assert_eq!(package.declared_license, "perl_5");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 5);
+
+        let moo = deps["pkg:cpan/Moo"];
+        assert_eq!(moo.requirement, "2.004");
+        assert_eq!(moo.scope, "runtime");
+        assert!(moo.is_runtime);
+        assert!(deps["pkg:cpan/Type::Tiny::XS"].is_optional);
+        assert_eq!(deps["pkg:cpan/Test::More"].scope, "test");
+        assert!(deps["pkg:cpan/Test::Pod"].is_optional);
+        assert_eq!(deps["pkg:cpan/Test::Pod"].scope, "develop");
+        assert_eq!(deps["pkg:cpan/ExtUtils::MakeMaker"].scope, "configure");
+    }
+
+    #[tokio::test]
+    async fn meta_yml() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/perl/meta/META.yml"
+        ));
+
+        let package = CpanMeta::new().recognize(filepath).await.unwrap();
+        assert_eq!(package.name, "Old-Lib");
+        assert_eq!(package.declared_license, "perl");
+
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+        assert_eq!(deps["pkg:cpan/LWP::UserAgent"].requirement, "6.0");
+        assert_eq!(deps["pkg:cpan/Carp"].requirement, "");
+        assert_eq!(deps["pkg:cpan/Test::Simple"].scope, "build");
+        assert!(deps["pkg:cpan/JSON::XS"].is_optional);
+    }
+}
@@ -0,0 +1,45 @@
+//! Perl distributions from CPAN: the prereqs of `cpanfile` and of the
+//! `META.json` or `META.yml` of a distribution, and the distributions
+//! installed by Carton in `cpanfile.snapshot`.
+
+use packageurl::PackageUrl;
+
+use crate::types::{DependentPackage, Relation};
+
+pub mod cpanfile;
+pub mod meta;
+pub mod snapshot;
+
+/// Modules of the interpreter itself, not packages of CPAN.
+const NOT_PACKAGES: &[&str] = &["perl"];
+
+/// A prereq on the module `module` in the phase `phase`, like `runtime` or
+/// `test`, with the relationship `relationship`, like `requires` or
+/// `recommends`. Those only recommended or suggested are optional, and
+/// conflicts are not dependencies.
+pub fn prereq(
+    module: &str,
+    version: &str,
+    phase: &str,
+    relationship: &str,
+) -> Option<DependentPackage> {
+    if NOT_PACKAGES.contains(&module) || relationship == "conflicts" {
+        return None;
+    }
+
+    let purl = PackageUrl::new("cpan", module).ok()?.to_string();
+    // `0` is any version
+    let requirement = match version.trim() {
+        "0" => "",
+        version => version,
+    };
+    Some(DependentPackage {
+        purl,
+        requirement: requirement.to_string(),
+        scope: phase.to_string(),
+        is_runtime: phase == "runtime",
+        is_optional: matches!(relationship, "recommends" | "suggests"),
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
@@ -0,0 +1,236 @@
+//! The distributions installed by Carton, in `cpanfile.snapshot`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use packageurl::PackageUrl;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+/// A distribution of the snapshot, like `Moo-2.005005`.
+#[derive(Debug, Default)]
+struct Distribution {
+    name: String,
+    pathname: String,
+    provides: Vec<String>,
+    requirements: Vec<String>,
+}
+
+/// The distributions of a snapshot, indented below `DISTRIBUTIONS` with
+/// their `pathname`, the modules they provide and those they require.
+fn distributions(content: &str) -> Vec<Distribution> {
+    let mut ret: Vec<Distribution> = vec![];
+    let mut section = "";
+    for line in content.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let line = line.trim();
+        let dist = match (indent, ret.last_mut()) {
+            (0, _) => {
+                section = "";
+                continue;
+            }
+            (2, _) => {
+                ret.push(Distribution {
+                    name: line.to_string(),
+                    ..Default::default()
+                });
+                continue;
+            }
+            (_, Some(dist)) => dist,
+            _ => continue,
+        };
+        if indent == 4 {
+            match line.split_once(':') {
+                Some(("pathname", pathname)) => dist.pathname = pathname.trim().to_string(),
+                Some((key, _)) => section = key,
+                None => {}
+            }
+            continue;
+        }
+        let module = line
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        match section {
+            "provides" => dist.provides.push(module),
+            "requirements" => dist.requirements.push(module),
+            _ => {}
+        }
+    }
+    ret
+}
+
+/// The purl of a distribution, by its CPAN author and name, like
+/// `pkg:cpan/HAARG/Moo@2.005005`.
+fn make_purl(dist: &Distribution) -> Option<(String, String)> {
+    let (name, version) = dist.name.rsplit_once('-')?;
+    let mut purl = PackageUrl::new("cpan", name).ok()?;
+    purl.with_version(version);
+    // `H/HA/HAARG/Moo-2.005005.tar.gz`
+    if let Some(author) = dist.pathname.rsplit('/').nth(1) {
+        purl.with_namespace(author);
+    }
+    Some((purl.to_string(), version.to_string()))
+}
+
+/// Parses a `cpanfile.snapshot`, the distributions requiring a module being
+/// parents of the distribution providing it. The distributions providing the
+/// prereqs `declared` by the `cpanfile` are the direct dependencies, in their
+/// phase, and otherwise those no other distribution requires are.
+pub fn parse(content: &str, declared: Option<&[DependentPackage]>) -> Vec<DependentPackage> {
+    let dists = distributions(content);
+
+    let mut dependencies = vec![];
+    let mut index = HashMap::new();
+    let mut providers = HashMap::new();
+    for (i, dist) in dists.iter().enumerate() {
+        let (purl, version) = match make_purl(dist) {
+            Some(purl) => purl,
+            None => {
+                log::warn!("Invalid distribution {}", dist.name);
+                continue;
+            }
+        };
+        for module in &dist.provides {
+            providers
+                .entry(module.as_str())
+                .or_insert(dependencies.len());
+        }
+        index.insert(i, dependencies.len());
+        dependencies.push(DependentPackage {
+            purl,
+            requirement: version,
+            scope: "runtime".into(),
+            is_runtime: true,
+            is_resolved: true,
+            ..Default::default()
+        });
+    }
+
+    for (i, dist) in dists.iter().enumerate() {
+        let parent = match index.get(&i) {
+            Some(&parent) => parent,
+            None => continue,
+        };
+        for module in &dist.requirements {
+            // Modules of the core are not in the snapshot
+            match providers.get(module.as_str()) {
+                Some(&child) if child != parent => {
+                    let purl = dependencies[parent].purl.clone();
+                    dependencies[child].parents.insert(purl);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // The declared prereqs, by the distribution providing them
+    let mut direct = HashMap::new();
+    for prereq in declared.unwrap_or_default() {
+        let module = prereq.purl.strip_prefix("pkg:cpan/").unwrap_or_default();
+        if let Some(&dist) = providers.get(module) {
+            direct.entry(dist).or_insert(prereq);
+        }
+    }
+
+    for (i, dep) in dependencies.iter_mut().enumerate() {
+        let is_direct = match (declared, direct.get(&i)) {
+            (Some(_), Some(prereq)) => {
+                dep.scope = prereq.scope.clone();
+                dep.is_runtime = prereq.is_runtime;
+                dep.is_optional = prereq.is_optional;
+                true
+            }
+            (Some(_), None) => false,
+            (None, _) => dep.parents.is_empty(),
+        };
+        dep.relation.insert(match is_direct {
+            true => Relation::Direct,
+            false => Relation::Indirect,
+        });
+    }
+
+    dependencies
+}
+
+pub struct CpanfileSnapshot {}
+
+impl CpanfileSnapshot {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for CpanfileSnapshot {
+    fn get_name(&self) -> String {
+        "cpan".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "cpanfile-snapshot".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        let declared = std::fs::read_to_string(path.with_file_name("cpanfile"))
+            .ok()
+            .map(|cpanfile| super::cpanfile::parse(&cpanfile));
+        Ok(Package {
+            primary_language: "Perl".into(),
+            dependencies: parse(&content, declared.as_deref()),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["cpanfile.snapshot"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pkgs::perl::cpanfile::Cpanfile;
+
+    #[tokio::test]
+    async fn snapshot() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/perl/carton"));
+
+        assert!(matches!(
+            Cpanfile::new().recognize(&dir.join("cpanfile")).await,
+            Err(SourcePkgError::Skipped)
+        ));
+
+        let package = CpanfileSnapshot::new()
+            .recognize(&dir.join("cpanfile.snapshot"))
+            .await
+            .unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 4);
+
+        let moo = deps["pkg:cpan/HAARG/Moo@2.005005"];
+        assert_eq!(moo.relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(moo.scope, "runtime");
+        assert!(moo.is_resolved);
+
+        let modifiers = deps["pkg:cpan/ETHER/Class-Method-Modifiers@2.15"];
+        assert_eq!(modifiers.relation, maplit::hashset! {Relation::Indirect});
+        assert_eq!(modifiers.parents, maplit::hashset! {moo.purl.clone()});
+
+        let test_deep = deps["pkg:cpan/RJBS/Test-Deep@1.204"];
+        assert_eq!(test_deep.relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(test_deep.scope, "test");
+        assert!(!test_deep.is_runtime);
+    }
+}
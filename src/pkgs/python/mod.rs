@@ -7,6 +7,7 @@ pub mod pyproject;
 pub mod pyrequirements;
 pub mod pysetup;
 pub mod pysetup_cfg;
+pub mod reachability;
 
 lazy_static::lazy_static! {
     static ref NORMALIZE_PATTERN: regex::Regex = regex::Regex::new(r"[-_.]+").unwrap();
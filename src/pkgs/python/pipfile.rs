@@ -1,10 +1,10 @@
 use packageurl::PackageUrl;
-use crate::types::DependentPackage;
+use crate::types::{DependentPackage, Relation};
 use toml::de::from_str;
 use toml::Value;
 
 use crate::error::SourcePkgError;
-use crate::pkgs::common::model::{Package, PackageManifest};
+use crate::pkgs::common::model::{Package, PackageManifest, RecognizeContext};
 
 use std::fs::File;
 use std::io::Read;
@@ -41,6 +41,7 @@ impl Pipfile {
                             .expect("purl arguments are invalid")
                             .to_string(),
                         requirement: version.to_string(),
+                        relation: maplit::hashset! {Relation::Direct},
                         ..Default::default()
                     };
                     dependencies.push(dependency);
@@ -69,6 +70,7 @@ impl Pipfile {
                         requirement: version.to_string(),
                         is_runtime: false,
                         is_optional: true,
+                        relation: maplit::hashset! {Relation::Direct},
                         ..Default::default()
                     };
                     dependencies.push(dependency);
@@ -96,8 +98,21 @@ impl PackageManifest for Pipfile {
         "pypi".to_string()
     }
 
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        _context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let package = Pipfile::parse(path)?;
+        match path.parent() {
+            Some(root) => Ok(super::reachability::mark_reachability(root, package).await),
+            None => Ok(package),
+        }
+    }
+
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        Pipfile::parse(path)
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
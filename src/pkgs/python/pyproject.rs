@@ -5,6 +5,7 @@ use std::{collections::HashMap, path::Path};
 use crate::types::Package;
 use serde::Deserialize;
 
+use crate::pkgs::common::model::RecognizeContext;
 use crate::{error::SourcePkgError, pkgs::python::poetrylock, PackageManifest};
 
 lazy_static::lazy_static! {
@@ -132,8 +133,21 @@ impl PackageManifest for PyProject {
         "pypi".to_string()
     }
 
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        _context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let package = Self::parse(path)?;
+        match path.parent() {
+            Some(root) => Ok(super::reachability::mark_reachability(root, package).await),
+            None => Ok(package),
+        }
+    }
+
     async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
-        Self::parse(path)
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
@@ -1,9 +1,9 @@
 use packageurl::PackageUrl;
 use requirements::enums::Comparison;
-use crate::types::DependentPackage;
+use crate::types::{DependentPackage, Relation};
 
 use crate::error::SourcePkgError;
-use crate::pkgs::common::model::{Package, PackageManifest};
+use crate::pkgs::common::model::{Package, PackageManifest, RecognizeContext};
 
 use std::path::Path;
 
@@ -60,6 +60,7 @@ impl PyRequirements {
                 } else {
                     Self::specs_to_string(&requirement.specs)
                 },
+                relation: maplit::hashset! {Relation::Direct},
                 ..Default::default()
             };
 
@@ -90,16 +91,34 @@ impl PackageManifest for PyRequirements {
         "pypi".to_string()
     }
 
-    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        _context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
         let dependencies = Self::parse_requirement(path)?;
         let package = Package {
             dependencies,
             ..Default::default()
         };
 
+        // `requirements/*.txt` live next to the project sources
+        let root = match path.parent() {
+            Some(p) if p.ends_with("requirements") => p.parent(),
+            p => p,
+        };
+        if let Some(root) = root {
+            return Ok(super::reachability::mark_reachability(root, package).await);
+        }
+
         Ok(package)
     }
 
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
+    }
+
     fn file_name_patterns(&self) -> &'static [&'static str] {
         &[
             "*requirement*.txt",
@@ -118,6 +137,7 @@ impl PackageManifest for PyRequirements {
 mod test {
     use super::*;
     use crate::helper::testing::*;
+    use crate::types::Reachability;
     use std::path::Path;
 
     #[test]
@@ -133,4 +153,31 @@ mod test {
             let _ = PyRequirements::parse_requirement(path).expect("Failed to parse");
         }
     }
+
+    #[tokio::test]
+    async fn requirements_reachability() {
+        let path = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/pypi/reachability/requirements.txt"
+        ));
+
+        let package = PyRequirements::new().recognize(path).await.unwrap();
+        let reachable = |name: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl.starts_with(&format!("pkg:pypi/{}", name)))
+                .unwrap()
+                .reachable
+        };
+
+        assert_eq!(reachable("requests"), Reachability::Possible);
+        // `top_level.txt` and the built-in table
+        assert_eq!(reachable("pillow"), Reachability::Possible);
+        assert_eq!(reachable("pyyaml"), Reachability::Possible);
+        // `RECORD`
+        assert_eq!(reachable("typing-inspect-ext"), Reachability::Possible);
+        // Only imported from the virtual environment
+        assert_eq!(reachable("flask"), Reachability::No);
+    }
 }
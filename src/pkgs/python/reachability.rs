@@ -0,0 +1,349 @@
+//! Reachability of Python distributions from the modules imported by `.py` files.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use tree_sitter::{Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp, query_matches};
+use crate::types::{Package, Reachability, Relation};
+
+static PY_IMPORT_QUERY: &str = "
+[
+    (import_statement name: (dotted_name) @module)
+    (import_statement name: (aliased_import name: (dotted_name) @module))
+    (import_from_statement module_name: (dotted_name) @module)
+]
+";
+static PY_IMPORT_MODULE_QUERY: &str = "
+(
+    call
+    function: [
+        (identifier) @function
+        (attribute object: (identifier) @object attribute: (identifier) @function)
+    ]
+    arguments: (argument_list . (string (string_content) @module))
+    (#match? @function \"^(import_module|__import__)$\")
+)
+";
+
+/// Virtual environments commonly created next to the project.
+const VIRTUALENV_DIRS: &[&str] = &[".venv", "venv", "env", ".env", "virtualenv"];
+
+/// Well-known distributions whose top-level module has a different name.
+const KNOWN_MODULES: &[(&str, &str)] = &[
+    ("PIL", "pillow"),
+    ("yaml", "pyyaml"),
+    ("sklearn", "scikit-learn"),
+    ("skimage", "scikit-image"),
+    ("cv2", "opencv-python"),
+    ("bs4", "beautifulsoup4"),
+    ("dateutil", "python-dateutil"),
+    ("dotenv", "python-dotenv"),
+    ("jwt", "pyjwt"),
+    ("Crypto", "pycryptodome"),
+    ("OpenSSL", "pyopenssl"),
+    ("serial", "pyserial"),
+    ("magic", "python-magic"),
+    ("docx", "python-docx"),
+    ("git", "gitpython"),
+    ("MySQLdb", "mysqlclient"),
+    ("psycopg2", "psycopg2-binary"),
+    ("attr", "attrs"),
+    ("google.protobuf", "protobuf"),
+    ("jose", "python-jose"),
+    ("multipart", "python-multipart"),
+    ("zmq", "pyzmq"),
+    ("usb", "pyusb"),
+    ("win32api", "pywin32"),
+];
+
+fn normalize(name: &str) -> String {
+    super::normalize_name(name).to_lowercase()
+}
+
+/// Returns the top-level modules imported by `code`, relative imports excluded.
+pub fn process_py(code: &str) -> Result<HashSet<String>, SourcePkgError> {
+    lazy_static::lazy_static! {
+        static ref IMPORT_QUERY: Query =
+            Query::new(&tree_sitter_python::language(), PY_IMPORT_QUERY).unwrap();
+        static ref IMPORT_MODULE_QUERY: Query =
+            Query::new(&tree_sitter_python::language(), PY_IMPORT_MODULE_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut modules = HashSet::new();
+
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_python::language())
+        .unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    for captures in query_matches(root, &IMPORT_QUERY, code_raw) {
+        for capture in captures {
+            modules.insert(capture.node.utf8_text(code_raw)?.to_string());
+        }
+    }
+
+    for captures in query_matches(root, &IMPORT_MODULE_QUERY, code_raw) {
+        let module = captures.last().expect("Expected module capture");
+        let module = module.node.utf8_text(code_raw)?;
+        if !module.is_empty() && !module.starts_with('.') {
+            modules.insert(module.to_string());
+        }
+    }
+
+    Ok(modules)
+}
+
+/// Maps top-level modules to the (normalized) distributions providing them.
+#[derive(Debug, Default)]
+pub struct ModuleIndex {
+    modules: HashMap<String, HashSet<String>>,
+}
+
+impl ModuleIndex {
+    /// Builds the index from the virtual environments found in `root`.
+    pub fn from_project(root: &Path) -> Self {
+        let mut index = Self::default();
+        for dir in site_packages_dirs(root) {
+            index.add_site_packages(&dir);
+        }
+        index
+    }
+
+    fn insert(&mut self, module: &str, distribution: &str) {
+        self.modules
+            .entry(module.to_string())
+            .or_default()
+            .insert(normalize(distribution));
+    }
+
+    /// Reads `top_level.txt` or `RECORD` of every installed distribution.
+    fn add_site_packages(&mut self, dir: &Path) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(e) => e,
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", dir.display(), e);
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            let stem = if let Some(s) = file_name
+                .strip_suffix(".dist-info")
+                .or_else(|| file_name.strip_suffix(".egg-info"))
+            {
+                s
+            } else {
+                continue;
+            };
+            // `Name-1.0.dist-info`, names are escaped to not contain `-`
+            let distribution = stem.split('-').next().unwrap_or(stem);
+
+            let info_dir = entry.path();
+            if let Ok(top_level) = std::fs::read_to_string(info_dir.join("top_level.txt")) {
+                for module in top_level.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    self.insert(module, distribution);
+                }
+                continue;
+            }
+
+            let record = std::fs::read_to_string(info_dir.join("RECORD"))
+                .or_else(|_| std::fs::read_to_string(info_dir.join("installed-files.txt")));
+            if let Ok(record) = record {
+                for module in record_modules(&record) {
+                    self.insert(&module, distribution);
+                }
+            }
+        }
+    }
+
+    /// Whether any of the imported `modules` belongs to `distribution`.
+    pub fn is_imported(&self, distribution: &str, modules: &HashSet<String>) -> bool {
+        let distribution = normalize(distribution);
+
+        modules.iter().any(|module| {
+            let top_level = module.split('.').next().unwrap_or(module);
+
+            if normalize(top_level) == distribution {
+                return true;
+            }
+
+            if self
+                .modules
+                .get(top_level)
+                .is_some_and(|d| d.contains(&distribution))
+            {
+                return true;
+            }
+
+            KNOWN_MODULES.iter().any(|(known, d)| {
+                *d == distribution
+                    && (module == known || module.starts_with(&format!("{}.", known)))
+            })
+        })
+    }
+}
+
+/// Top-level modules listed in a `RECORD` file (`path,hash,size` per line).
+fn record_modules(record: &str) -> HashSet<String> {
+    let mut modules = HashSet::new();
+
+    for line in record.lines() {
+        let path = line.split(',').next().unwrap_or_default();
+        let first = path.split('/').next().unwrap_or_default();
+
+        let module = if path.contains('/') {
+            if first.ends_with(".dist-info")
+                || first.ends_with(".egg-info")
+                || first.ends_with(".data")
+                || first.starts_with("..")
+                || first == "__pycache__"
+            {
+                continue;
+            }
+            first
+        } else if let Some(m) = first.strip_suffix(".py") {
+            m
+        } else {
+            continue;
+        };
+
+        if !module.is_empty() {
+            modules.insert(module.to_string());
+        }
+    }
+
+    modules
+}
+
+/// `site-packages` of the virtual environments directly under `root`.
+fn site_packages_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut ret = vec![];
+
+    for venv in VIRTUALENV_DIRS {
+        let venv = root.join(venv);
+
+        // Windows layout
+        let windows = venv.join("Lib").join("site-packages");
+        if windows.is_dir() {
+            ret.push(windows);
+        }
+
+        if let Ok(entries) = std::fs::read_dir(venv.join("lib")) {
+            for entry in entries.flatten() {
+                let site_packages = entry.path().join("site-packages");
+                if site_packages.is_dir() {
+                    ret.push(site_packages);
+                }
+            }
+        }
+    }
+
+    ret
+}
+
+fn walk_reachability(path: &Path) -> HashSet<String> {
+    let (walk, matcher) = build_walker_excluding(path, &["py"], VIRTUALENV_DIRS);
+
+    let mut ret = HashSet::new();
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        if match_ftyp(&entry, &matcher).is_none() {
+            continue;
+        }
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        match process_py(&content) {
+            Ok(modules) => ret.extend(modules),
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+
+    ret
+}
+
+/// Marks the direct runtime dependencies of `res` that no `.py` file under
+/// `root` imports as unreachable.
+pub async fn mark_reachability(root: &Path, mut res: Package) -> Package {
+    if res.dependencies.is_empty() {
+        return res;
+    }
+
+    let root = root.to_path_buf();
+    let (modules, index) = tokio::task::spawn_blocking(move || {
+        (walk_reachability(&root), ModuleIndex::from_project(&root))
+    })
+    .await
+    .unwrap();
+
+    log::debug!("Imported modules: {:?}", modules);
+
+    for dep in res.dependencies.iter_mut() {
+        if dep.is_runtime && dep.relation.contains(&Relation::Direct) {
+            let purl_without_version = dep.purl.split('@').next().unwrap();
+            let name = purl_without_version
+                .rsplit('/')
+                .next()
+                .unwrap_or(purl_without_version);
+
+            if !index.is_imported(name, &modules) {
+                dep.reachable = Reachability::No;
+            }
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn py_imports() {
+        let code = r#"
+import os.path
+import numpy as np
+from PIL import Image
+from . import sibling
+from .models import User
+import importlib
+plugin = importlib.import_module("yaml")
+"#;
+
+        let modules = process_py(code).unwrap();
+        assert!(modules.contains("os.path"));
+        assert!(modules.contains("numpy"));
+        assert!(modules.contains("PIL"));
+        assert!(modules.contains("yaml"));
+        assert!(!modules.iter().any(|m| m.contains("models")));
+
+        let index = ModuleIndex::default();
+        assert!(index.is_imported("Pillow", &modules));
+        assert!(index.is_imported("PyYAML", &modules));
+        assert!(!index.is_imported("requests", &modules));
+    }
+}
@@ -72,6 +72,7 @@ impl RenvLock {
                 relation: maplit::hashset! { Relation::Direct },
                 parents,
                 reachable: Default::default(),
+                declared_license: Default::default(),
             };
 
             if !d.parents.is_empty() {
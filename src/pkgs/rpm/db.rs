@@ -0,0 +1,204 @@
+//! The header blobs of the packages of the RPM database backends: SQLite
+//! (`rpmdb.sqlite`), NDB (`Packages.db`) and Berkeley DB (`Packages`).
+
+use std::path::Path;
+
+use crate::error::SourcePkgError;
+
+/// Headers in the `Packages` table of an SQLite database.
+pub fn read_sqlite(path: &Path) -> Result<Vec<Vec<u8>>, SourcePkgError> {
+    use rusqlite::{Connection, OpenFlags};
+
+    // Immutable, the database may be in a read-only image without its WAL
+    let path = std::path::absolute(path)?;
+    let mut uri = url::Url::from_file_path(&path)
+        .map_err(|_| SourcePkgError::GenericsError("Invalid rpmdb path"))?;
+    uri.set_query(Some("immutable=1"));
+    let conn = Connection::open_with_flags(
+        uri.as_str(),
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI,
+    )
+    .map_err(|e| SourcePkgError::GenericsError2(format!("Failed to open rpmdb: {}", e)))?;
+
+    let blobs = conn
+        .prepare("SELECT blob FROM Packages ORDER BY hnum")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, Vec<u8>>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| SourcePkgError::GenericsError2(format!("Failed to read rpmdb: {}", e)))?;
+
+    Ok(blobs)
+}
+
+fn le_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let bytes = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?))
+}
+
+const NDB_HEADER_MAGIC: &[u8] = b"RpmP";
+const NDB_SLOT_MAGIC: &[u8] = b"Slot";
+const NDB_BLOB_MAGIC: &[u8] = b"BlbS";
+const NDB_PAGE_SIZE: usize = 4096;
+const NDB_SLOT_SIZE: usize = 16;
+const NDB_BLOCK_SIZE: usize = 16;
+const NDB_BLOB_HEAD_SIZE: usize = 16;
+
+/// Headers of an NDB database: a header and the slots of the packages in the
+/// first pages, pointing to the blocks of their blobs.
+pub fn read_ndb(content: &[u8]) -> Result<Vec<Vec<u8>>, SourcePkgError> {
+    let invalid = || SourcePkgError::GenericsError("Invalid NDB rpmdb");
+
+    if !content.starts_with(NDB_HEADER_MAGIC) {
+        return Err(invalid());
+    }
+    let slot_pages = le_u32(content, 12).ok_or_else(invalid)? as usize;
+    let slots_end = slot_pages
+        .checked_mul(NDB_PAGE_SIZE)
+        .filter(|end| *end <= content.len())
+        .ok_or_else(invalid)?;
+
+    let mut ret = vec![];
+    // The database header takes the first two slots
+    for at in (2 * NDB_SLOT_SIZE..slots_end).step_by(NDB_SLOT_SIZE) {
+        let slot = &content[at..at + NDB_SLOT_SIZE];
+        if !slot.starts_with(NDB_SLOT_MAGIC) {
+            continue;
+        }
+        let package = le_u32(slot, 4).ok_or_else(invalid)?;
+        let block = le_u32(slot, 8).ok_or_else(invalid)? as usize;
+        if package == 0 {
+            continue;
+        }
+
+        let head = block * NDB_BLOCK_SIZE;
+        let blob_head = content
+            .get(head..head + NDB_BLOB_HEAD_SIZE)
+            .ok_or_else(invalid)?;
+        if !blob_head.starts_with(NDB_BLOB_MAGIC) || le_u32(blob_head, 4) != Some(package) {
+            log::warn!("Invalid blob of package {} in NDB rpmdb", package);
+            continue;
+        }
+        let len = le_u32(blob_head, 12).ok_or_else(invalid)? as usize;
+        let start = head + NDB_BLOB_HEAD_SIZE;
+        let blob = content.get(start..start + len).ok_or_else(invalid)?;
+        ret.push(blob.to_vec());
+    }
+
+    Ok(ret)
+}
+
+const BDB_HASH_MAGIC: u32 = 0x061561;
+const BDB_PAGE_HEADER_SIZE: usize = 26;
+const BDB_PAGE_HASH_UNSORTED: u8 = 2;
+const BDB_PAGE_HASH: u8 = 13;
+const BDB_ITEM_OFFPAGE: u8 = 3;
+
+/// Reads the integers of a Berkeley DB file in its byte order.
+struct Bdb<'a> {
+    content: &'a [u8],
+    swapped: bool,
+    page_size: usize,
+}
+
+impl Bdb<'_> {
+    fn u16(&self, at: usize) -> Option<u16> {
+        let bytes = self.content.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.swapped {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&self, at: usize) -> Option<u32> {
+        let bytes = self.content.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.swapped {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    /// The data of the chain of overflow pages starting at `page`.
+    fn overflow(&self, mut page: u32, len: usize) -> Option<Vec<u8>> {
+        let mut ret = Vec::with_capacity(len);
+        let mut visited = 0;
+        while page != 0 && ret.len() < len {
+            visited += 1;
+            if visited > self.content.len() / self.page_size {
+                return None;
+            }
+
+            let start = page as usize * self.page_size;
+            let next = self.u32(start + 16)?;
+            // The length of the data on the page
+            let used = self.u16(start + 22)? as usize;
+            let data_start = start + BDB_PAGE_HEADER_SIZE;
+            let used = used.min(self.page_size - BDB_PAGE_HEADER_SIZE);
+            ret.extend_from_slice(self.content.get(data_start..data_start + used)?);
+            page = next;
+        }
+        ret.truncate(len);
+        Some(ret)
+    }
+}
+
+/// Headers of a Berkeley DB hash database, the values of its key and value
+/// pairs, which are stored on overflow pages.
+pub fn read_bdb(content: &[u8]) -> Result<Vec<Vec<u8>>, SourcePkgError> {
+    let invalid = || SourcePkgError::GenericsError("Invalid Berkeley DB rpmdb");
+
+    let magic = content.get(12..16).ok_or_else(invalid)?;
+    let swapped = if u32::from_le_bytes(magic.try_into().unwrap()) == BDB_HASH_MAGIC {
+        false
+    } else if u32::from_be_bytes(magic.try_into().unwrap()) == BDB_HASH_MAGIC {
+        true
+    } else {
+        return Err(invalid());
+    };
+    let mut db = Bdb {
+        content,
+        swapped,
+        page_size: 0,
+    };
+    db.page_size = db
+        .u32(20)
+        .map(|s| s as usize)
+        .filter(|s| *s > BDB_PAGE_HEADER_SIZE)
+        .ok_or_else(invalid)?;
+    let last_page = db.u32(32).ok_or_else(invalid)? as usize;
+
+    let mut ret = vec![];
+    for page in 1..=last_page {
+        let start = page * db.page_size;
+        let page_type = match content.get(start + 25) {
+            Some(t) => *t,
+            None => break,
+        };
+        if page_type != BDB_PAGE_HASH && page_type != BDB_PAGE_HASH_UNSORTED {
+            continue;
+        }
+
+        // Offsets of the items, keys and values in turn
+        let entries = db.u16(start + 20).ok_or_else(invalid)? as usize;
+        for value in (1..entries).step_by(2) {
+            let offset = db
+                .u16(start + BDB_PAGE_HEADER_SIZE + value * 2)
+                .ok_or_else(invalid)? as usize;
+            let item = start + offset;
+            if content.get(item) != Some(&BDB_ITEM_OFFPAGE) {
+                continue;
+            }
+
+            let first_page = db.u32(item + 4).ok_or_else(invalid)?;
+            let len = db.u32(item + 8).ok_or_else(invalid)? as usize;
+            match db.overflow(first_page, len) {
+                Some(blob) if blob.len() == len => ret.push(blob),
+                _ => log::warn!("Invalid overflow pages {} in Berkeley DB rpmdb", first_page),
+            }
+        }
+    }
+
+    Ok(ret)
+}
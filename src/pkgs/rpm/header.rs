@@ -0,0 +1,135 @@
+//! RPM header blobs, as stored by the package databases: the index entries
+//! of the tags followed by their data, big-endian.
+
+use std::collections::HashMap;
+
+use crate::error::SourcePkgError;
+
+pub const TAG_NAME: i32 = 1000;
+pub const TAG_VERSION: i32 = 1001;
+pub const TAG_RELEASE: i32 = 1002;
+pub const TAG_EPOCH: i32 = 1003;
+pub const TAG_LICENSE: i32 = 1014;
+pub const TAG_ARCH: i32 = 1022;
+pub const TAG_SOURCERPM: i32 = 1044;
+pub const TAG_PROVIDENAME: i32 = 1047;
+pub const TAG_REQUIREFLAGS: i32 = 1048;
+pub const TAG_REQUIRENAME: i32 = 1049;
+pub const TAG_DIRINDEXES: i32 = 1116;
+pub const TAG_BASENAMES: i32 = 1117;
+pub const TAG_DIRNAMES: i32 = 1118;
+
+const TYPE_INT8: u32 = 2;
+const TYPE_INT16: u32 = 3;
+const TYPE_INT32: u32 = 4;
+const TYPE_STRING: u32 = 6;
+const TYPE_STRING_ARRAY: u32 = 8;
+const TYPE_I18NSTRING: u32 = 9;
+
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    ty: u32,
+    offset: usize,
+    count: usize,
+}
+
+#[derive(Debug)]
+pub struct Header {
+    entries: HashMap<i32, Entry>,
+    data: Vec<u8>,
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let bytes = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes(bytes.try_into().ok()?))
+}
+
+impl Header {
+    pub fn parse(blob: &[u8]) -> Result<Self, SourcePkgError> {
+        let invalid = || SourcePkgError::GenericsError("Invalid RPM header");
+
+        let index_count = be_u32(blob, 0).ok_or_else(invalid)? as usize;
+        let data_len = be_u32(blob, 4).ok_or_else(invalid)? as usize;
+        let data_start = index_count
+            .checked_mul(ENTRY_SIZE)
+            .and_then(|n| n.checked_add(8))
+            .ok_or_else(invalid)?;
+        let data = blob
+            .get(data_start..data_start.saturating_add(data_len))
+            .ok_or_else(invalid)?;
+
+        let mut entries = HashMap::new();
+        for i in 0..index_count {
+            let at = 8 + i * ENTRY_SIZE;
+            let tag = be_u32(blob, at).ok_or_else(invalid)? as i32;
+            let entry = Entry {
+                ty: be_u32(blob, at + 4).ok_or_else(invalid)?,
+                offset: be_u32(blob, at + 8).ok_or_else(invalid)? as usize,
+                count: be_u32(blob, at + 12).ok_or_else(invalid)? as usize,
+            };
+            entries.entry(tag).or_insert(entry);
+        }
+
+        Ok(Self {
+            entries,
+            data: data.to_vec(),
+        })
+    }
+
+    /// The strings of a string, string array or internationalized string tag.
+    pub fn strings(&self, tag: i32) -> Vec<String> {
+        let entry = match self.entries.get(&tag) {
+            Some(e) if [TYPE_STRING, TYPE_STRING_ARRAY, TYPE_I18NSTRING].contains(&e.ty) => *e,
+            _ => return vec![],
+        };
+        let count = if entry.ty == TYPE_STRING {
+            1
+        } else {
+            entry.count
+        };
+
+        self.data
+            .get(entry.offset..)
+            .unwrap_or_default()
+            .split(|b| *b == 0)
+            .take(count)
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+            .collect()
+    }
+
+    /// The first string of a tag, the untranslated one for internationalized
+    /// strings.
+    pub fn string(&self, tag: i32) -> Option<String> {
+        self.strings(tag).into_iter().next()
+    }
+
+    /// The values of an integer tag.
+    pub fn integers(&self, tag: i32) -> Vec<u32> {
+        let entry = match self.entries.get(&tag) {
+            Some(e) => *e,
+            None => return vec![],
+        };
+        let size = match entry.ty {
+            TYPE_INT8 => 1,
+            TYPE_INT16 => 2,
+            TYPE_INT32 => 4,
+            _ => return vec![],
+        };
+
+        let bytes = match self.data.get(
+            entry.offset
+                ..entry
+                    .offset
+                    .saturating_add(entry.count.saturating_mul(size)),
+        ) {
+            Some(b) => b,
+            None => return vec![],
+        };
+        bytes
+            .chunks_exact(size)
+            .map(|c| c.iter().fold(0u32, |acc, b| (acc << 8) | *b as u32))
+            .collect()
+    }
+}
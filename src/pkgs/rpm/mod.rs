@@ -0,0 +1,302 @@
+//! Packages installed on RPM-based systems, from the rpmdb in
+//! `var/lib/rpm` or `usr/lib/sysimage/rpm`.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use packageurl::PackageUrl;
+
+use crate::error::SourcePkgError;
+use crate::pkgs::common::os_release::{self, OsRelease};
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+pub mod db;
+pub mod header;
+
+use header::*;
+
+/// Directories of the rpmdb, relative to the root.
+const RPMDB_DIRS: &[&str] = &["var/lib/rpm", "usr/lib/sysimage/rpm"];
+
+/// Requirements on features of rpm itself, like `rpmlib(PayloadIsZstd)`.
+const RPMSENSE_RPMLIB: u32 = 1 << 24;
+
+/// Public keys imported into the database, which are not packages.
+const GPG_PUBKEY: &str = "gpg-pubkey";
+
+/// An installed package and what it requires.
+#[derive(Debug)]
+struct Installed {
+    name: String,
+    purl: String,
+    requirement: String,
+    license: String,
+    provides: Vec<String>,
+    requires: Vec<String>,
+    files: Vec<String>,
+}
+
+fn read_installed(header: &Header, release: Option<&OsRelease>) -> Option<Installed> {
+    let name = header.string(TAG_NAME)?;
+    if name == GPG_PUBKEY {
+        return None;
+    }
+    let version = header.string(TAG_VERSION)?;
+    let release_tag = header.string(TAG_RELEASE).unwrap_or_default();
+    let full_version = if release_tag.is_empty() {
+        version
+    } else {
+        format!("{}-{}", version, release_tag)
+    };
+
+    let mut purl = PackageUrl::new("rpm", name.clone()).ok()?;
+    purl.with_namespace(release.map_or("redhat", |r| r.id.as_str()))
+        .with_version(full_version.as_str());
+    if let Some(arch) = header.string(TAG_ARCH) {
+        purl.add_qualifier("arch", arch).ok()?;
+    }
+    let epoch = header.integers(TAG_EPOCH).first().copied();
+    if let Some(epoch) = epoch {
+        purl.add_qualifier("epoch", epoch.to_string()).ok()?;
+    }
+    if let Some(distro) = release.and_then(|r| r.distro()) {
+        purl.add_qualifier("distro", distro).ok()?;
+    }
+    if let Some(source) = header.string(TAG_SOURCERPM) {
+        purl.add_qualifier("upstream", source).ok()?;
+    }
+
+    let flags = header.integers(TAG_REQUIREFLAGS);
+    let requires = header
+        .strings(TAG_REQUIRENAME)
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| flags.get(*i).is_none_or(|f| f & RPMSENSE_RPMLIB == 0))
+        .map(|(_, name)| name)
+        .collect();
+
+    let dirs = header.strings(TAG_DIRNAMES);
+    let files = header
+        .strings(TAG_BASENAMES)
+        .into_iter()
+        .zip(header.integers(TAG_DIRINDEXES))
+        .filter_map(|(base, dir)| Some(format!("{}{}", dirs.get(dir as usize)?, base)))
+        .collect();
+
+    Some(Installed {
+        name,
+        purl: purl.to_string(),
+        requirement: match epoch {
+            Some(epoch) => format!("{}:{}", epoch, full_version),
+            None => full_version,
+        },
+        license: header.string(TAG_LICENSE).unwrap_or_default(),
+        provides: header.strings(TAG_PROVIDENAME),
+        requires,
+        files,
+    })
+}
+
+/// Installed packages of the headers of an rpmdb, with the packages requiring
+/// them as parents.
+pub fn build_graph(headers: &[Header], release: Option<&OsRelease>) -> Vec<DependentPackage> {
+    let installed = headers
+        .iter()
+        .filter_map(|h| read_installed(h, release))
+        .collect::<Vec<_>>();
+
+    // Capabilities and files resolve to the packages providing them, only the
+    // files required are indexed
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, pkg) in installed.iter().enumerate() {
+        index.insert(pkg.name.as_str(), i);
+    }
+    for (i, pkg) in installed.iter().enumerate() {
+        for name in &pkg.provides {
+            index.entry(name.as_str()).or_insert(i);
+        }
+    }
+    let required_files = installed
+        .iter()
+        .flat_map(|pkg| pkg.requires.iter())
+        .filter(|name| name.starts_with('/'))
+        .map(String::as_str)
+        .collect::<HashSet<_>>();
+    for (i, pkg) in installed.iter().enumerate() {
+        for file in &pkg.files {
+            if required_files.contains(file.as_str()) {
+                index.entry(file.as_str()).or_insert(i);
+            }
+        }
+    }
+
+    let mut dependencies = installed
+        .iter()
+        .map(|pkg| DependentPackage {
+            purl: pkg.purl.clone(),
+            requirement: pkg.requirement.clone(),
+            is_resolved: true,
+            declared_license: pkg.license.clone(),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    for (i, pkg) in installed.iter().enumerate() {
+        for name in &pkg.requires {
+            match index.get(name.as_str()) {
+                Some(&child) if child != i => {
+                    dependencies[child].parents.insert(pkg.purl.clone());
+                    dependencies[child].relation.insert(Relation::Indirect);
+                }
+                Some(_) => {}
+                None => log::debug!("{} requires {}, which is not installed", pkg.purl, name),
+            }
+        }
+    }
+
+    for dep in dependencies.iter_mut() {
+        if dep.parents.is_empty() {
+            dep.relation.insert(Relation::Direct);
+        }
+    }
+
+    dependencies
+}
+
+pub struct RpmDb {}
+
+impl RpmDb {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for RpmDb {
+    fn get_name(&self) -> String {
+        "rpm".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "rpmdb".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let root = path
+            .parent()
+            .and_then(|dir| {
+                RPMDB_DIRS.iter().find_map(|rpmdb_dir| {
+                    let depth = Path::new(rpmdb_dir).components().count();
+                    dir.ends_with(rpmdb_dir)
+                        .then(|| dir.ancestors().nth(depth))
+                        .flatten()
+                })
+            })
+            .ok_or(SourcePkgError::NotSupported)?;
+
+        let file_name = path.file_name().unwrap_or_default().to_owned();
+        let db_path: PathBuf = path.to_path_buf();
+        let blobs = tokio::task::spawn_blocking(move || {
+            if file_name == "rpmdb.sqlite" {
+                db::read_sqlite(&db_path)
+            } else {
+                let content = std::fs::read(&db_path)?;
+                if file_name == "Packages.db" {
+                    db::read_ndb(&content)
+                } else {
+                    db::read_bdb(&content)
+                }
+            }
+        })
+        .await
+        .unwrap()?;
+
+        let headers = blobs
+            .iter()
+            .filter_map(|blob| match Header::parse(blob) {
+                Ok(h) => Some(h),
+                Err(e) => {
+                    log::warn!("Failed to parse a header of {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let release = os_release::read(root);
+
+        Ok(Package {
+            dependencies: build_graph(&headers, release.as_ref()),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["rpmdb.sqlite", "Packages", "Packages.db"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn check(file_name: &str) {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/rpm/rootfs/var/lib/rpm"
+        ))
+        .join(file_name);
+
+        let package = RpmDb::new().recognize(&filepath).await.unwrap();
+        let deps = package
+            .dependencies
+            .iter()
+            .map(|d| (d.purl.as_str(), d))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(deps.len(), 5, "{}", file_name);
+
+        let release = "pkg:rpm/rocky/rocky-release@9.3-1.2.el9?arch=noarch&distro=rocky-9.3&upstream=rocky-release-9.3-1.2.el9.src.rpm";
+        let basesystem = "pkg:rpm/rocky/basesystem@11-13.el9?arch=noarch&distro=rocky-9.3&upstream=basesystem-11-13.el9.src.rpm";
+        let glibc = "pkg:rpm/rocky/glibc@2.34-83.el9_3.7?arch=x86_64&distro=rocky-9.3&upstream=glibc-2.34-83.el9_3.7.src.rpm";
+        let bash = "pkg:rpm/rocky/bash@5.1.8-6.el9_1?arch=x86_64&distro=rocky-9.3&upstream=bash-5.1.8-6.el9_1.src.rpm";
+        let openssl = "pkg:rpm/rocky/openssl-libs@3.0.7-25.el9_3?arch=x86_64&distro=rocky-9.3&epoch=1&upstream=openssl-3.0.7-25.el9_3.src.rpm";
+
+        // Through the `system-release` capability
+        assert_eq!(
+            deps[release].parents,
+            maplit::hashset! {basesystem.to_string()}
+        );
+        assert_eq!(
+            deps[basesystem].parents,
+            maplit::hashset! {glibc.to_string()}
+        );
+        assert_eq!(
+            deps[glibc].parents,
+            maplit::hashset! {bash.to_string(), openssl.to_string()}
+        );
+        // Through the `/bin/sh` file
+        assert_eq!(deps[bash].parents, maplit::hashset! {glibc.to_string()});
+        assert_eq!(deps[openssl].relation, maplit::hashset! {Relation::Direct});
+        assert_eq!(deps[glibc].relation, maplit::hashset! {Relation::Indirect});
+
+        assert_eq!(deps[openssl].requirement, "1:3.0.7-25.el9_3");
+        assert_eq!(
+            deps[glibc].declared_license,
+            "LGPL-2.1-or-later AND GPL-2.0-or-later"
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite() {
+        check("rpmdb.sqlite").await;
+    }
+
+    #[tokio::test]
+    async fn ndb() {
+        check("Packages.db").await;
+    }
+
+    #[tokio::test]
+    async fn berkeley_db() {
+        check("Packages").await;
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
@@ -10,7 +10,10 @@ use packageurl::PackageUrl;
 use regex::Regex;
 
 use crate::error::SourcePkgError;
-use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest};
+use crate::pkgs::common::model::{DependentPackage, Package, PackageManifest, RecognizeContext};
+use crate::types::{Reachability, Relation};
+
+use super::reachability::{self, GemIndex};
 
 lazy_static! {
     static ref GEMFILE_REGEXES: BTreeMap<&'static str, Regex> = {
@@ -66,6 +69,8 @@ pub struct GemfileInner {
     current_group: String,
     dependencies: Vec<DependentPackage>,
     dependency_keys: HashSet<(String, String)>,
+    /// Purls of gems declared with `require: false`.
+    no_autorequire: HashSet<String>,
 }
 
 fn preprocess(line: &str) -> &str {
@@ -91,6 +96,7 @@ impl GemfileInner {
             current_group: "".into(),
             dependencies: vec![],
             dependency_keys: HashSet::new(),
+            no_autorequire: HashSet::new(),
         }
     }
 
@@ -110,9 +116,12 @@ impl GemfileInner {
         }
 
         let mut dep = DependentPackage {
+            scope: self.current_group.clone(),
             is_resolved: false,
+            relation: maplit::hashset! {Relation::Direct},
             ..Default::default()
         };
+        let mut autorequire = true;
 
         for column in column_list {
             for (criteria, criteria_regex) in GEMFILE_REGEXES.iter() {
@@ -125,6 +134,8 @@ impl GemfileInner {
                         dep.requirement = criteria_value;
                     } else if criteria == "group" {
                         dep.scope = criteria_value;
+                    } else if criteria == "autorequire" {
+                        autorequire = criteria_value != "false";
                     } else if criteria == "name" {
                         dep.purl = PackageUrl::new("gem", criteria_value)
                             .expect("purl arguments are invalid")
@@ -135,6 +146,10 @@ impl GemfileInner {
             }
         }
 
+        if !autorequire && !dep.purl.is_empty() {
+            self.no_autorequire.insert(dep.purl.clone());
+        }
+
         if !dep.purl.is_empty()
             && !self
                 .dependency_keys
@@ -208,19 +223,37 @@ impl Gemfile {
     }
 }
 
-#[async_trait::async_trait]
-impl PackageManifest for Gemfile {
-    fn get_name(&self) -> String {
-        "gemfile".to_string()
+/// Bundler groups of a Gemfile scope, `group :development, :test do`.
+fn gem_groups(scope: &str) -> HashSet<String> {
+    let groups = scope
+        .split(',')
+        .map(|g| g.trim().trim_start_matches(':').to_string())
+        .filter(|g| !g.is_empty() && g != "runtime")
+        .collect::<HashSet<_>>();
+
+    if groups.is_empty() {
+        maplit::hashset! {"default".to_string()}
+    } else {
+        groups
     }
+}
 
-    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+impl Gemfile {
+    fn parse(path: &Path) -> Result<(Package, GemfileInner), SourcePkgError> {
         let lock_path = path.with_file_name("Gemfile.lock");
         if lock_path.exists() {
             log::info!("parsing Gemfile.lock");
 
             match super::gemfilelock::parse_file(&lock_path) {
-                Ok(package) => return Ok(package),
+                Ok(package) => {
+                    // Only needed for the groups and `require: false` of the gems
+                    let mut parser = GemfileInner::new();
+                    if let Err(err) = parser.parse_gemfile(path) {
+                        log::warn!("failed to parse Gemfile: {}", err);
+                        parser = GemfileInner::new();
+                    }
+                    return Ok((package, parser));
+                }
                 Err(err) => {
                     log::warn!("failed to parse Gemfile.lock: {}", err);
                 }
@@ -231,11 +264,125 @@ impl PackageManifest for Gemfile {
         parser.parse_gemfile(path)?;
 
         let package = Package {
-            dependencies: parser.dependencies,
+            dependencies: parser.dependencies.clone(),
             ..Default::default()
         };
 
-        Ok(package)
+        Ok((package, parser))
+    }
+
+    async fn recognize_with_reachability(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        let (mut res, gemfile) = Self::parse(path)?;
+
+        if res.dependencies.is_empty() {
+            return Ok(res);
+        }
+
+        let root = if let Some(parent) = path.parent() {
+            parent.to_path_buf()
+        } else {
+            return Ok(res);
+        };
+
+        let rails_autoload = context
+            .reachability
+            .rails_autoload
+            .unwrap_or_else(|| root.join("config/application.rb").exists());
+
+        let (usage, index) = tokio::task::spawn_blocking(move || {
+            (
+                reachability::walk_reachability(&root),
+                GemIndex::from_project(&root),
+            )
+        })
+        .await
+        .unwrap();
+
+        log::debug!("Required paths: {:?}", usage.requires);
+
+        let gem_name = |purl: &str| {
+            let purl_without_version = purl.split('@').next().unwrap();
+            purl_without_version
+                .rsplit('/')
+                .next()
+                .unwrap_or(purl_without_version)
+                .to_lowercase()
+        };
+        let gems = res
+            .dependencies
+            .iter()
+            .map(|d| gem_name(&d.purl))
+            .collect::<HashSet<_>>();
+
+        let mut paths = usage.requires.iter().cloned().collect::<Vec<_>>();
+        if rails_autoload {
+            // Zeitwerk loads constants without explicit requires
+            paths.extend(
+                usage
+                    .constants
+                    .iter()
+                    .map(|c| reachability::constant_to_path(c)),
+            );
+        }
+        let used = paths
+            .iter()
+            .flat_map(|p| index.resolve(p, &gems))
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        let scopes = gemfile
+            .dependencies
+            .iter()
+            .map(|d| (gem_name(&d.purl), d.scope.as_str()))
+            .collect::<HashMap<_, _>>();
+        let no_autorequire = gemfile
+            .no_autorequire
+            .iter()
+            .map(|purl| gem_name(purl))
+            .collect::<HashSet<_>>();
+
+        for dep in res.dependencies.iter_mut() {
+            if dep.is_runtime && dep.relation.contains(&Relation::Direct) {
+                let name = gem_name(&dep.purl);
+
+                let autorequired = !no_autorequire.contains(&name)
+                    && usage.bundler_groups.as_ref().is_some_and(|required| {
+                        gem_groups(scopes.get(&name).copied().unwrap_or_default())
+                            .iter()
+                            .any(|g| required.contains(g))
+                    });
+
+                if !used.contains(&name) && !autorequired {
+                    dep.reachable = Reachability::No;
+                }
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for Gemfile {
+    fn get_name(&self) -> String {
+        "gemfile".to_string()
+    }
+
+    async fn recognize_with_config(
+        &self,
+        path: &Path,
+        context: &RecognizeContext,
+    ) -> Result<Package, SourcePkgError> {
+        self.recognize_with_reachability(path, context).await
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        self.recognize_with_config(path, &RecognizeContext::default())
+            .await
     }
 
     fn file_name_patterns(&self) -> &'static [&'static str] {
@@ -258,4 +405,48 @@ mod test {
         parser.parse_gemfile(filepath).unwrap();
         println!("{:?}", parser.dependencies);
     }
+
+    #[tokio::test]
+    async fn test_gemfile_reachability() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/ruby/reachability/Gemfile"
+        ));
+
+        let reachable = |package: &Package, name: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl == format!("pkg:gem/{}", name))
+                .unwrap()
+                .reachable
+        };
+
+        let p = Gemfile::new().recognize(filepath).await.unwrap();
+        assert_eq!(reachable(&p, "sinatra"), Reachability::Possible);
+        assert_eq!(reachable(&p, "net-http-persistent"), Reachability::Possible);
+        // Installed in `vendor/bundle`, `lib/restclient.rb`
+        assert_eq!(reachable(&p, "rest-client"), Reachability::Possible);
+        assert_eq!(reachable(&p, "rspec"), Reachability::Possible);
+        // Required by `Bundler.require(:development)`
+        assert_eq!(reachable(&p, "pry"), Reachability::Possible);
+        assert_eq!(reachable(&p, "bootsnap"), Reachability::No);
+        assert_eq!(reachable(&p, "unused_gem"), Reachability::No);
+        assert_eq!(reachable(&p, "faraday"), Reachability::No);
+        // Not declared in the Gemfile
+        assert_eq!(reachable(&p, "msgpack"), Reachability::Possible);
+
+        let context = RecognizeContext {
+            reachability: crate::pkgs::ReachabilityConfig {
+                rails_autoload: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let p = Gemfile::new()
+            .recognize_with_config(filepath, &context)
+            .await
+            .unwrap();
+        assert_eq!(reachable(&p, "faraday"), Reachability::Possible);
+    }
 }
@@ -12,7 +12,9 @@ use packageurl::PackageUrl;
 
 use crate::error::SourcePkgError;
 use crate::pkgs::common::model::{DependentPackage, Package};
+use crate::types::Relation;
 
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
@@ -72,6 +74,18 @@ fn parse(input: &str) -> Result<Vec<(String, String)>, SourcePkgError> {
     Ok(pkgs)
 }
 
+/// Gems listed in the `DEPENDENCIES` section, the ones declared by the Gemfile.
+fn direct_dependencies(input: &str) -> HashSet<String> {
+    input
+        .lines()
+        .skip_while(|line| line.trim_end() != "DEPENDENCIES")
+        .skip(1)
+        .take_while(|line| line.starts_with("  "))
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|name| name.trim_end_matches('!').to_string())
+        .collect()
+}
+
 // NOTICE:
 // DO NOT parse the 'unlocked' gemfile file
 // because some version requirements are unable to resolve
@@ -83,14 +97,24 @@ pub fn parse_file(path: impl AsRef<Path>) -> Result<Package, SourcePkgError> {
     fs.read_to_string(&mut content)?;
 
     let pkgs = parse(&content)?;
+    let direct = direct_dependencies(&content);
 
     let mut dependencies = vec![];
 
     for (name, version) in pkgs {
+        let relation = if direct.is_empty() {
+            HashSet::new()
+        } else if direct.contains(&name) {
+            maplit::hashset! {Relation::Direct}
+        } else {
+            maplit::hashset! {Relation::Indirect}
+        };
+
         let dependency = DependentPackage {
             purl: PackageUrl::new("gem", name).unwrap().to_string(),
             requirement: version,
             is_resolved: true,
+            relation,
             ..Default::default()
         };
 
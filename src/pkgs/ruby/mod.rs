@@ -1,2 +1,3 @@
 pub mod gemfile;
 pub mod gemfilelock;
+pub mod reachability;
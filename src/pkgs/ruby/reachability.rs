@@ -0,0 +1,352 @@
+//! Reachability of gems from the `require`s of Ruby sources.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use tree_sitter::{Node, Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::{build_walker_excluding, match_ftyp, query_matches};
+
+static RB_REQUIRE_QUERY: &str = "
+(
+    call
+    !receiver
+    method: (identifier) @method
+    arguments: (argument_list . (string . (string_content) @path .))
+    (#match? @method \"^(require|load)$\")
+)
+";
+static RB_BUNDLER_QUERY: &str = "
+(
+    call
+    receiver: (constant) @receiver
+    method: (identifier) @method
+    (#eq? @receiver \"Bundler\")
+    (#eq? @method \"require\")
+) @call
+";
+static RB_CONSTANT_QUERY: &str = "[(scope_resolution) (constant)] @constant";
+
+/// The `Bundler.require` groups of a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundlerGroups {
+    /// Groups computed at runtime, e.g. `Rails.groups`
+    All,
+    Groups(HashSet<String>),
+}
+
+impl BundlerGroups {
+    pub fn contains(&self, group: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Groups(groups) => groups.contains(group),
+        }
+    }
+
+    fn extend(&mut self, other: BundlerGroups) {
+        match (self, other) {
+            (Self::All, _) => {}
+            (this, Self::All) => *this = Self::All,
+            (Self::Groups(a), Self::Groups(b)) => a.extend(b),
+        }
+    }
+}
+
+/// What the Ruby sources of a project load.
+#[derive(Debug, Default)]
+pub struct RubyUsage {
+    /// Paths given to `require` and `load`, `require_relative` excluded.
+    pub requires: HashSet<String>,
+    /// Referenced constants, like `Faraday` or `ActiveSupport::Concern`.
+    pub constants: HashSet<String>,
+    /// `None` if Bundler never requires the gems of the Gemfile.
+    pub bundler_groups: Option<BundlerGroups>,
+}
+
+impl RubyUsage {
+    fn extend(&mut self, other: RubyUsage) {
+        self.requires.extend(other.requires);
+        self.constants.extend(other.constants);
+        if let Some(groups) = other.bundler_groups {
+            match &mut self.bundler_groups {
+                Some(g) => g.extend(groups),
+                None => self.bundler_groups = Some(groups),
+            }
+        }
+    }
+}
+
+/// Groups passed to a `Bundler.require` call, `:default` without arguments.
+fn bundler_groups(call: Node, code_raw: &[u8]) -> BundlerGroups {
+    let arguments = if let Some(a) = call.child_by_field_name("arguments") {
+        a
+    } else {
+        return BundlerGroups::Groups(maplit::hashset! {"default".to_string()});
+    };
+
+    let mut groups = HashSet::new();
+    let mut cursor = arguments.walk();
+    for arg in arguments.named_children(&mut cursor) {
+        let text = arg.utf8_text(code_raw).unwrap_or_default();
+        match arg.kind() {
+            "simple_symbol" => {
+                groups.insert(text.trim_start_matches(':').to_string());
+            }
+            "string" => {
+                groups.insert(text.trim_matches(|c| c == '"' || c == '\'').to_string());
+            }
+            _ => return BundlerGroups::All,
+        }
+    }
+
+    BundlerGroups::Groups(groups)
+}
+
+pub fn process_rb(code: &str) -> Result<RubyUsage, SourcePkgError> {
+    lazy_static::lazy_static! {
+        static ref REQUIRE_QUERY: Query =
+            Query::new(&tree_sitter_ruby::language(), RB_REQUIRE_QUERY).unwrap();
+        static ref BUNDLER_QUERY: Query =
+            Query::new(&tree_sitter_ruby::language(), RB_BUNDLER_QUERY).unwrap();
+        static ref CONSTANT_QUERY: Query =
+            Query::new(&tree_sitter_ruby::language(), RB_CONSTANT_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut usage = RubyUsage::default();
+
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_ruby::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    for captures in query_matches(root, &REQUIRE_QUERY, code_raw) {
+        let path = captures.last().expect("Expected path capture");
+        usage
+            .requires
+            .insert(path.node.utf8_text(code_raw)?.to_string());
+    }
+
+    for captures in query_matches(root, &BUNDLER_QUERY, code_raw) {
+        let call = captures
+            .iter()
+            .find(|c| c.node.kind() == "call")
+            .expect("Expected call capture");
+        usage.extend(RubyUsage {
+            bundler_groups: Some(bundler_groups(call.node, code_raw)),
+            ..Default::default()
+        });
+    }
+
+    for captures in query_matches(root, &CONSTANT_QUERY, code_raw) {
+        for capture in captures {
+            let text = capture.node.utf8_text(code_raw)?;
+            usage
+                .constants
+                .insert(text.trim_start_matches("::").to_string());
+        }
+    }
+
+    Ok(usage)
+}
+
+/// `ActiveSupport::Concern` to `active_support/concern`, as Zeitwerk expects.
+pub fn constant_to_path(constant: &str) -> String {
+    constant
+        .split("::")
+        .map(|segment| {
+            let chars = segment.chars().collect::<Vec<_>>();
+            let mut ret = String::new();
+            for (i, c) in chars.iter().enumerate() {
+                if c.is_uppercase() && i > 0 {
+                    let prev_lower = chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit();
+                    let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                    if prev_lower || (chars[i - 1].is_uppercase() && next_lower) {
+                        ret.push('_');
+                    }
+                }
+                ret.extend(c.to_lowercase());
+            }
+            ret
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Maps the top-level entries of installed gems' `lib` directories to gem names.
+#[derive(Debug, Default)]
+pub struct GemIndex {
+    entries: HashMap<String, HashSet<String>>,
+}
+
+/// `nokogiri-1.15.4-x86_64-linux` to `nokogiri`.
+fn gem_dir_name(dir_name: &str) -> &str {
+    dir_name
+        .match_indices('-')
+        .find(|(i, _)| {
+            dir_name[i + 1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit())
+        })
+        .map(|(i, _)| &dir_name[..i])
+        .unwrap_or(dir_name)
+}
+
+/// Directories with installed gems, `vendor/bundle` and the configured `GEM_HOME`.
+fn gem_dirs(root: &Path) -> Vec<PathBuf> {
+    let mut ret = vec![];
+
+    if let Ok(entries) = std::fs::read_dir(root.join("vendor/bundle/ruby")) {
+        for entry in entries.flatten() {
+            ret.push(entry.path().join("gems"));
+        }
+    }
+
+    if let Some(gem_home) = std::env::var_os("GEM_HOME") {
+        ret.push(PathBuf::from(gem_home).join("gems"));
+    }
+
+    ret
+}
+
+impl GemIndex {
+    pub fn from_project(root: &Path) -> Self {
+        let mut index = Self::default();
+
+        for dir in gem_dirs(root) {
+            let gems = if let Ok(g) = std::fs::read_dir(&dir) {
+                g
+            } else {
+                continue;
+            };
+
+            for gem in gems.flatten() {
+                let dir_name = gem.file_name().to_string_lossy().into_owned();
+                let name = gem_dir_name(&dir_name).to_lowercase();
+
+                let lib = if let Ok(l) = std::fs::read_dir(gem.path().join("lib")) {
+                    l
+                } else {
+                    continue;
+                };
+                for entry in lib.flatten() {
+                    let file_name = entry.file_name().to_string_lossy().into_owned();
+                    let entry_name = file_name.strip_suffix(".rb").unwrap_or(&file_name);
+                    index
+                        .entries
+                        .entry(entry_name.to_string())
+                        .or_default()
+                        .insert(name.clone());
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Gems among `gems` that may provide the `require` path.
+    ///
+    /// Without an installed copy, gems are matched by name: `net/http/persistent`
+    /// is `net-http-persistent`, `active_support` is `activesupport`.
+    pub fn resolve<'a>(&self, path: &str, gems: &'a HashSet<String>) -> HashSet<&'a String> {
+        let segments = path.split('/').collect::<Vec<_>>();
+        let first = segments[0];
+
+        if let Some(installed) = self.entries.get(first) {
+            let found = gems
+                .iter()
+                .filter(|g| installed.contains(g.as_str()))
+                .collect::<HashSet<_>>();
+            if !found.is_empty() {
+                return found;
+            }
+        }
+
+        let mut candidates = HashSet::new();
+        for i in 1..=segments.len() {
+            let joined = segments[..i].join("-");
+            candidates.insert(joined.replace('_', "-"));
+            candidates.insert(joined.replace('_', ""));
+            candidates.insert(joined);
+        }
+
+        gems.iter()
+            .filter(|g| candidates.contains(g.as_str()))
+            .collect()
+    }
+}
+
+pub fn walk_reachability(path: &Path) -> RubyUsage {
+    let (walk, matcher) = build_walker_excluding(path, &["ruby", "rake:*.rake"], &["vendor"]);
+
+    let mut ret = RubyUsage::default();
+
+    for entry in walk {
+        let entry = if let Ok(entry) = entry {
+            entry
+        } else {
+            log::error!("Failed to walk entry: {:?}", entry);
+            continue;
+        };
+
+        if match_ftyp(&entry, &matcher).is_none() {
+            continue;
+        }
+
+        let content = match std::fs::read_to_string(entry.path()) {
+            Ok(c) => c,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", entry.path().display(), e);
+                continue;
+            }
+        };
+
+        match process_rb(&content) {
+            Ok(usage) => ret.extend(usage),
+            Err(e) => {
+                log::error!("Failed to parse {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rb_requires() {
+        let code = r#"
+require "json"
+require 'net/http/persistent'
+require_relative "lib/helper"
+Bundler.require(:default, :assets)
+
+class App < Sinatra::Base
+end
+"#;
+
+        let usage = process_rb(code).unwrap();
+        assert!(usage.requires.contains("json"));
+        assert!(usage.requires.contains("net/http/persistent"));
+        assert!(!usage.requires.iter().any(|r| r.contains("helper")));
+        assert!(usage.constants.contains("Sinatra::Base"));
+
+        let groups = usage.bundler_groups.unwrap();
+        assert!(groups.contains("assets"));
+        assert!(!groups.contains("test"));
+
+        let usage = process_rb("Bundler.require(*Rails.groups)").unwrap();
+        assert_eq!(usage.bundler_groups, Some(BundlerGroups::All));
+
+        assert_eq!(
+            constant_to_path("ActiveSupport::Concern"),
+            "active_support/concern"
+        );
+        assert_eq!(constant_to_path("HTMLParser"), "html_parser");
+    }
+}
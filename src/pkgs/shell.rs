@@ -0,0 +1,653 @@
+//! Packages installed imperatively by shell scripts, like `pip install x==1.2`
+//! or `curl ... | sh`.
+
+use std::path::Path;
+
+use lazy_static::lazy_static;
+use packageurl::PackageUrl;
+use regex::Regex;
+use tree_sitter::{Node, Parser, Query};
+
+use crate::error::SourcePkgError;
+use crate::helper::query_matches;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+static SH_COMMAND_QUERY: &str = "(command) @command";
+static SH_PIPELINE_QUERY: &str = "(pipeline) @pipeline";
+
+/// Scope of the packages installed by shell scripts.
+pub const SCRIPT_INSTALL_SCOPE: &str = "script-install";
+
+/// Commands running the command given as their arguments.
+const WRAPPERS: &[&str] = &["sudo", "env", "time", "nice", "nohup", "exec", "command"];
+/// Interpreters a downloaded script may be piped into.
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ash"];
+const DOWNLOADERS: &[&str] = &["curl", "wget"];
+
+/// Options taking a value, per package manager, to not mistake it for a package.
+const PIP_VALUE_OPTIONS: &[&str] = &[
+    "-r",
+    "--requirement",
+    "-c",
+    "--constraint",
+    "-e",
+    "--editable",
+    "-i",
+    "--index-url",
+    "--extra-index-url",
+    "-f",
+    "--find-links",
+    "-t",
+    "--target",
+    "--prefix",
+    "--root",
+    "--trusted-host",
+    "--platform",
+    "--python-version",
+    "--implementation",
+    "--abi",
+    "--src",
+    "--upgrade-strategy",
+    "--cache-dir",
+    "--log",
+    "--python",
+];
+const NPM_VALUE_OPTIONS: &[&str] = &[
+    "--registry",
+    "--prefix",
+    "--cache",
+    "--tag",
+    "-w",
+    "--workspace",
+    "--modules-folder",
+    "--cwd",
+];
+const APT_VALUE_OPTIONS: &[&str] = &["-o", "--option", "-t", "--target-release", "-c"];
+const APK_VALUE_OPTIONS: &[&str] = &[
+    "-X",
+    "--repository",
+    "-t",
+    "--virtual",
+    "--root",
+    "-p",
+    "--arch",
+    "--cache-dir",
+];
+const YUM_VALUE_OPTIONS: &[&str] = &[
+    "--enablerepo",
+    "--disablerepo",
+    "--repo",
+    "--setopt",
+    "--installroot",
+    "--releasever",
+    "-c",
+    "--config",
+    "-d",
+    "-e",
+    "-x",
+    "--exclude",
+];
+const GEM_VALUE_OPTIONS: &[&str] = &[
+    "-i",
+    "--install-dir",
+    "-n",
+    "--bindir",
+    "-s",
+    "--source",
+    "--platform",
+    "-g",
+    "--file",
+];
+const GO_VALUE_OPTIONS: &[&str] = &["-C", "-p", "-tags", "-ldflags", "-gcflags", "-o"];
+const CARGO_VALUE_OPTIONS: &[&str] = &[
+    "--git",
+    "--branch",
+    "--tag",
+    "--rev",
+    "--path",
+    "--root",
+    "--index",
+    "--registry",
+    "--features",
+    "-F",
+    "--target",
+    "--profile",
+    "-j",
+    "--jobs",
+    "--bin",
+    "--example",
+];
+
+lazy_static! {
+    static ref PIP_SPEC: Regex =
+        Regex::new(r"^([A-Za-z0-9][A-Za-z0-9._-]*)(\[[^\]]*\])?\s*(.*)$").unwrap();
+    static ref PYTHON: Regex = Regex::new(r"^python[0-9.]*$").unwrap();
+    static ref PIP: Regex = Regex::new(r"^pip[0-9.]*$").unwrap();
+    static ref DOWNLOAD_URL: Regex =
+        Regex::new(r#"\b(?:curl|wget)\b[^|;&)]*?(https?://[^\s|;&)"']+)"#).unwrap();
+}
+
+/// A command with its arguments, quotes removed.
+fn command_args(command: Node, code_raw: &[u8]) -> Vec<String> {
+    let mut args = vec![];
+
+    if let Some(name) = command.child_by_field_name("name") {
+        args.push(unquote(name.utf8_text(code_raw).unwrap_or_default()));
+    }
+
+    let mut cursor = command.walk();
+    for arg in command.children_by_field_name("argument", &mut cursor) {
+        args.push(unquote(arg.utf8_text(code_raw).unwrap_or_default()));
+    }
+
+    args
+}
+
+fn unquote(text: &str) -> String {
+    text.chars().filter(|&c| c != '"' && c != '\'').collect()
+}
+
+fn program(arg: &str) -> &str {
+    arg.rsplit('/').next().unwrap_or(arg)
+}
+
+/// Removes `sudo`, `env VAR=1` and the like in front of the actual command.
+fn strip_wrappers(mut args: &[String]) -> &[String] {
+    while let Some(first) = args.first() {
+        let name = program(first);
+        if !WRAPPERS.contains(&name) {
+            break;
+        }
+        args = &args[1..];
+        while let Some(arg) = args.first() {
+            if name == "sudo" && ["-u", "-g", "-C", "-D"].contains(&arg.as_str()) {
+                args = args.get(2..).unwrap_or_default();
+            } else if arg.starts_with('-') || (name == "env" && arg.contains('=')) {
+                args = &args[1..];
+            } else {
+                break;
+            }
+        }
+    }
+    args
+}
+
+/// Positional arguments, and the value of the option named in `capture`.
+fn positional<'a>(
+    args: &'a [String],
+    value_options: &[&str],
+    capture: &[&str],
+) -> (Vec<&'a str>, Option<&'a str>) {
+    let mut ret = vec![];
+    let mut captured = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg.starts_with('-') {
+            let (option, value) = match arg.split_once('=') {
+                Some((o, v)) => (o, Some(v)),
+                None => (arg.as_str(), None),
+            };
+            let takes_value = value_options.contains(&option) || capture.contains(&option);
+            let value = match value {
+                Some(v) => Some(v),
+                None if takes_value => iter.next().map(|s| s.as_str()),
+                None => None,
+            };
+            if capture.contains(&option) {
+                captured = value;
+            }
+        } else if !arg.starts_with('$') && !arg.starts_with('`') {
+            ret.push(arg.as_str());
+        }
+    }
+
+    (ret, captured)
+}
+
+fn is_dynamic(s: &str) -> bool {
+    s.contains('$') || s.contains('`')
+}
+
+fn dependency(purl: PackageUrl, requirement: &str, exact: bool, scope: &str) -> DependentPackage {
+    DependentPackage {
+        purl: purl.to_string(),
+        requirement: requirement.to_string(),
+        scope: scope.to_string(),
+        is_resolved: exact,
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    }
+}
+
+/// `requests==2.31.0`, `uvicorn[standard]>=0.20`.
+fn pip_dependency(spec: &str, scope: &str) -> Option<DependentPackage> {
+    if spec.contains("://")
+        || spec.contains('/')
+        || spec.starts_with('.')
+        || [".whl", ".zip", ".tar.gz"]
+            .iter()
+            .any(|e| spec.ends_with(e))
+    {
+        return None;
+    }
+
+    let caps = PIP_SPEC.captures(spec)?;
+    let name = caps.get(1)?.as_str();
+    let requirement = caps.get(3).map(|m| m.as_str().trim()).unwrap_or_default();
+
+    let mut purl = PackageUrl::new("pypi", name).ok()?;
+    let version = requirement
+        .strip_prefix("==")
+        .filter(|v| !v.contains('*') && !v.contains(',') && !is_dynamic(v));
+    if let Some(v) = version {
+        purl.with_version(v);
+    }
+
+    Some(dependency(purl, requirement, version.is_some(), scope))
+}
+
+/// `express@4.18.2`, `@angular/cli@^17`.
+fn npm_dependency(spec: &str, scope: &str) -> Option<DependentPackage> {
+    if spec.contains("://")
+        || spec.contains(':')
+        || spec.starts_with('.')
+        || spec.starts_with('/')
+        || spec.starts_with('~')
+        || spec.ends_with(".tgz")
+    {
+        return None;
+    }
+
+    let (name, version) = match spec.strip_prefix('@') {
+        Some(rest) => match rest.split_once('@') {
+            Some((n, v)) => (&spec[..n.len() + 1], v),
+            None => (spec, ""),
+        },
+        None => spec.split_once('@').unwrap_or((spec, "")),
+    };
+
+    let mut purl = match name.strip_prefix('@').and_then(|n| n.split_once('/')) {
+        Some((namespace, name)) => {
+            let mut purl = PackageUrl::new("npm", name).ok()?;
+            purl.with_namespace(format!("@{}", namespace));
+            purl
+        }
+        // `user/repo` is a GitHub shorthand
+        None if name.contains('/') => return None,
+        None => PackageUrl::new("npm", name).ok()?,
+    };
+
+    let exact = semver::Version::parse(version).is_ok();
+    if exact {
+        purl.with_version(version);
+    }
+
+    Some(dependency(purl, version, exact, scope))
+}
+
+/// `libssl-dev=1.1*`, `curl:amd64`, `nginx/bookworm-backports`.
+fn apt_dependency(spec: &str, scope: &str) -> Option<DependentPackage> {
+    if spec.contains('/') && spec.ends_with(".deb") {
+        return None;
+    }
+
+    let (name, version) = spec.split_once('=').unwrap_or((spec, ""));
+    let name = name.split('/').next().unwrap_or(name);
+    let (name, arch) = match name.split_once(':') {
+        Some((n, a)) => (n, Some(a)),
+        None => (name, None),
+    };
+
+    let mut purl = PackageUrl::new("deb", name).ok()?;
+    let exact = !version.is_empty() && !version.contains('*') && !is_dynamic(version);
+    if exact {
+        purl.with_version(version);
+    }
+    if let Some(arch) = arch {
+        purl.add_qualifier("arch", arch).ok()?;
+    }
+
+    Some(dependency(purl, version, exact, scope))
+}
+
+/// `openssl=3.1.4-r0`, `python3~3.11`.
+fn apk_dependency(spec: &str, scope: &str) -> Option<DependentPackage> {
+    if spec.ends_with(".apk") {
+        return None;
+    }
+
+    let split = spec.find(|c| "=~<>".contains(c)).unwrap_or(spec.len());
+    let (name, requirement) = spec.split_at(split);
+    let name = name.split('@').next().unwrap_or(name);
+
+    let mut purl = PackageUrl::new("apk", name).ok()?;
+    let version = requirement
+        .strip_prefix('=')
+        .filter(|v| !v.is_empty() && !is_dynamic(v));
+    if let Some(v) = version {
+        purl.with_version(v);
+    }
+
+    Some(dependency(purl, requirement, version.is_some(), scope))
+}
+
+/// `httpd`, `nodejs-18.19.0`.
+///
+/// Names may contain dashes followed by digits, `java-17-openjdk`, so versions
+/// can't be told apart and are kept as requirement only.
+fn rpm_dependency(spec: &str, scope: &str) -> Option<DependentPackage> {
+    if spec.ends_with(".rpm") || spec.contains('/') || spec.starts_with('@') {
+        return None;
+    }
+
+    let purl = PackageUrl::new("rpm", spec).ok()?;
+    Some(dependency(purl, "", false, scope))
+}
+
+/// `rails:7.1.2`, or the `-v` option for all gems of the command.
+fn gem_dependency(spec: &str, version: Option<&str>, scope: &str) -> Option<DependentPackage> {
+    if spec.ends_with(".gem") {
+        return None;
+    }
+
+    let (name, requirement) = match spec.split_once(':') {
+        Some((n, v)) => (n, v),
+        None => (spec, version.unwrap_or_default()),
+    };
+
+    let mut purl = PackageUrl::new("gem", name).ok()?;
+    let exact = !requirement.is_empty()
+        && requirement
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if exact {
+        purl.with_version(requirement);
+    }
+
+    Some(dependency(purl, requirement, exact, scope))
+}
+
+/// `golang.org/x/tools/gopls@v0.14.2`.
+fn go_dependency(spec: &str, scope: &str) -> Option<DependentPackage> {
+    let (path, version) = spec.split_once('@').unwrap_or((spec, ""));
+    if path.starts_with('.') || !path.contains('/') {
+        // Packages of the current module
+        return None;
+    }
+
+    let (namespace, name) = path.rsplit_once('/')?;
+    let mut purl = PackageUrl::new("golang", name).ok()?;
+    purl.with_namespace(namespace);
+
+    let exact = version
+        .strip_prefix('v')
+        .is_some_and(|v| semver::Version::parse(v).is_ok());
+    if exact {
+        purl.with_version(version);
+    }
+
+    Some(dependency(purl, version, exact, scope))
+}
+
+/// `ripgrep@14.0.3`, or the `--version` option.
+fn cargo_dependency(spec: &str, version: Option<&str>, scope: &str) -> Option<DependentPackage> {
+    let (name, requirement) = match spec.split_once('@') {
+        Some((n, v)) => (n, v),
+        None => (spec, version.unwrap_or_default()),
+    };
+
+    let mut purl = PackageUrl::new("cargo", name).ok()?;
+    let requirement = requirement.trim_start_matches('=');
+    let exact = semver::Version::parse(requirement).is_ok();
+    if exact {
+        purl.with_version(requirement);
+    }
+
+    Some(dependency(purl, requirement, exact, scope))
+}
+
+/// A script downloaded and run by a shell, named after the host serving it.
+fn script_dependency(url: &str, scope: &str) -> Option<DependentPackage> {
+    let host = url.split("://").nth(1)?.split('/').next()?;
+    let mut purl = PackageUrl::new("generic", host).ok()?;
+    purl.add_qualifier("download_url", url).ok()?;
+
+    Some(dependency(purl, "", false, scope))
+}
+
+/// Packages installed by a single command.
+fn command_dependencies(args: &[String], scope: &str) -> Vec<DependentPackage> {
+    let args = strip_wrappers(args);
+    let (first, rest) = if let Some((f, r)) = args.split_first() {
+        (program(f), r)
+    } else {
+        return vec![];
+    };
+    let sub = |i: usize| rest.get(i).map(|s| s.as_str());
+
+    let deps: Vec<Option<DependentPackage>> = if PIP.is_match(first)
+        || (PYTHON.is_match(first)
+            && sub(0) == Some("-m")
+            && sub(1).is_some_and(|m| PIP.is_match(m)))
+        || (first == "uv" && sub(0) == Some("pip"))
+    {
+        let rest = match first {
+            "uv" => &rest[1..],
+            f if PYTHON.is_match(f) => &rest[2..],
+            _ => rest,
+        };
+        if rest.first().map(|s| s.as_str()) != Some("install") {
+            return vec![];
+        }
+        let (specs, _) = positional(&rest[1..], PIP_VALUE_OPTIONS, &[]);
+        specs.iter().map(|s| pip_dependency(s, scope)).collect()
+    } else if first == "pipx" && sub(0) == Some("install") {
+        let (specs, _) = positional(&rest[1..], PIP_VALUE_OPTIONS, &[]);
+        specs.iter().map(|s| pip_dependency(s, scope)).collect()
+    } else if ["npm", "pnpm"].contains(&first)
+        && sub(0).is_some_and(|s| ["install", "i", "add"].contains(&s))
+    {
+        let (specs, _) = positional(&rest[1..], NPM_VALUE_OPTIONS, &[]);
+        specs.iter().map(|s| npm_dependency(s, scope)).collect()
+    } else if first == "yarn" && (sub(0) == Some("add") || sub(1) == Some("add")) {
+        let skip = if sub(0) == Some("add") { 1 } else { 2 };
+        let (specs, _) = positional(&rest[skip..], NPM_VALUE_OPTIONS, &[]);
+        specs.iter().map(|s| npm_dependency(s, scope)).collect()
+    } else if ["apt-get", "apt"].contains(&first) {
+        let (specs, _) = positional(rest, APT_VALUE_OPTIONS, &[]);
+        match specs.split_first() {
+            Some((&"install", specs)) => specs.iter().map(|s| apt_dependency(s, scope)).collect(),
+            _ => vec![],
+        }
+    } else if first == "apk" {
+        let (specs, _) = positional(rest, APK_VALUE_OPTIONS, &[]);
+        match specs.split_first() {
+            Some((&"add", specs)) => specs.iter().map(|s| apk_dependency(s, scope)).collect(),
+            _ => vec![],
+        }
+    } else if ["yum", "dnf", "microdnf", "tdnf"].contains(&first) {
+        let (specs, _) = positional(rest, YUM_VALUE_OPTIONS, &[]);
+        match specs.split_first() {
+            Some((&"install", specs)) => specs.iter().map(|s| rpm_dependency(s, scope)).collect(),
+            _ => vec![],
+        }
+    } else if first == "gem" && sub(0) == Some("install") {
+        let (specs, version) = positional(&rest[1..], GEM_VALUE_OPTIONS, &["-v", "--version"]);
+        specs
+            .iter()
+            .map(|s| gem_dependency(s, version, scope))
+            .collect()
+    } else if first == "go" && sub(0) == Some("install") {
+        let (specs, _) = positional(&rest[1..], GO_VALUE_OPTIONS, &[]);
+        specs.iter().map(|s| go_dependency(s, scope)).collect()
+    } else if first == "cargo" && sub(0) == Some("install") {
+        let (specs, version) =
+            positional(&rest[1..], CARGO_VALUE_OPTIONS, &["--version", "--vers"]);
+        specs
+            .iter()
+            .map(|s| cargo_dependency(s, version, scope))
+            .collect()
+    } else if SHELLS.contains(&first) {
+        // `sh -c "$(curl -fsSL https://...)"`, `bash <(curl -s https://...)`
+        rest.iter()
+            .filter_map(|arg| DOWNLOAD_URL.captures(arg))
+            .map(|caps| script_dependency(&caps[1], scope))
+            .collect()
+    } else {
+        vec![]
+    };
+
+    deps.into_iter().flatten().collect()
+}
+
+/// Returns the packages installed by a shell script, with the given scope.
+pub fn parse_script(code: &str, scope: &str) -> Result<Vec<DependentPackage>, SourcePkgError> {
+    lazy_static! {
+        static ref COMMAND_QUERY: Query =
+            Query::new(&tree_sitter_bash::language(), SH_COMMAND_QUERY).unwrap();
+        static ref PIPELINE_QUERY: Query =
+            Query::new(&tree_sitter_bash::language(), SH_PIPELINE_QUERY).unwrap();
+    }
+
+    let code_raw = code.as_bytes();
+    let mut ret: Vec<DependentPackage> = vec![];
+
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_bash::language()).unwrap();
+    let tree = parser.parse(code, None).unwrap();
+    let root = tree.root_node();
+
+    let mut push = |dep: DependentPackage| {
+        if !ret
+            .iter()
+            .any(|d| d.purl == dep.purl && d.requirement == dep.requirement)
+        {
+            ret.push(dep);
+        }
+    };
+
+    for captures in query_matches(root, &COMMAND_QUERY, code_raw) {
+        for capture in captures {
+            let args = command_args(capture.node, code_raw);
+            command_dependencies(&args, scope)
+                .into_iter()
+                .for_each(&mut push);
+        }
+    }
+
+    // `curl -fsSL https://... | sh`
+    for captures in query_matches(root, &PIPELINE_QUERY, code_raw) {
+        for capture in captures {
+            let mut download = None;
+            let mut cursor = capture.node.walk();
+            for command in capture.node.named_children(&mut cursor) {
+                if command.kind() != "command" {
+                    continue;
+                }
+                let args = command_args(command, code_raw);
+                let args = strip_wrappers(&args);
+                let name = args.first().map(|a| program(a)).unwrap_or_default();
+
+                if DOWNLOADERS.contains(&name) {
+                    download = args[1..]
+                        .iter()
+                        .find(|a| a.starts_with("http://") || a.starts_with("https://"))
+                        .cloned();
+                } else if SHELLS.contains(&name) {
+                    if let Some(dep) = download.take().and_then(|u| script_dependency(&u, scope)) {
+                        push(dep);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(ret)
+}
+
+pub struct ShellScript {}
+
+impl ShellScript {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for ShellScript {
+    fn get_name(&self) -> String {
+        "shell".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = tokio::fs::read_to_string(path).await?;
+
+        Ok(Package {
+            dependencies: parse_script(&content, SCRIPT_INSTALL_SCOPE)?,
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["*.sh", "*.bash"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn install_script() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/shell/install.sh"
+        ));
+
+        let package = ShellScript::new().recognize(filepath).await.unwrap();
+        let find = |purl: &str| {
+            package
+                .dependencies
+                .iter()
+                .find(|d| d.purl == purl)
+                .unwrap_or_else(|| panic!("{} not found in {:?}", purl, package.dependencies))
+        };
+
+        assert!(find("pkg:pypi/requests@2.31.0").is_resolved);
+        assert_eq!(find("pkg:pypi/uvicorn").requirement, ">=0.20");
+        find("pkg:pypi/black@23.12.1");
+        find("pkg:npm/typescript@5.3.3");
+        assert_eq!(find("pkg:npm/%40angular/cli").requirement, "^17");
+        find("pkg:npm/pm2@5.3.0");
+        find("pkg:deb/curl");
+        let ssl = find("pkg:deb/libssl-dev");
+        assert_eq!(ssl.requirement, "1.1*");
+        assert!(!ssl.is_resolved);
+        find("pkg:deb/git@1:2.39.2-1.1");
+        find("pkg:apk/openssl@3.1.4-r0");
+        find("pkg:gem/bundler@2.4.22");
+        find("pkg:gem/rails@7.1.2");
+        find("pkg:golang/golang.org/x/tools/gopls@v0.14.2");
+        assert_eq!(
+            find("pkg:golang/github.com/go-delve/delve/cmd/dlv").requirement,
+            "latest"
+        );
+        find("pkg:cargo/ripgrep@14.0.3");
+        find("pkg:generic/sh.rustup.rs?download_url=https://sh.rustup.rs");
+        find("pkg:generic/raw.githubusercontent.com?download_url=https://raw.githubusercontent.com/nvm-sh/nvm/v0.39.7/install.sh");
+
+        assert!(package
+            .dependencies
+            .iter()
+            .all(|d| d.scope == SCRIPT_INSTALL_SCOPE));
+        // `-r requirements.txt`, `$PKG` and local paths are not packages
+        assert!(!package
+            .dependencies
+            .iter()
+            .any(|d| d.purl.contains("requirements")
+                || d.purl.contains("PKG")
+                || d.purl.contains("wheel")));
+    }
+}
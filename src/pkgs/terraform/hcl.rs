@@ -0,0 +1,344 @@
+//! The blocks and attributes of HCL files like `.tf` files and
+//! `.terraform.lock.hcl`, with their literal values. Other expressions are
+//! not evaluated.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Str(String),
+    List(Vec<Expr>),
+    /// Any other expression.
+    Other,
+}
+
+impl Expr {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Expr::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The strings of a list.
+    pub fn strings(&self) -> Vec<&str> {
+        match self {
+            Expr::List(items) => items.iter().filter_map(Expr::as_str).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub kind: String,
+    pub labels: Vec<String>,
+    pub body: Body,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Body {
+    pub attributes: Vec<(String, Expr)>,
+    pub blocks: Vec<Block>,
+}
+
+impl Body {
+    pub fn get(&self, name: &str) -> Option<&Expr> {
+        self.attributes
+            .iter()
+            .find_map(|(n, expr)| (n == name).then_some(expr))
+    }
+
+    pub fn string(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(Expr::as_str)
+    }
+
+    /// The blocks of the type `kind`, like `module`.
+    pub fn blocks<'a>(&'a self, kind: &'a str) -> impl Iterator<Item = &'a Block> {
+        self.blocks.iter().filter(move |block| block.kind == kind)
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        s.chars()
+            .enumerate()
+            .all(|(i, c)| self.chars.get(self.pos + i) == Some(&c))
+    }
+
+    /// Skips spaces and comments, and newlines unless `inline`.
+    fn skip_space(&mut self, inline: bool) {
+        while let Some(c) = self.peek() {
+            if c == '\n' && inline {
+                return;
+            } else if c.is_whitespace() {
+                self.pos += 1;
+            } else if c == '#' || self.starts_with("//") {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.pos += 1;
+                }
+            } else if self.starts_with("/*") {
+                self.pos += 2;
+                while self.peek().is_some() && !self.starts_with("*/") {
+                    self.pos += 1;
+                }
+                self.pos += 2;
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    /// A quoted string, at its opening quote. Interpolations like `${var.x}`
+    /// are kept as they are.
+    fn string(&mut self) -> String {
+        self.pos += 1;
+        let mut ret = String::new();
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' => {
+                    let escaped = self.peek().unwrap_or('\\');
+                    self.pos += 1;
+                    ret.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        c => c,
+                    });
+                }
+                '"' if depth == 0 => break,
+                '\n' if depth == 0 => break,
+                '$' | '%' if self.peek() == Some('{') => {
+                    depth += 1;
+                    ret.push(c);
+                    ret.push('{');
+                    self.pos += 1;
+                }
+                '}' if depth > 0 => {
+                    depth -= 1;
+                    ret.push(c);
+                }
+                c => ret.push(c),
+            }
+        }
+        ret
+    }
+
+    /// Skips a heredoc like `<<EOF` or `<<-EOF`, at its `<<`.
+    fn heredoc(&mut self) {
+        self.pos += 2;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        let marker = self.identifier();
+        while self.peek().is_some() {
+            while self.peek().is_some_and(|c| c != '\n') {
+                self.pos += 1;
+            }
+            self.pos += 1;
+            self.skip_space(true);
+            if !marker.is_empty() && self.starts_with(&marker) {
+                self.pos += marker.chars().count();
+                return;
+            }
+        }
+    }
+
+    /// Skips the rest of an expression, up to a newline, a comma or a closing
+    /// bracket at its level.
+    fn skip_expression(&mut self, in_list: bool) {
+        let mut depth = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                '"' => {
+                    self.string();
+                    continue;
+                }
+                '<' if self.starts_with("<<") && depth == 0 => {
+                    self.heredoc();
+                    continue;
+                }
+                '#' => {
+                    self.skip_space(true);
+                    continue;
+                }
+                '/' if self.starts_with("//") || self.starts_with("/*") => {
+                    self.skip_space(true);
+                    continue;
+                }
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' if depth == 0 => return,
+                ')' | ']' | '}' => depth -= 1,
+                ',' if depth == 0 && in_list => return,
+                '\n' if depth == 0 && !in_list => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn expression(&mut self, in_list: bool) -> Expr {
+        self.skip_space(!in_list);
+        let expr = match self.peek() {
+            Some('"') => Expr::Str(self.string()),
+            Some('[') => {
+                self.pos += 1;
+                let mut items = vec![];
+                loop {
+                    self.skip_space(false);
+                    match self.peek() {
+                        Some(']') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        None => break,
+                        _ => {}
+                    }
+                    let start = self.pos;
+                    items.push(self.expression(true));
+                    self.skip_space(false);
+                    if self.peek() == Some(',') || self.pos == start {
+                        self.pos += 1;
+                    }
+                }
+                Expr::List(items)
+            }
+            _ => {
+                self.skip_expression(in_list);
+                return Expr::Other;
+            }
+        };
+
+        // Like `"a" + var.b`
+        self.skip_space(!in_list);
+        match self.peek() {
+            Some(',' | ']' | ')' | '}') | None => expr,
+            Some('\n') if !in_list => expr,
+            _ => {
+                self.skip_expression(in_list);
+                Expr::Other
+            }
+        }
+    }
+
+    /// The attributes and blocks up to the closing brace of the body.
+    fn body(&mut self) -> Body {
+        let mut body = Body::default();
+        loop {
+            self.skip_space(false);
+            match self.peek() {
+                Some('}') => {
+                    self.pos += 1;
+                    return body;
+                }
+                None => return body,
+                _ => {}
+            }
+
+            let name = self.identifier();
+            if name.is_empty() {
+                // Not HCL
+                self.pos += 1;
+                continue;
+            }
+            self.skip_space(true);
+            if self.peek() == Some('=') && !self.starts_with("==") {
+                self.pos += 1;
+                let expr = self.expression(false);
+                body.attributes.push((name, expr));
+                continue;
+            }
+
+            let mut labels = vec![];
+            loop {
+                self.skip_space(true);
+                match self.peek() {
+                    Some('"') => labels.push(self.string()),
+                    Some(c) if c.is_alphanumeric() || c == '_' => labels.push(self.identifier()),
+                    _ => break,
+                }
+            }
+            if self.peek() == Some('{') {
+                self.pos += 1;
+                let block_body = self.body();
+                body.blocks.push(Block {
+                    kind: name,
+                    labels,
+                    body: block_body,
+                });
+            }
+        }
+    }
+}
+
+/// The top-level body of an HCL file.
+pub fn parse(content: &str) -> Body {
+    let mut parser = Parser {
+        chars: content.chars().collect(),
+        pos: 0,
+    };
+    parser.body()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks() {
+        let body = parse(
+            r#"
+# Network
+module "vpc" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "~> 5.0" // pinned
+  name    = "${var.prefix}-vpc"
+  azs     = ["eu-west-1a", "eu-west-1b"]
+  tags    = merge(var.tags, { "Name" = "vpc" })
+
+  policy = <<-EOT
+    { "module": "fake" }
+  EOT
+}
+
+/* A module in a single line */
+module "dns" { source = "./dns" }
+"#,
+        );
+
+        let modules = body.blocks("module").collect::<Vec<_>>();
+        assert_eq!(modules.len(), 2);
+        assert_eq!(modules[0].labels, vec!["vpc"]);
+        assert_eq!(
+            modules[0].body.string("source"),
+            Some("terraform-aws-modules/vpc/aws")
+        );
+        assert_eq!(modules[0].body.string("version"), Some("~> 5.0"));
+        assert_eq!(modules[0].body.string("name"), Some("${var.prefix}-vpc"));
+        assert_eq!(
+            modules[0].body.get("azs").unwrap().strings(),
+            vec!["eu-west-1a", "eu-west-1b"]
+        );
+        assert_eq!(modules[0].body.get("tags"), Some(&Expr::Other));
+        assert_eq!(modules[1].body.string("source"), Some("./dns"));
+    }
+}
@@ -0,0 +1,129 @@
+//! The providers locked by `terraform init` in `.terraform.lock.hcl`.
+
+use std::path::Path;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::{hcl, registry_purl};
+
+/// The checksums of a provider, the `zh:` hashes being the SHA-256 of its
+/// packages and the `h1:` hashes those of their contents.
+fn checksums(hashes: &[&str]) -> String {
+    hashes
+        .iter()
+        .map(|hash| match hash.split_once(':') {
+            Some(("zh", hex)) => format!("sha256:{}", hex.to_lowercase()),
+            _ => hash.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Parses a `.terraform.lock.hcl`, the `provider` blocks being labelled by
+/// the address of the provider, like `registry.terraform.io/hashicorp/aws`.
+pub fn parse(content: &str) -> Vec<DependentPackage> {
+    let body = hcl::parse(content);
+
+    let mut ret = vec![];
+    for provider in body.blocks("provider") {
+        let address = match provider.labels.first() {
+            Some(address) => address.as_str(),
+            None => continue,
+        };
+        let (host, address) = match address.matches('/').count() {
+            2 => address
+                .split_once('/')
+                .map_or((None, address), |(host, rest)| (Some(host), rest)),
+            _ => (None, address),
+        };
+        let version = provider.body.string("version");
+
+        let mut purl = match registry_purl(host, address, version) {
+            Some(purl) => purl,
+            None => {
+                log::warn!("Invalid provider address {}", address);
+                continue;
+            }
+        };
+        let hashes = provider
+            .body
+            .get("hashes")
+            .map(hcl::Expr::strings)
+            .unwrap_or_default();
+        if !hashes.is_empty() && purl.add_qualifier("checksum", checksums(&hashes)).is_err() {
+            continue;
+        }
+
+        ret.push(DependentPackage {
+            purl: purl.to_string(),
+            requirement: version.unwrap_or_default().to_string(),
+            is_runtime: true,
+            is_resolved: version.is_some(),
+            relation: maplit::hashset! {Relation::Direct},
+            ..Default::default()
+        });
+    }
+    ret
+}
+
+pub struct TerraformLock {}
+
+impl TerraformLock {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for TerraformLock {
+    fn get_name(&self) -> String {
+        "terraform".into()
+    }
+
+    fn get_identifier(&self) -> String {
+        "terraform-lock".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "HCL".into(),
+            dependencies: parse(&content),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &[".terraform.lock.hcl"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn terraform_lock() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/terraform/.terraform.lock.hcl"
+        ));
+
+        let package = TerraformLock::new().recognize(filepath).await.unwrap();
+        let deps = &package.dependencies;
+        assert_eq!(deps.len(), 2);
+
+        assert_eq!(
+            deps[0].purl,
+            "pkg:terraform/hashicorp/aws@5.31.0?checksum=h1:Gdgq5a2Y1dOrBwDqg3PWJlxWp0XxP7zcVpwF7jB8yVk%3D,sha256:0d9e9b5e6b7c8a1f2e3d4c5b6a79880f1e2d3c4b5a6978869504132231f0e9d8,sha256:1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c4d5e6f708192a3b4c5d6e7f809"
+        );
+        assert_eq!(deps[0].requirement, "5.31.0");
+        assert!(deps[0].is_resolved);
+        assert_eq!(
+            deps[1].purl,
+            "pkg:terraform/integrations/github@5.42.0?repository_url=registry.opentofu.org"
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Terraform configurations: the providers locked in `.terraform.lock.hcl`,
+//! and the modules called by the `module` blocks of `.tf` files.
+
+use packageurl::PackageUrl;
+
+pub mod hcl;
+pub mod lock;
+pub mod module;
+
+/// The host of the providers and modules of addresses without one.
+const DEFAULT_REGISTRY: &str = "registry.terraform.io";
+
+/// The purl of a package of a registry, by its address without the host,
+/// like `hashicorp/aws` for a provider or `terraform-aws-modules/vpc/aws`
+/// for a module, with the host as `repository_url` unless it is the default
+/// one.
+pub fn registry_purl(
+    host: Option<&str>,
+    address: &str,
+    version: Option<&str>,
+) -> Option<PackageUrl<'static>> {
+    let (namespace, name) = address.rsplit_once('/')?;
+    let mut purl = PackageUrl::new("terraform", name.to_string()).ok()?;
+    purl.with_namespace(namespace.to_string());
+    if let Some(version) = version {
+        purl.with_version(version.to_string());
+    }
+    if let Some(host) = host.filter(|host| !host.eq_ignore_ascii_case(DEFAULT_REGISTRY)) {
+        purl.add_qualifier("repository_url", host.to_string())
+            .ok()?;
+    }
+    Some(purl)
+}
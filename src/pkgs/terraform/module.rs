@@ -0,0 +1,203 @@
+//! The modules called by the `module` blocks of `.tf` files, from a registry
+//! or a git repository.
+
+use std::path::Path;
+
+use packageurl::PackageUrl;
+
+use crate::error::SourcePkgError;
+use crate::types::{DependentPackage, Package, Relation};
+use crate::PackageManifest;
+
+use super::{hcl, registry_purl};
+
+/// Splits the `?ref=` of a source, and its subdirectory like
+/// `//modules/vpc`.
+fn split_ref(source: &str) -> (&str, Option<&str>) {
+    let (location, query) = source.split_once('?').unwrap_or((source, ""));
+    let git_ref = query.split('&').find_map(|kv| kv.strip_prefix("ref="));
+    // After the scheme, like `https://`
+    let after_scheme = location.find("://").map_or(0, |i| i + 3);
+    let location = match location[after_scheme..].find("//") {
+        Some(i) => &location[..after_scheme + i],
+        None => location,
+    };
+    (location, git_ref)
+}
+
+/// The last segment of a repository URL, without `.git`.
+fn repo_name(url: &str) -> &str {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(url);
+    name.strip_suffix(".git").unwrap_or(name)
+}
+
+/// The purl of the git repository `url` at `git_ref`, `pkg:github` for the
+/// repositories of GitHub.
+fn git_purl(url: &str, git_ref: Option<&str>) -> Option<String> {
+    let github = url
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.strip_prefix("git@github.com:"))
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+        .or_else(|| url.strip_prefix("github.com/"));
+    if let Some((owner, repo)) = github.and_then(|path| path.split_once('/')) {
+        let mut purl = PackageUrl::new("github", repo_name(repo)).ok()?;
+        purl.with_namespace(owner);
+        if let Some(git_ref) = git_ref {
+            purl.with_version(git_ref);
+        }
+        return Some(purl.to_string());
+    }
+
+    let url = match url.strip_prefix("bitbucket.org/") {
+        Some(path) => format!("https://bitbucket.org/{}", path),
+        None => url.to_string(),
+    };
+    let mut purl = PackageUrl::new("generic", repo_name(&url)).ok()?;
+    if let Some(git_ref) = git_ref {
+        purl.with_version(git_ref);
+    }
+    let vcs_url = match git_ref {
+        Some(git_ref) => format!("git+{}@{}", url, git_ref),
+        None => format!("git+{}", url),
+    };
+    purl.add_qualifier("vcs_url", vcs_url).ok()?;
+    Some(purl.to_string())
+}
+
+/// Whether a source is the address of a module of a registry, like
+/// `terraform-aws-modules/vpc/aws` or `app.terraform.io/corp/vpc/aws`.
+fn is_registry_address(location: &str) -> bool {
+    let segments = location.split('/').collect::<Vec<_>>();
+    let is_name = |s: &&str| {
+        !s.is_empty()
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    };
+    match segments.as_slice() {
+        [_, _, _] => segments.iter().all(is_name),
+        [host, rest @ ..] if rest.len() == 3 => host.contains('.') && rest.iter().all(is_name),
+        _ => false,
+    }
+}
+
+/// The module called by the block `name` from `source`, at `version` for
+/// those of a registry. Local modules are part of the configuration.
+fn module_dep(name: &str, source: &str, version: Option<&str>) -> Option<DependentPackage> {
+    if source.starts_with("./") || source.starts_with("../") {
+        return None;
+    }
+
+    let (location, git_ref) = split_ref(source);
+    let (purl, requirement) = if is_registry_address(location) {
+        let (host, address) = match location.matches('/').count() {
+            3 => location
+                .split_once('/')
+                .map_or((None, location), |(host, rest)| (Some(host), rest)),
+            _ => (None, location),
+        };
+        (
+            registry_purl(host, address, None)?.to_string(),
+            version.unwrap_or_default(),
+        )
+    } else if let Some(url) = location.strip_prefix("git::") {
+        (git_purl(url, git_ref)?, git_ref.unwrap_or_default())
+    } else if location.starts_with("github.com/")
+        || location.starts_with("git@github.com:")
+        || location.starts_with("bitbucket.org/")
+    {
+        (git_purl(location, git_ref)?, git_ref.unwrap_or_default())
+    } else {
+        // Archives, like `https://example.com/vpc.zip` or `s3::https://...`
+        let url = location.split_once("::").map_or(location, |(_, url)| url);
+        let mut purl = PackageUrl::new("generic", name).ok()?;
+        purl.add_qualifier("download_url", url).ok()?;
+        (purl.to_string(), "")
+    };
+
+    let is_resolved =
+        git_ref.is_some_and(|r| r.len() == 40 && r.chars().all(|c| c.is_ascii_hexdigit()));
+    Some(DependentPackage {
+        purl,
+        requirement: requirement.to_string(),
+        is_runtime: true,
+        is_resolved,
+        relation: maplit::hashset! {Relation::Direct},
+        ..Default::default()
+    })
+}
+
+/// Reads the `module` blocks of a `.tf` file.
+pub fn parse(content: &str) -> Vec<DependentPackage> {
+    let body = hcl::parse(content);
+    body.blocks("module")
+        .filter_map(|module| {
+            let name = module.labels.first()?;
+            let source = module.body.string("source")?;
+            module_dep(name, source, module.body.string("version"))
+        })
+        .collect()
+}
+
+pub struct TerraformModules {}
+
+impl TerraformModules {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait::async_trait]
+impl PackageManifest for TerraformModules {
+    fn get_name(&self) -> String {
+        "terraform".into()
+    }
+
+    async fn recognize(&self, path: &Path) -> Result<Package, SourcePkgError> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Package {
+            primary_language: "HCL".into(),
+            dependencies: parse(&content),
+            ..Default::default()
+        })
+    }
+
+    fn file_name_patterns(&self) -> &'static [&'static str] {
+        &["*.tf"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn modules() {
+        let filepath = Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/terraform/main.tf"
+        ));
+
+        let package = TerraformModules::new().recognize(filepath).await.unwrap();
+        let deps = &package.dependencies;
+        assert_eq!(
+            deps.iter().map(|d| d.purl.as_str()).collect::<Vec<_>>(),
+            vec![
+                "pkg:terraform/terraform-aws-modules/vpc/aws",
+                "pkg:terraform/corp/eks/aws?repository_url=app.terraform.io",
+                "pkg:generic/network@v1.2.0?vcs_url=git+https://example.com/infra/network.git%40v1.2.0",
+                "pkg:github/cloudposse/terraform-null-label@0.25.0",
+                "pkg:github/corp/tf-dns@5f7a3c2e9b1d4a6c8e0f2b4d6a8c0e2f4b6d8a0c",
+                "pkg:generic/artifacts?download_url=https://example.com/modules/artifacts.zip",
+            ]
+        );
+        assert_eq!(deps[0].requirement, "~> 5.0");
+        assert!(!deps[0].is_resolved);
+        assert_eq!(deps[2].requirement, "v1.2.0");
+        assert!(deps[4].is_resolved);
+        assert_eq!(deps[0].relation, maplit::hashset! {Relation::Direct});
+    }
+}
@@ -44,6 +44,11 @@ pub struct DependentPackage {
     /// parents, in purl format
     #[serde(default)]
     pub parents: HashSet<String>,
+
+    /// The declared license of this dependency, when the manifest records
+    /// it, like the package databases of operating systems.
+    #[serde(default)]
+    pub declared_license: String,
 }
 
 impl Default for DependentPackage {
@@ -58,6 +63,7 @@ impl Default for DependentPackage {
             reachable: Default::default(),
             relation: HashSet::new(),
             parents: HashSet::new(),
+            declared_license: String::new(),
         }
     }
 }
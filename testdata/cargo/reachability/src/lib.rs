@@ -0,0 +1,12 @@
+use anyhow::Result;
+
+pub fn parse(input: &str) -> Result<serde_json::Value> {
+    let re = fancy::Regex::new(r"\w+")?;
+    let _ = re.is_match(input);
+    Ok(serde_json::from_str(input)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use test_only::fixture;
+}
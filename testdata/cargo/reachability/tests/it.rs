@@ -0,0 +1,5 @@
+#[test]
+fn parses() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(dir.path().exists());
+}
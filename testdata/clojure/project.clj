@@ -0,0 +1,13 @@
+(def clojure-version "1.11.1")
+
+(defproject com.example/app "0.1.0-SNAPSHOT"
+  :description "An example app"
+  :dependencies [[org.clojure/clojure ~clojure-version]
+                 [ring/ring-core "1.10.0" :exclusions [commons-io]]
+                 [cheshire "5.12.0" :optional true]
+                 [midje "1.10.9" :scope "test"]]
+  :plugins [[lein-ring "0.12.6"]]
+  :profiles {:dev {:dependencies [[ring/ring-mock "0.4.0"]]}
+             :provided {:dependencies [[javax.servlet/servlet-api "2.5"]]}
+             :uberjar {:aot :all}}
+  :jvm-opts ^:replace ["-Xmx1g"])
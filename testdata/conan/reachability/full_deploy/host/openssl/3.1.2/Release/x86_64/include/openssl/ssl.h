@@ -0,0 +1 @@
+/* ssl.h */
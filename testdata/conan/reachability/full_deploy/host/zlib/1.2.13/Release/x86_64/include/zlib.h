@@ -0,0 +1 @@
+/* zlib.h */
@@ -0,0 +1,12 @@
+#include <vector>
+#include <zlib.h>
+#include <openssl/ssl.h>
+#include <spdlog/spdlog.h>
+#include <app/config.h>
+#include "util.h"
+
+int main() {
+    std::vector<int> v;
+    spdlog::info("{}", zlibVersion());
+    return SSL_library_init();
+}
@@ -0,0 +1,31 @@
+defmodule MyApp.MixProject do
+  use Mix.Project
+
+  def project do
+    [
+      app: :my_app,
+      version: "0.1.0",
+      elixir: "~> 1.14",
+      start_permanent: Mix.env() == :prod,
+      deps: deps()
+    ]
+  end
+
+  def application do
+    [extra_applications: [:logger]]
+  end
+
+  # Run "mix help deps" to learn about dependencies.
+  defp deps do
+    [
+      {:phoenix, "~> 1.7.10"},
+      {:phoenix_live_reload, "~> 1.2", only: :dev},
+      {:floki, ">= 0.30.0", only: [:dev, :test]},
+      {:esbuild, "~> 0.8", runtime: Mix.env() == :dev},
+      # {:commented, "~> 1.0"},
+      {:jason, "~> 1.2", optional: true},
+      {:ecto, github: "elixir-ecto/ecto", tag: "v3.11.0"},
+      {:my_other_app, in_umbrella: true}
+    ]
+  end
+end
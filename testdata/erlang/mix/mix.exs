@@ -0,0 +1,16 @@
+defmodule MyApp.MixProject do
+  use Mix.Project
+
+  def project do
+    [app: :my_app, version: "0.1.0", deps: deps()]
+  end
+
+  defp deps do
+    [
+      {:plug_cowboy, "~> 2.6"},
+      {:phoenix, github: "phoenixframework/phoenix", branch: "main"},
+      {:local_lib, path: "../local_lib"},
+      {:credo, "~> 1.7", only: [:dev, :test], runtime: false}
+    ]
+  end
+end
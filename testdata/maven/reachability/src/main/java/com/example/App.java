@@ -0,0 +1,9 @@
+package com.example;
+
+import com.google.gson.Gson;
+
+public class App {
+    public static void main(String[] args) {
+        System.out.println(new Gson().toJson(args));
+    }
+}
@@ -0,0 +1,26 @@
+{
+  description = "A dev shell";
+
+  inputs = {
+    nixpkgs.url = "github:NixOS/nixpkgs/nixos-unstable";
+    # Pinned for the toolchain
+    # old.url = "github:NixOS/nixpkgs/nixos-22.05";
+    flake-utils.url = "github:numtide/flake-utils";
+    rust-overlay = {
+      url = "github:oxalica/rust-overlay";
+      inputs.nixpkgs.follows = "nixpkgs";
+    };
+    tools = {
+      flake = false;
+      url = "git+https://example.com/tools.git?ref=main";
+    };
+    registry.url = "nixpkgs/nixos-23.11";
+    local.url = "path:./local";
+  };
+  inputs.src.url = "https://example.com/src-1.0.tar.gz";
+
+  outputs = { self, nixpkgs, flake-utils, ... }:
+    flake-utils.lib.eachDefaultSystem (system: {
+      devShells.default = nixpkgs.legacyPackages.${system}.mkShell { };
+    });
+}
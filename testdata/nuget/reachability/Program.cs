@@ -0,0 +1,5 @@
+using Microsoft.Extensions.Logging;
+using Json = Newtonsoft.Json.JsonConvert;
+
+var items = new List<string> { "a", "b" };
+Log.Information(Json.SerializeObject(items));
@@ -0,0 +1,11 @@
+namespace App.Services;
+
+public class Repository
+{
+    private readonly ILogger<Repository> _logger;
+
+    public Repository(ILogger<Repository> logger) => _logger = logger;
+
+    public IEnumerable<T> All<T>(System.Data.IDbConnection connection) =>
+        Dapper.SqlMapper.Query<T>(connection, "SELECT 1");
+}
@@ -0,0 +1,2 @@
+// <auto-generated/>
+global using global::Polly;
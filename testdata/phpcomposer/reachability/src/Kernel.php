@@ -0,0 +1,16 @@
+<?php
+
+namespace App;
+
+use Monolog\Logger;
+use Twig_Environment;
+
+final class Kernel
+{
+    public function boot(Twig_Environment $twig): Logger
+    {
+        $client = new \GuzzleHttp\Client();
+
+        return new Logger('app');
+    }
+}
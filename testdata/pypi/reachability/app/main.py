@@ -0,0 +1,9 @@
+import importlib
+
+import requests
+from PIL import Image
+from .views import index
+
+from tinyplug.hooks import register
+
+config = importlib.import_module("yaml")
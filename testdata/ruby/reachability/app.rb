@@ -0,0 +1,11 @@
+require "bundler"
+Bundler.require(:development)
+
+require "sinatra"
+require "net/http/persistent"
+require "restclient"
+require_relative "lib/helpers"
+
+get "/" do
+  Faraday::Connection.new.get("https://example.com").body
+end
@@ -0,0 +1 @@
+module Helpers; end
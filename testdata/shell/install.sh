@@ -0,0 +1,32 @@
+#!/usr/bin/env bash
+set -euo pipefail
+
+PKG=httpie
+
+sudo apt-get update
+sudo -E apt-get install -y --no-install-recommends \
+    curl \
+    libssl-dev=1.1* \
+    git=1:2.39.2-1.1 \
+    && rm -rf /var/lib/apt/lists/*
+
+apk add --no-cache openssl=3.1.4-r0
+
+pip install --no-cache-dir -r requirements.txt requests==2.31.0 "uvicorn[standard]>=0.20"
+pip install "$PKG"
+pip install ./dist/wheel-0.1.0-py3-none-any.whl
+python3 -m pip install black==23.12.1
+
+npm i -g typescript@5.3.3 @angular/cli@^17
+yarn global add pm2@5.3.0
+
+gem install bundler -v 2.4.22 --no-document
+gem install rails:7.1.2
+
+go install golang.org/x/tools/gopls@v0.14.2
+go install github.com/go-delve/delve/cmd/dlv@latest
+
+cargo install ripgrep --version 14.0.3 --locked
+
+curl --proto '=https' --tlsv1.2 -sSf https://sh.rustup.rs | sh -s -- -y
+bash -c "$(curl -fsSL https://raw.githubusercontent.com/nvm-sh/nvm/v0.39.7/install.sh)"
@@ -0,0 +1,16 @@
+# This file is maintained automatically by "terraform init".
+# Manual edits may be lost in future updates.
+
+provider "registry.terraform.io/hashicorp/aws" {
+  version     = "5.31.0"
+  constraints = "~> 5.0"
+  hashes = [
+    "h1:Gdgq5a2Y1dOrBwDqg3PWJlxWp0XxP7zcVpwF7jB8yVk=",
+    "zh:0d9e9b5e6b7c8a1f2e3d4c5b6a79880f1e2d3c4b5a6978869504132231f0e9d8",
+    "zh:1A2B3C4D5E6F708192A3B4C5D6E7F8091A2B3C4D5E6F708192A3B4C5D6E7F809",
+  ]
+}
+
+provider "registry.opentofu.org/integrations/github" {
+  version = "5.42.0"
+}
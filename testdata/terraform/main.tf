@@ -0,0 +1,50 @@
+terraform {
+  required_version = ">= 1.5"
+
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = "~> 5.0"
+    }
+  }
+}
+
+provider "aws" {
+  region = var.region
+}
+
+module "vpc" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "~> 5.0"
+
+  name = "${var.prefix}-vpc"
+  cidr = "10.0.0.0/16"
+  azs  = ["eu-west-1a", "eu-west-1b"]
+}
+
+module "eks" {
+  source  = "app.terraform.io/corp/eks/aws"
+  version = "2.1.0"
+  vpc_id  = module.vpc.vpc_id
+}
+
+module "network" {
+  source = "git::https://example.com/infra/network.git//modules/core?ref=v1.2.0"
+}
+
+module "label" {
+  source = "github.com/cloudposse/terraform-null-label?ref=0.25.0"
+}
+
+module "dns" {
+  source = "git::git@github.com:corp/tf-dns.git?ref=5f7a3c2e9b1d4a6c8e0f2b4d6a8c0e2f4b6d8a0c"
+}
+
+module "artifacts" {
+  source = "s3::https://example.com/modules/artifacts.zip"
+}
+
+# Part of this configuration
+module "local" {
+  source = "./modules/local"
+}